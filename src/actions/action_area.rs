@@ -9,8 +9,12 @@ use bevy::{
         },
     },
     math::Vec3Swizzles,
-    prelude::{Commands, Component, Entity, GlobalTransform, Query, Vec2, With},
+    prelude::{
+        Commands, Component, Entity, EventReader, GlobalTransform, IVec2, Query, Res, Vec2, With,
+    },
     reflect::Reflect,
+    time::{Time, Timer, TimerMode},
+    utils::HashSet,
 };
 use big_brain::{
     prelude::ScorerBuilder,
@@ -18,7 +22,17 @@ use big_brain::{
     thinker::{Actor, ScorerSpan},
 };
 
-use crate::pathfinding::{Path, Pathfinding};
+use crate::{
+    labor::job::{
+        BlacklistedWorkers, EligibleWorkers, JobPriorityParam, DEFAULT_JOB_PRIORITY,
+        MAX_JOB_PRIORITY, MIN_JOB_PRIORITY,
+    },
+    path_request::{
+        failed_path_entities, PathFailedEvent, PathRequest, PathVersion, PathfindingSnapshot,
+    },
+    pathfinding::Path,
+    terrain::TerrainParam,
+};
 
 #[derive(Component, Clone, Reflect, Debug)]
 pub struct ActionArea(pub Vec<Vec2>);
@@ -92,7 +106,8 @@ where
 {
     action_query: Query<'w, 's, (Entity, Read<T>), F>,
     action_pos_query: StaticSystemParam<'w, 's, <T as HasActionPosition>::PositionParam>,
-    pathfinding: Pathfinding<'w, 's>,
+    eligible_workers_query: Query<'w, 's, Read<EligibleWorkers>>,
+    blacklisted_workers_query: Query<'w, 's, Read<BlacklistedWorkers>>,
 }
 
 impl<'w, 's, T, F> ActionAreaParam<'_, '_, T, F>
@@ -100,23 +115,45 @@ where
     T: GlobalActionArea + Component,
     F: ReadOnlyWorldQuery + 'static,
 {
-    pub fn path_to_action_area(&self, actor_pos: Vec2, action: &T) -> Option<Path> {
-        self.global_action_area(action).and_then(|area| {
-            area.0
-                .iter()
-                .flat_map(|tile| self.pathfinding.find_path(actor_pos, *tile))
-                .min_by_key(|path| path.0.len())
+    /// Actions `worker` is one of the [`EligibleWorkers`] of and isn't blacklisted from. Actions
+    /// whose eligible workers aren't known yet are left out.
+    pub fn eligible_actions(&self, worker: Entity) -> impl Iterator<Item = (Entity, &T)> + '_ {
+        self.action_query.iter().filter(move |(action_entity, _)| {
+            let eligible = self
+                .eligible_workers_query
+                .get(*action_entity)
+                .map_or(false, |eligible_workers| {
+                    eligible_workers.0.contains(&worker)
+                });
+            let blacklisted = self
+                .blacklisted_workers_query
+                .get(*action_entity)
+                .map_or(false, |blacklisted_workers| {
+                    blacklisted_workers.contains(worker)
+                });
+            eligible && !blacklisted
         })
     }
 
-    pub fn closest_action(&self, actor_pos: Vec2) -> Option<(Entity, &T, Path)> {
-        self.action_query
-            .iter()
-            .flat_map(|(entity, action)| {
-                self.path_to_action_area(actor_pos, action)
-                    .map(|path| (entity, action, path))
+    /// The action `worker` is eligible for whose action area contains `tile_pos`, e.g. the end
+    /// of a path to the closest action.
+    pub fn action_at_tile(
+        &self,
+        worker: Entity,
+        tile_pos: IVec2,
+        terrain: &TerrainParam,
+    ) -> Option<Entity> {
+        self.eligible_actions(worker)
+            .find(|(_, action)| {
+                self.global_action_area(action)
+                    .map_or(false, |action_area| {
+                        action_area
+                            .0
+                            .iter()
+                            .any(|pos| terrain.global_to_tile_pos(*pos) == Some(tile_pos))
+                    })
             })
-            .min_by_key(|(_, _, path)| path.0.len())
+            .map(|(action_entity, _)| action_entity)
     }
 
     pub fn global_action_area(&self, action: &T) -> Option<ActionArea> {
@@ -126,45 +163,168 @@ where
     }
 }
 
-/// Checks if any action of type T is reachable for the actor.
+/// Score of a reachable job: at least 0.8 so it passes the worker thinkers' thresholds, higher for
+/// higher priorities, with shorter paths breaking ties between jobs of the same priority.
+fn reachable_job_score(priority: u32, path_length: usize) -> f32 {
+    let priority = priority.clamp(MIN_JOB_PRIORITY, MAX_JOB_PRIORITY);
+    let path_score = 1.0 - (path_length as f32 / 100.0).min(1.0);
+    let levels = (MAX_JOB_PRIORITY - MIN_JOB_PRIORITY + 1) as f32;
+    0.8 + 0.2 * ((priority - MIN_JOB_PRIORITY) as f32 + path_score) / levels
+}
+
+/// Priority of the actions an [`ActionAreaReachable`] scorer last requested a path to.
+#[derive(Component, Debug)]
+pub struct ReachableActionPriority(u32);
+
+/// Seconds an [`ActionAreaReachable`] scorer keeps its result while nothing changes, before it
+/// looks for the closest action again.
+pub const RECHECK_REACHABLE_SECONDS: f32 = 2.;
+
+/// What an [`ActionAreaReachable`] scorer last looked for reachable actions on: the
+/// [`PathfindingSnapshot`] version and the eligible actions.
+#[derive(Component, Debug)]
+pub struct ReachableRecheck {
+    version: u64,
+    actions: HashSet<Entity>,
+    timer: Timer,
+}
+
+/// Checks if any action of type T the actor is eligible for is reachable.
+///
+/// Scores the path to the closest action found by the last request by the action's priority and
+/// the path length. Paths are requested to the actions of the highest priority first, falling
+/// back to lower priorities when none of them can be reached. The result is kept until the
+/// terrain or the eligible actions change, or for [`RECHECK_REACHABLE_SECONDS`].
 ///
 /// # Panics
 ///
 /// Panics if the actor does not have a GlobalTransform component.
 pub fn action_area_reachable<T, F>(
-    mut actor_query: Query<(&Actor, &mut Score, &ScorerSpan), With<ActionAreaReachable<T>>>,
+    mut commands: Commands,
+    mut actor_query: Query<
+        (
+            Entity,
+            &Actor,
+            &mut Score,
+            &ScorerSpan,
+            Option<&Path>,
+            Option<&PathRequest>,
+            Option<&ReachableActionPriority>,
+            Option<&mut ReachableRecheck>,
+        ),
+        With<ActionAreaReachable<T>>,
+    >,
     global_transform_query: Query<&GlobalTransform>,
+    mut path_failed_events: EventReader<PathFailedEvent>,
     action_area_param: ActionAreaParam<T, F>,
+    job_priority_param: JobPriorityParam,
+    terrain: TerrainParam,
+    snapshot: Res<PathfindingSnapshot>,
+    time: Res<Time>,
 ) where
     T: GlobalActionArea + Component,
     F: ReadOnlyWorldQuery + 'static,
 {
-    for (actor, mut score, span) in &mut actor_query {
+    let failed_paths = failed_path_entities(&mut path_failed_events);
+    for (
+        scorer_entity,
+        actor,
+        mut score,
+        span,
+        opt_path,
+        opt_request,
+        opt_priority,
+        mut opt_recheck,
+    ) in &mut actor_query
+    {
         let _guard = span.span().enter();
+
+        if let Some(recheck) = opt_recheck.as_mut() {
+            recheck.timer.tick(time.delta());
+        }
+
+        let failed = failed_paths.contains(&scorer_entity);
+        if failed {
+            score.set(0.0);
+        } else if let Some(path) = opt_path {
+            let priority = path
+                .0
+                .last()
+                .and_then(|&end_tile_pos| {
+                    action_area_param.action_at_tile(actor.0, end_tile_pos, &terrain)
+                })
+                .map_or(DEFAULT_JOB_PRIORITY, |action_entity| {
+                    job_priority_param.priority(action_entity)
+                });
+            score.set(reachable_job_score(priority, path.0.len()));
+        } else if opt_request.is_some() {
+            // Keep the last score until the search is done
+            continue;
+        }
+
+        let action_priorities: Vec<_> = action_area_param
+            .eligible_actions(actor.0)
+            .map(|(action_entity, action)| {
+                let priority = job_priority_param.priority(action_entity);
+                (action_entity, action, priority)
+            })
+            .collect();
+        let highest_priority_below = |below: u32| {
+            action_priorities
+                .iter()
+                .map(|(_, _, priority)| *priority)
+                .filter(|priority| *priority < below)
+                .max()
+        };
+        // After a failed search fall back to the next lower priority
+        let fallback_priority = opt_priority
+            .filter(|_| failed)
+            .and_then(|failed_priority| highest_priority_below(failed_priority.0));
+
+        if fallback_priority.is_none() {
+            // Otherwise keep the path or the failure until something changes. Paths blocked by
+            // terrain changes are dropped when the snapshot version changes.
+            let actions: HashSet<Entity> = action_priorities
+                .iter()
+                .map(|(action_entity, _, _)| *action_entity)
+                .collect();
+            let up_to_date = opt_recheck.as_deref().map_or(false, |recheck| {
+                !recheck.timer.finished()
+                    && recheck.version == snapshot.version()
+                    && recheck.actions == actions
+            });
+            if up_to_date {
+                continue;
+            }
+            commands.entity(scorer_entity).insert(ReachableRecheck {
+                version: snapshot.version(),
+                actions,
+                timer: Timer::from_seconds(RECHECK_REACHABLE_SECONDS, TimerMode::Once),
+            });
+        }
+
+        let mut scorer_commands = commands.entity(scorer_entity);
+        scorer_commands.remove::<(Path, PathVersion)>();
+        let Some(priority) = fallback_priority.or_else(|| highest_priority_below(u32::MAX)) else {
+            scorer_commands.remove::<ReachableActionPriority>();
+            score.set(0.0);
+            continue;
+        };
+
         let actor_pos = global_transform_query
             .get(actor.0)
             .expect("Actor should have a global transform")
             .translation()
             .xy();
-
-        let closest_action_path_length = action_area_param
-            .action_query
+        let action_area_positions: Vec<_> = action_priorities
             .iter()
-            .flat_map(|(_, action)| {
-                let path = action_area_param.path_to_action_area(actor_pos, action)?;
-                Some(path.0.len())
-            })
-            .min();
-
-        // Convert path length to a score clamped between 0 and 1,
-        // where 0 is the longest path and 1 is a path length of zero.
-
-        if let Some(closest_action_path_length) = closest_action_path_length {
-            let path_score = 1.0 - (closest_action_path_length as f32 / 100.0).min(0.9);
-            debug_assert!(path_score >= 0.1 && path_score <= 1.0);
-            score.set(path_score);
-        } else {
-            score.set(0.0);
-        }
+            .filter(|(_, _, action_priority)| *action_priority == priority)
+            .flat_map(|(_, action, _)| action_area_param.global_action_area(action))
+            .flat_map(|action_area| action_area.0)
+            .collect();
+        scorer_commands.insert((
+            ReachableActionPriority(priority),
+            PathRequest::to_any(actor_pos, action_area_positions),
+        ));
     }
 }
@@ -0,0 +1,215 @@
+use bevy::{
+    ecs::system::{SystemParam, SystemParamItem},
+    prelude::{
+        resource_exists, App, Commands, Component, Entity, GlobalTransform, IntoSystemConfigs,
+        Plugin, PreUpdate, Query, Res, Update, Vec2, With,
+    },
+    reflect::Reflect,
+    time::{Time, Timer, TimerMode},
+};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
+    thinker::{ActionSpan, Actor},
+    BigBrainSet,
+};
+use tracing::{debug, error, info};
+
+use crate::{
+    actions::action_area::{ActionArea, ActionAreaParam},
+    labor::{build_structure::UnderConstruction, job::Labor},
+    skills::{worker_speed, Skills},
+    structure::{PlacedStructure, StructureDefinitions},
+    terrain_settings::TerrainSettings,
+    util::get_entity_position,
+};
+
+use super::{
+    action_area::{HasActionArea, HasActionPosition},
+    move_to::{move_to_action_area, MoveToActionArea},
+};
+
+pub struct BuildPlugin;
+
+impl Plugin for BuildPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Build>()
+            .register_type::<BuildTarget>()
+            .register_type::<BuildTimer>()
+            .add_systems(
+                PreUpdate,
+                (move_to_action_area::<BuildTarget>, build).in_set(BigBrainSet::Actions),
+            )
+            .add_systems(
+                Update,
+                build_timer.run_if(resource_exists::<StructureDefinitions>()),
+            );
+    }
+}
+
+#[derive(Component, Clone, Debug, Reflect, ActionBuilder)]
+pub struct Build;
+
+/// Construction site the actor works on.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct BuildTarget(pub Entity);
+
+impl HasActionArea for BuildTarget {
+    fn action_area() -> ActionArea {
+        // Never on the site itself, it may turn into solid tiles
+        ActionArea(vec![
+            // West
+            Vec2::new(-16., 0.),
+            // East
+            Vec2::new(16., 0.),
+            // South
+            Vec2::new(0., -16.),
+            // Southwest
+            Vec2::new(-16., -16.),
+            // Southeast
+            Vec2::new(16., -16.),
+        ])
+    }
+}
+
+#[derive(SystemParam)]
+pub struct BuildActionSystemParam<'w, 's> {
+    structure_query: Query<'w, 's, &'static PlacedStructure>,
+    terrain_settings: Option<Res<'w, TerrainSettings>>,
+}
+
+impl HasActionPosition for BuildTarget {
+    type PositionParam = BuildActionSystemParam<'static, 'static>;
+
+    fn action_pos(
+        &self,
+        build_action_params: &SystemParamItem<BuildActionSystemParam>,
+    ) -> Option<Vec2> {
+        let structure = build_action_params.structure_query.get(self.0).ok()?;
+        build_action_params
+            .terrain_settings
+            .as_deref()
+            .map(|terrain_settings| terrain_settings.tile_to_world(structure.origin))
+    }
+}
+
+fn at_work_area(actor_position: Vec2, work_area: &ActionArea) -> bool {
+    work_area
+        .0
+        .iter()
+        .any(|&site| Vec2::new(site.x, 0.).distance(Vec2::new(actor_position.x, 0.)) < 5.)
+}
+
+/// Seconds between two build ticks, before the builder's construction speed is applied. Every tick
+/// adds a second of work to the construction site.
+pub const BUILD_TICK_SECONDS: f32 = 1.;
+
+#[derive(Component, Debug, Reflect)]
+pub struct BuildTimer {
+    pub construction_site: Entity,
+    pub timer: Timer,
+}
+
+fn build(
+    mut commands: Commands,
+    mut build_action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<Build>>,
+    global_transform_query: Query<&GlobalTransform>,
+    build_target_query: Query<&BuildTarget>,
+    build_timer_query: Query<&BuildTimer>,
+    structure_query: Query<(), With<PlacedStructure>>,
+    under_construction_query: Query<(), With<UnderConstruction>>,
+    action_area_param: ActionAreaParam<BuildTarget>,
+    skills_query: Query<&Skills>,
+) {
+    for (actor, mut action_state, span) in &mut build_action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Starting building");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                debug!("Building");
+                let Ok(build_target) = build_target_query.get(actor.0) else {
+                    error!("No build target");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                if !structure_query.contains(build_target.0) {
+                    info!("Construction site no longer exists");
+                    commands
+                        .entity(actor.0)
+                        .remove::<BuildTimer>()
+                        .remove::<BuildTarget>();
+                    *action_state = ActionState::Failure;
+                    continue;
+                }
+                if !under_construction_query.contains(build_target.0) {
+                    info!("Building finished");
+                    commands
+                        .entity(actor.0)
+                        .remove::<BuildTimer>()
+                        .remove::<BuildTarget>();
+                    *action_state = ActionState::Success;
+                    continue;
+                }
+
+                let actor_position = get_entity_position(&global_transform_query, actor.0);
+                let Some(work_area) = action_area_param.global_action_area(build_target) else {
+                    error!("Construction site has no action area");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                if at_work_area(actor_position, &work_area) {
+                    if !build_timer_query.contains(actor.0) {
+                        info!("Building started");
+                        let construction_speed =
+                            worker_speed(&skills_query, actor.0, Labor::Construction);
+                        commands.entity(actor.0).insert(BuildTimer {
+                            construction_site: build_target.0,
+                            timer: Timer::from_seconds(
+                                BUILD_TICK_SECONDS / construction_speed,
+                                TimerMode::Repeating,
+                            ),
+                        });
+                    }
+                } else {
+                    info!("Too far away to build");
+                    commands.entity(actor.0).remove::<BuildTimer>();
+                    *action_state = ActionState::Failure;
+                }
+            }
+            ActionState::Cancelled => {
+                info!("Building cancelled");
+                commands.entity(actor.0).remove::<BuildTimer>();
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn build_timer(
+    time: Res<Time>,
+    mut build_timer_query: Query<&mut BuildTimer>,
+    mut construction_site_query: Query<(&mut UnderConstruction, &PlacedStructure)>,
+    structure_definitions: Res<StructureDefinitions>,
+) {
+    for mut build_timer in &mut build_timer_query {
+        if !build_timer.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let Ok((mut under_construction, structure)) = construction_site_query.get_mut(build_timer.construction_site) else { continue; };
+        let Some(definition) = structure_definitions.get(&structure.name) else { continue; };
+        info!(construction_site = ?build_timer.construction_site, "Building tick");
+        under_construction.add_progress(definition.progress_per_second());
+    }
+}
+
+pub fn build_structure() -> StepsBuilder {
+    Steps::build()
+        .label("builder")
+        .step(MoveToActionArea::<BuildTarget>::builder())
+        .step(Build)
+}
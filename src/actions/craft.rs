@@ -0,0 +1,206 @@
+use bevy::{
+    ecs::system::{lifetimeless::SQuery, SystemParamItem},
+    math::Vec3Swizzles,
+    prelude::*,
+};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
+    thinker::{ActionSpan, Actor},
+    BigBrainSet,
+};
+
+use crate::{
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        move_to::{move_to_action_area, MoveToActionArea},
+    },
+    item::{spawn_item, ItemDefinitions},
+    labor::{
+        craft::{CraftOrder, Crafted},
+        job::Labor,
+    },
+    skills::{worker_speed, Skills},
+};
+
+pub struct CraftPlugin;
+
+impl Plugin for CraftPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Craft>()
+            .register_type::<CraftTarget>()
+            .register_type::<CraftTimer>()
+            .add_systems(
+                PreUpdate,
+                (move_to_action_area::<CraftTarget>, craft).in_set(BigBrainSet::Actions),
+            )
+            .add_systems(
+                Update,
+                craft_timer.run_if(resource_exists::<ItemDefinitions>()),
+            );
+    }
+}
+
+#[derive(Component, Clone, Debug, Reflect, ActionBuilder)]
+pub struct Craft;
+
+/// Craft order the actor works on.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct CraftTarget(pub Entity);
+
+impl HasActionArea for CraftTarget {
+    fn action_area() -> ActionArea {
+        ActionArea(vec![Vec2::ZERO])
+    }
+}
+
+impl HasActionPosition for CraftTarget {
+    type PositionParam = SQuery<&'static GlobalTransform>;
+
+    fn action_pos(
+        &self,
+        global_transform_query: &SystemParamItem<Self::PositionParam>,
+    ) -> Option<Vec2> {
+        global_transform_query
+            .get(self.0)
+            .map(|transform| transform.translation().xy())
+            .ok()
+    }
+}
+
+#[derive(Component, Debug, Reflect)]
+pub struct CraftTimer {
+    pub order: Entity,
+    pub timer: Timer,
+}
+
+fn craft(
+    mut commands: Commands,
+    mut craft_action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<Craft>>,
+    global_transform_query: Query<&GlobalTransform>,
+    craft_target_query: Query<&CraftTarget>,
+    craft_timer_query: Query<(), With<CraftTimer>>,
+    order_query: Query<(&CraftOrder, Option<&Crafted>)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+    skills_query: Query<&Skills>,
+) {
+    for (actor, mut action_state, span) in &mut craft_action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Starting crafting");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let Ok(craft_target) = craft_target_query.get(actor.0) else {
+                    error!("No craft target");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Ok((order, opt_crafted)) = order_query.get(craft_target.0) else {
+                    info!("Craft order no longer exists");
+                    commands
+                        .entity(actor.0)
+                        .remove::<CraftTimer>()
+                        .remove::<CraftTarget>();
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                if opt_crafted.is_some() {
+                    info!("Crafting finished");
+                    commands
+                        .entity(actor.0)
+                        .remove::<CraftTimer>()
+                        .remove::<CraftTarget>();
+                    *action_state = ActionState::Success;
+                    continue;
+                }
+                let Some(recipe) = item_definitions
+                    .as_deref()
+                    .and_then(|item_definitions| item_definitions.get(&order.item))
+                    .and_then(|definition| definition.recipe.as_ref())
+                else {
+                    error!(item = order.item.as_str(), "Ordered item has no recipe");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
+                let (Ok(actor_transform), Ok(order_transform)) = (
+                    global_transform_query.get(actor.0),
+                    global_transform_query.get(craft_target.0),
+                ) else {
+                    error!("Actor and craft order should have a global transform");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let distance = actor_transform
+                    .translation()
+                    .xy()
+                    .distance(order_transform.translation().xy());
+                if distance < 16. {
+                    if !craft_timer_query.contains(actor.0) {
+                        info!("Crafting started");
+                        let crafting_speed = worker_speed(&skills_query, actor.0, Labor::Crafting);
+                        commands.entity(actor.0).insert(CraftTimer {
+                            order: craft_target.0,
+                            timer: Timer::from_seconds(
+                                recipe.craft_time / crafting_speed,
+                                TimerMode::Once,
+                            ),
+                        });
+                    }
+                } else {
+                    info!("Too far away to craft");
+                    commands.entity(actor.0).remove::<CraftTimer>();
+                    *action_state = ActionState::Failure;
+                }
+            }
+            ActionState::Cancelled => {
+                info!("Crafting cancelled");
+                commands.entity(actor.0).remove::<CraftTimer>();
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Spawns the ordered item at the workshop once the crafting is done.
+fn craft_timer(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut craft_timer_query: Query<&mut CraftTimer>,
+    order_query: Query<(&CraftOrder, &GlobalTransform), Without<Crafted>>,
+    item_definitions: Res<ItemDefinitions>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for mut craft_timer in &mut craft_timer_query {
+        if !craft_timer.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let Ok((order, transform)) = order_query.get(craft_timer.order) else { continue; };
+        let Some(definition) = item_definitions.get(&order.item) else {
+            error!(item = order.item.as_str(), "Ordered item is not defined");
+            continue;
+        };
+        let item_entity = spawn_item(
+            &mut commands,
+            definition,
+            1,
+            transform.translation().xy(),
+            &mut materials,
+            &mut meshes,
+        );
+        info!(order = ?craft_timer.order, item = ?item_entity, "Crafted");
+        commands.entity(craft_timer.order).insert(Crafted);
+    }
+}
+
+pub fn craft_item() -> StepsBuilder {
+    Steps::build()
+        .label("crafter")
+        .step(MoveToActionArea::<CraftTarget>::builder())
+        .step(Craft)
+}
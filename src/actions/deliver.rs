@@ -1,38 +1,94 @@
 use bevy::{
+    ecs::system::SystemParamItem,
     prelude::{
-        App, BuildChildren, Commands, Component, Entity, GlobalTransform, IntoSystemConfigs,
-        Plugin, PreUpdate, Query,
+        App, Assets, ColorMaterial, Commands, Component, Entity, GlobalTransform,
+        IntoSystemConfigs, Mesh, Name, Plugin, PreUpdate, Query, Res, ResMut, Vec2, With,
     },
     reflect::Reflect,
 };
 use big_brain::{
-    prelude::{ActionBuilder, ActionState},
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
     thinker::{ActionSpan, Actor},
     BigBrainSet,
 };
 use tracing::{error, info};
 
+use crate::{
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        move_to::{move_to_action_area, MoveToActionArea},
+    },
+    inventory::{drop_items, transfer, Inventory, ItemStack},
+    item::ItemDefinitions,
+    labor::{build_structure::BuildingMaterialsNeeded, stockpile::Stockpile},
+    terrain_settings::TerrainSettings,
+};
+
 pub struct DeliverPlugin;
 
 impl Plugin for DeliverPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Deliver>()
-            .add_systems(PreUpdate, deliver.in_set(BigBrainSet::Actions));
+            .register_type::<DeliveryTarget>()
+            .add_systems(
+                PreUpdate,
+                (deliver, move_to_action_area::<DeliveryTarget>).in_set(BigBrainSet::Actions),
+            );
     }
 }
 
+/// Delivers the items of the actor's [`DeliveryTarget`] from its [`Inventory`].
+///
+/// Construction sites consume the items as building materials and stockpiles get them dropped on
+/// the tile the actor stands on. Other targets store them in their own inventory.
 #[derive(Component, Debug, Clone, Reflect, ActionBuilder)]
-pub struct Deliver {
-    pub load: Entity,
+pub struct Deliver;
+
+/// Up to `count` of `item` to deliver to `to`, from `position`.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct DeliveryTarget {
     pub to: Entity,
+    pub item: Name,
+    pub count: u32,
+    pub position: Vec2,
+}
+
+impl HasActionArea for DeliveryTarget {
+    fn action_area() -> ActionArea {
+        ActionArea(vec![Vec2::ZERO])
+    }
+}
+
+impl HasActionPosition for DeliveryTarget {
+    type PositionParam = ();
+
+    fn action_pos(&self, _: &SystemParamItem<Self::PositionParam>) -> Option<Vec2> {
+        Some(self.position)
+    }
+}
+
+pub fn deliver_items() -> StepsBuilder {
+    Steps::build()
+        .label("deliver_items")
+        .step(MoveToActionArea::<DeliveryTarget>::builder())
+        .step(Deliver)
 }
 
 fn deliver(
     mut commands: Commands,
-    mut deliver_query: Query<(&Actor, &mut Deliver, &mut ActionState, &ActionSpan)>,
+    mut deliver_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<Deliver>>,
+    delivery_target_query: Query<&DeliveryTarget>,
     global_transform_query: Query<&GlobalTransform>,
+    mut inventory_query: Query<&mut Inventory>,
+    mut materials_needed_query: Query<&mut BuildingMaterialsNeeded>,
+    stockpile_query: Query<&Stockpile>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+    terrain_settings: Option<Res<TerrainSettings>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
 ) {
-    for (actor, deliver, mut action_state, span) in &mut deliver_query {
+    for (actor, mut action_state, span) in &mut deliver_query {
         let _guard = span.span().enter();
 
         match *action_state {
@@ -42,23 +98,99 @@ fn deliver(
             }
             ActionState::Executing => {
                 info!("Delivering");
+                let Ok(deliver) = delivery_target_query.get(actor.0) else {
+                    error!("No delivery target");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
                 let actor_position = global_transform_query
                     .get(actor.0)
                     .unwrap()
                     .translation()
                     .truncate();
-                let destination_position = global_transform_query
-                    .get(deliver.to)
-                    .unwrap()
-                    .translation()
-                    .truncate();
 
-                if actor_position.distance(destination_position) < 16. {
-                    info!("Delivered");
-                    commands.entity(deliver.to).add_child(deliver.load);
+                // Stockpiles are delivered to from any of their tiles
+                let actor_tile_pos = terrain_settings
+                    .as_deref()
+                    .map(|terrain_settings| terrain_settings.world_to_tile(actor_position));
+                let opt_stockpile = stockpile_query.get(deliver.to).ok();
+                let in_reach = match (opt_stockpile, actor_tile_pos) {
+                    (Some(stockpile), Some(actor_tile_pos)) => stockpile.contains(actor_tile_pos),
+                    (Some(_), None) => false,
+                    (None, _) => actor_position.distance(deliver.position) < 16.,
+                };
+                if !in_reach {
+                    error!("Too far away to deliver");
+                    *action_state = ActionState::Failure;
+                    continue;
+                }
+
+                let delivered =
+                    if let Ok(mut materials_needed) = materials_needed_query.get_mut(deliver.to) {
+                        let Ok(mut inventory) = inventory_query.get_mut(actor.0) else {
+                            error!("Actor has no inventory");
+                            *action_state = ActionState::Failure;
+                            continue;
+                        };
+                        let delivered = inventory.remove(&deliver.item, deliver.count);
+                        materials_needed.deliver_resource(&deliver.item, delivered);
+                        delivered
+                    } else if let (
+                        Some(_),
+                        Some(actor_tile_pos),
+                        Some(item_definitions),
+                        Some(terrain_settings),
+                    ) = (
+                        opt_stockpile,
+                        actor_tile_pos,
+                        item_definitions.as_deref(),
+                        terrain_settings.as_deref(),
+                    ) {
+                        let Ok(mut inventory) = inventory_query.get_mut(actor.0) else {
+                            error!("Actor has no inventory");
+                            *action_state = ActionState::Failure;
+                            continue;
+                        };
+                        let count_before = inventory.count(&deliver.item);
+                        drop_items(
+                            &mut commands,
+                            &mut inventory,
+                            item_definitions,
+                            &ItemStack {
+                                item: deliver.item.clone(),
+                                count: deliver.count,
+                            },
+                            terrain_settings.tile_to_world(actor_tile_pos),
+                            &mut materials,
+                            &mut meshes,
+                        );
+                        count_before - inventory.count(&deliver.item)
+                    } else if let Some(item_definitions) = item_definitions.as_deref() {
+                        let Ok([mut from, mut to]) =
+                            inventory_query.get_many_mut([actor.0, deliver.to])
+                        else {
+                            error!("No inventory to deliver from or to");
+                            *action_state = ActionState::Failure;
+                            continue;
+                        };
+                        transfer(
+                            &mut from,
+                            &mut to,
+                            item_definitions,
+                            &deliver.item,
+                            deliver.count,
+                        )
+                    } else {
+                        error!("Item definitions not loaded");
+                        0
+                    };
+
+                if delivered > 0 {
+                    info!(item = deliver.item.as_str(), count = delivered, "Delivered");
+                    commands.entity(actor.0).remove::<DeliveryTarget>();
                     *action_state = ActionState::Success;
                 } else {
-                    error!("Too far away to deliver");
+                    error!(item = deliver.item.as_str(), "Nothing delivered");
                     *action_state = ActionState::Failure;
                 }
             }
@@ -19,7 +19,12 @@ use tracing::{debug, error, info, trace};
 
 use crate::{
     actions::action_area::ActionArea,
-    terrain::{TerrainParam, TerrainSet, TileDamageEvent, TileDestroyedEvent},
+    equipment::{worker_tool_speed, Equipment, ToolKind},
+    item::ItemDefinitions,
+    labor::job::Labor,
+    material::MaterialProperties,
+    skills::{worker_speed, Skills},
+    terrain::{TerrainParam, TerrainSet, TerrainTiles, TileDamageEvent, TileDestroyedEvent},
     util::get_entity_position,
 };
 
@@ -71,7 +76,6 @@ impl HasActionArea for DigTarget {
 
 #[derive(SystemParam)]
 pub struct DigActionSystemParam<'w, 's> {
-    tile_pos_query: Query<'w, 's, &'static TilePos>,
     terrain: TerrainParam<'w, 's>,
 }
 
@@ -83,15 +87,13 @@ impl HasActionPosition for DigTarget {
         dig_action_params: &SystemParamItem<DigActionSystemParam>,
     ) -> Option<Vec2> {
         dig_action_params
-            .tile_pos_query
-            .get(self.0)
-            .ok()
-            .map(|tile_pos| dig_action_params.terrain.tile_to_global_pos(*tile_pos))
+            .terrain
+            .get_entity_tile_pos(self.0)
+            .and_then(|tile_pos| dig_action_params.terrain.tile_to_global_pos(tile_pos))
     }
 }
 
-fn dig_work_area(tile_pos: TilePos, terrain: &TerrainParam) -> ActionArea {
-    let tile_global_pos = terrain.tile_to_global_pos(tile_pos);
+fn dig_work_area(tile_global_pos: Vec2) -> ActionArea {
     // All sites around the target tile
     // NW, NE, E, SE, S, SW, W
     ActionArea(vec![
@@ -113,6 +115,12 @@ fn at_work_area(actor_position: Vec2, work_area: &ActionArea) -> bool {
         .any(|&site| Vec2::new(site.x, 0.).distance(Vec2::new(actor_position.x, 0.)) < 5.)
 }
 
+/// Seconds between two dig ticks, before the material's dig time multiplier and the speed of the
+/// digger's skill and pickaxe are applied.
+pub const DIG_TICK_SECONDS: f32 = 1.;
+/// Damage dealt to the tile every dig tick.
+pub const DIG_DAMAGE: u32 = 20;
+
 #[derive(Component, Debug, Reflect)]
 pub struct DigTimer {
     pub tile_entity: Entity,
@@ -128,6 +136,10 @@ fn dig(
     terrain: TerrainParam,
     mut dig_timer_query: Query<&mut DigTimer>,
     mut tile_destroyed_event_reader: EventReader<TileDestroyedEvent>,
+    material_properties: Option<Res<MaterialProperties>>,
+    skills_query: Query<&Skills>,
+    equipment_query: Query<&Equipment>,
+    item_definitions: Option<Res<ItemDefinitions>>,
 ) {
     for (actor, mut action_state, span) in &mut dig_action_query {
         let _guard = span.span().enter();
@@ -150,12 +162,15 @@ fn dig(
                     *action_state = ActionState::Cancelled;
                     continue;
                 }
-                let Some(tile_pos) = terrain.get_entity_tile_pos(dig_target.0) else {
+                let Some((tile_pos, tile_global_pos)) = terrain
+                    .get_entity_tile_pos(dig_target.0)
+                    .and_then(|tile_pos| Some((tile_pos, terrain.tile_to_global_pos(tile_pos)?)))
+                else {
                     error!("Tile has no position");
                     *action_state = ActionState::Failure;
                     continue;
                 };
-                let work_area = dig_work_area(tile_pos, &terrain);
+                let work_area = dig_work_area(tile_global_pos);
                 // if we're close to a job site, we're done
                 if at_work_area(actor_position, &work_area) {
                     if let Ok(dig_timer) = dig_timer_query.get_mut(actor.0) {
@@ -172,9 +187,26 @@ fn dig(
                         }
                     } else {
                         info!("Digging started");
+                        let dig_time_multiplier = terrain
+                            .get_tile(tile_pos)
+                            .zip(material_properties.as_deref())
+                            .and_then(|(material, material_properties)| {
+                                material_properties.get(material)
+                            })
+                            .map_or(1., |material| material.dig_time_multiplier);
+                        let mining_speed = worker_speed(&skills_query, actor.0, Labor::Mining)
+                            * worker_tool_speed(
+                                &equipment_query,
+                                item_definitions.as_deref(),
+                                actor.0,
+                                ToolKind::Pickaxe,
+                            );
                         commands.entity(actor.0).insert(DigTimer {
                             tile_entity: dig_target.0,
-                            timer: Timer::from_seconds(1., TimerMode::Repeating),
+                            timer: Timer::from_seconds(
+                                DIG_TICK_SECONDS * dig_time_multiplier / mining_speed,
+                                TimerMode::Repeating,
+                            ),
                         });
                     }
                 } else {
@@ -203,7 +235,7 @@ fn dig_timer(
             info!(tile_entity = ?dig_timer.tile_entity, "Digging tick");
             tile_damage_event_writer.send(TileDamageEvent {
                 tile: dig_timer.tile_entity,
-                damage: 20,
+                damage: DIG_DAMAGE,
             });
         }
     }
@@ -0,0 +1,69 @@
+use bevy::prelude::{App, Commands, Component, IntoSystemConfigs, Plugin, PreUpdate, Query, With};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
+    thinker::{ActionSpan, Actor},
+    BigBrainSet,
+};
+use tracing::info;
+
+use crate::{
+    actions::build::BuildTarget,
+    labor::{build_structure::ConstructionJob, job::AssignedJob},
+};
+
+use super::build::build_structure;
+
+pub struct DoBuildJobPlugin;
+
+impl Plugin for DoBuildJobPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, set_build_target.in_set(BigBrainSet::Actions));
+    }
+}
+
+pub fn do_build_job() -> StepsBuilder {
+    info!("Building do_build_job action");
+    Steps::build()
+        .label("do_build_job")
+        .step(SetBuildTarget)
+        .step(build_structure())
+}
+
+#[derive(Component, Debug, Clone, ActionBuilder)]
+struct SetBuildTarget;
+
+fn set_build_target(
+    mut commands: Commands,
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<SetBuildTarget>>,
+    assigned_job_query: Query<&AssignedJob>,
+    construction_job_query: Query<&ConstructionJob>,
+) {
+    for (actor, mut action_state, span) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Setting build target");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let assigned_construction_job = assigned_job_query
+                    .get(actor.0)
+                    .and_then(|assigned_job| construction_job_query.get(assigned_job.0))
+                    .expect("Actor should have an assigned job");
+
+                info!(job=?assigned_construction_job, "Setting build target");
+                commands
+                    .entity(actor.0)
+                    .insert(BuildTarget(assigned_construction_job.0));
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Setting build target cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::{App, Commands, Component, IntoSystemConfigs, Plugin, PreUpdate, Query, With};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
+    thinker::{ActionSpan, Actor},
+    BigBrainSet,
+};
+use tracing::info;
+
+use crate::{
+    actions::craft::CraftTarget,
+    labor::{craft::CraftJob, job::AssignedJob},
+};
+
+use super::craft::craft_item;
+
+pub struct DoCraftJobPlugin;
+
+impl Plugin for DoCraftJobPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, set_craft_target.in_set(BigBrainSet::Actions));
+    }
+}
+
+pub fn do_craft_job() -> StepsBuilder {
+    info!("Building do_craft_job action");
+    Steps::build()
+        .label("do_craft_job")
+        .step(SetCraftTarget)
+        .step(craft_item())
+}
+
+#[derive(Component, Debug, Clone, ActionBuilder)]
+struct SetCraftTarget;
+
+fn set_craft_target(
+    mut commands: Commands,
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<SetCraftTarget>>,
+    assigned_job_query: Query<&AssignedJob>,
+    craft_job_query: Query<&CraftJob>,
+) {
+    for (actor, mut action_state, span) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Setting craft target");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let assigned_craft_job = assigned_job_query
+                    .get(actor.0)
+                    .and_then(|assigned_job| craft_job_query.get(assigned_job.0))
+                    .expect("Actor should have an assigned job");
+
+                info!(job=?assigned_craft_job, "Setting craft target");
+                commands
+                    .entity(actor.0)
+                    .insert(CraftTarget(assigned_craft_job.0));
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Setting craft target cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
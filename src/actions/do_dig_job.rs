@@ -1,6 +1,4 @@
-use bevy::prelude::{
-    App, Commands, Component, IntoSystemConfigs, Plugin, PreUpdate, Query, With, Without,
-};
+use bevy::prelude::{App, Commands, Component, IntoSystemConfigs, Plugin, PreUpdate, Query, With};
 use bevy_ecs_tilemap::tiles::TilePos;
 use big_brain::{
     actions::ConcurrentlyBuilder,
@@ -12,22 +10,15 @@ use tracing::{debug, info};
 
 use crate::{
     actions::dig::DigTarget,
-    labor::{
-        dig_tile::DigJob,
-        job::{AssignedJob, AssignedWorker},
-    },
+    labor::{dig_tile::DigJob, job::AssignedJob},
 };
 
-use super::{action_area::action_area_reachable, dig::dig_tile};
+use super::dig::dig_tile;
 pub struct DoDigJobPlugin;
 
 impl Plugin for DoDigJobPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            PreUpdate,
-            action_area_reachable::<DigJob, Without<AssignedWorker>>.in_set(BigBrainSet::Scorers),
-        )
-        .add_systems(
             PreUpdate,
             (set_dig_target, check_tile_exists).in_set(BigBrainSet::Actions),
         );
@@ -1,6 +1,4 @@
-use bevy::prelude::{
-    App, Commands, Component, IntoSystemConfigs, Plugin, PreUpdate, Query, With, Without,
-};
+use bevy::prelude::{App, Commands, Component, IntoSystemConfigs, Plugin, PreUpdate, Query, With};
 use big_brain::{
     actions::ConcurrentlyBuilder,
     prelude::{ActionBuilder, ActionState, ConcurrentMode, Concurrently, Steps},
@@ -11,24 +9,16 @@ use tracing::{debug, info};
 
 use crate::{
     actions::fell::FellTarget,
-    labor::{
-        chop_tree::FellingJob,
-        job::{AssignedJob, AssignedWorker},
-    },
+    labor::{chop_tree::FellingJob, job::AssignedJob},
     tree::Tree,
 };
 
-use super::{action_area::action_area_reachable, fell::fell_tree};
+use super::fell::fell_tree;
 pub struct DoFellingJobPlugin;
 
 impl Plugin for DoFellingJobPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            PreUpdate,
-            action_area_reachable::<FellingJob, Without<AssignedWorker>>
-                .in_set(BigBrainSet::Scorers),
-        )
-        .add_systems(
             PreUpdate,
             (set_fell_target, check_tree_exists).in_set(BigBrainSet::Actions),
         );
@@ -0,0 +1,69 @@
+use bevy::prelude::{App, Commands, Component, IntoSystemConfigs, Plugin, PreUpdate, Query, With};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
+    thinker::{ActionSpan, Actor},
+    BigBrainSet,
+};
+use tracing::info;
+
+use crate::{
+    actions::harvest::HarvestTarget,
+    labor::{farm::HarvestJob, job::AssignedJob},
+};
+
+use super::harvest::harvest_plant;
+
+pub struct DoHarvestJobPlugin;
+
+impl Plugin for DoHarvestJobPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, set_harvest_target.in_set(BigBrainSet::Actions));
+    }
+}
+
+pub fn do_harvest_job() -> StepsBuilder {
+    info!("Building do_harvest_job action");
+    Steps::build()
+        .label("do_harvest_job")
+        .step(SetHarvestTarget)
+        .step(harvest_plant())
+}
+
+#[derive(Component, Debug, Clone, ActionBuilder)]
+struct SetHarvestTarget;
+
+fn set_harvest_target(
+    mut commands: Commands,
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<SetHarvestTarget>>,
+    assigned_job_query: Query<&AssignedJob>,
+    harvest_job_query: Query<&HarvestJob>,
+) {
+    for (actor, mut action_state, span) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Setting harvest target");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let assigned_harvest_job = assigned_job_query
+                    .get(actor.0)
+                    .and_then(|assigned_job| harvest_job_query.get(assigned_job.0))
+                    .expect("Actor should have an assigned job");
+
+                info!(job=?assigned_harvest_job, "Setting harvest target");
+                commands
+                    .entity(actor.0)
+                    .insert(HarvestTarget(assigned_harvest_job.0));
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Setting harvest target cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
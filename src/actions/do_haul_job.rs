@@ -0,0 +1,232 @@
+use bevy::{
+    math::Vec3Swizzles,
+    prelude::{
+        resource_exists, App, Assets, ColorMaterial, Commands, Component, Entity, GlobalTransform,
+        IntoSystemConfigs, Mesh, Name, Parent, Plugin, PreUpdate, Query, Res, ResMut, Update, With,
+    },
+};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
+    thinker::{ActionSpan, Actor},
+    BigBrainSet,
+};
+use tracing::{error, info};
+
+use crate::{
+    actions::{
+        deliver::{deliver_items, DeliveryTarget},
+        pickup::{pick_up, PickupTarget},
+    },
+    inventory::{drop_items, Inventory, ItemStack},
+    item::{Item, ItemDefinitions},
+    labor::{
+        build_structure::BuildingMaterialsNeeded,
+        haul::{HaulItem, HaulRequest},
+        job::{AssignedJob, JobManagerParams, Labor},
+        stockpile::{free_stockpile_tiles, Stockpile, Stockpiled},
+    },
+    movement::WalkSpeed,
+    skills::{worker_speed, Skills},
+    terrain::TerrainParam,
+};
+
+pub struct DoHaulJobPlugin;
+
+impl Plugin for DoHaulJobPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, set_haul_target.in_set(BigBrainSet::Actions))
+            .add_systems(
+                Update,
+                drop_undelivered_items.run_if(resource_exists::<ItemDefinitions>()),
+            );
+    }
+}
+
+pub fn do_haul_job() -> StepsBuilder {
+    info!("Building do_haul_job action");
+    Steps::build()
+        .label("do_haul_job")
+        .step(SetHaulTarget)
+        .step(pick_up())
+        .step(deliver_items())
+}
+
+#[derive(Component, Debug, Clone, ActionBuilder)]
+struct SetHaulTarget;
+
+/// The haul job `job` the actor is doing with the haul steps `steps`.
+#[derive(Component, Debug)]
+struct Hauling {
+    steps: Entity,
+    job: Entity,
+}
+
+fn set_haul_target(
+    mut commands: Commands,
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan, &Parent), With<SetHaulTarget>>,
+    assigned_job_query: Query<&AssignedJob>,
+    haul_request_query: Query<&HaulRequest>,
+    global_transform_query: Query<&GlobalTransform>,
+    materials_needed_query: Query<&BuildingMaterialsNeeded>,
+    stockpile_query: Query<&Stockpile>,
+    item_query: Query<&Name, With<Item>>,
+    stockpiled_query: Query<(&GlobalTransform, &Stockpiled)>,
+    skills_query: Query<&Skills>,
+    terrain: TerrainParam,
+) {
+    for (actor, mut action_state, span, steps) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Setting haul target");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let assigned_job = assigned_job_query
+                    .get(actor.0)
+                    .expect("Actor should have an assigned job");
+                let haul_request = haul_request_query
+                    .get(assigned_job.0)
+                    .expect("Assigned job should be a haul");
+                let HaulItem::Entity(load) = haul_request.load else {
+                    error!("Only hauls of a specific item can be done");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Ok(item_name) = item_query.get(load) else {
+                    info!("Item to haul no longer exists");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
+                // Construction sites only get what they still need, anything else gets it all
+                let count = match materials_needed_query.get(haul_request.to) {
+                    Ok(materials_needed) => materials_needed
+                        .resources_needed()
+                        .iter()
+                        .filter(|(name, _)| name == item_name)
+                        .map(|(_, amount)| *amount)
+                        .sum(),
+                    Err(_) => u32::MAX,
+                };
+                if count == 0 {
+                    info!(item = item_name.as_str(), "Item is no longer needed");
+                    *action_state = ActionState::Failure;
+                    continue;
+                }
+
+                // Stockpiles get the item on a free tile, anything else right where it is
+                let position = match stockpile_query.get(haul_request.to) {
+                    Ok(stockpile) => free_stockpile_tiles(
+                        haul_request.to,
+                        stockpile,
+                        &terrain,
+                        &stockpiled_query,
+                    )
+                    .first()
+                    .and_then(|tile_pos| terrain.tile_to_global_pos(*tile_pos)),
+                    Err(_) => global_transform_query
+                        .get(haul_request.to)
+                        .map(|transform| transform.translation().xy())
+                        .ok(),
+                };
+                let Some(position) = position else {
+                    info!(to = ?haul_request.to, "Nowhere to deliver to");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
+                info!(item = ?load, to = ?haul_request.to, "Setting haul target");
+                commands.entity(actor.0).insert((
+                    PickupTarget {
+                        entity: load,
+                        count,
+                    },
+                    DeliveryTarget {
+                        to: haul_request.to,
+                        item: item_name.clone(),
+                        count,
+                        position,
+                    },
+                    Hauling {
+                        steps: steps.get(),
+                        job: assigned_job.0,
+                    },
+                    WalkSpeed(worker_speed(&skills_query, actor.0, Labor::Hauling)),
+                ));
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Setting haul target cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Drops the items of hauls that ended before they were delivered where the hauler stands, and
+/// gives up the haul so the items can be hauled again.
+///
+/// Hauls end early when they are canceled, the hauler stops working or the delivery fails.
+fn drop_undelivered_items(
+    mut commands: Commands,
+    mut hauler_query: Query<(
+        Entity,
+        &Hauling,
+        &GlobalTransform,
+        &mut Inventory,
+        Option<&PickupTarget>,
+        Option<&DeliveryTarget>,
+    )>,
+    entity_query: Query<Entity>,
+    assigned_job_query: Query<&AssignedJob>,
+    mut job_manager_params: JobManagerParams,
+    item_definitions: Res<ItemDefinitions>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (hauler, hauling, transform, mut inventory, opt_pickup, opt_delivery) in &mut hauler_query {
+        if entity_query.contains(hauling.steps) {
+            continue;
+        }
+        commands.entity(hauler).remove::<(Hauling, WalkSpeed)>();
+        // Delivered
+        let Some(delivery) = opt_delivery else { continue; };
+
+        commands
+            .entity(hauler)
+            .remove::<(PickupTarget, DeliveryTarget)>();
+        // Nothing was picked up yet when the pickup target is still there
+        if opt_pickup.is_none() {
+            let dropped = drop_items(
+                &mut commands,
+                &mut inventory,
+                &item_definitions,
+                &ItemStack {
+                    item: delivery.item.clone(),
+                    count: delivery.count,
+                },
+                transform.translation().xy(),
+                &mut materials,
+                &mut meshes,
+            );
+            if let Some(item_entity) = dropped {
+                info!(
+                    item = delivery.item.as_str(),
+                    ?item_entity,
+                    "Dropped undelivered haul items"
+                );
+            }
+        }
+        if assigned_job_query
+            .get(hauler)
+            .is_ok_and(|assigned_job| assigned_job.0 == hauling.job)
+        {
+            info!(job = ?hauling.job, "Giving up haul");
+            job_manager_params.cancel_job_assignment(hauling.job, hauler);
+        }
+    }
+}
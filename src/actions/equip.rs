@@ -0,0 +1,228 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, ScorerBuilder, Steps},
+    scorers::Score,
+    thinker::{ActionSpan, Actor, ScorerSpan},
+    BigBrainSet,
+};
+
+use crate::{
+    actions::{
+        pickup::{fetch_item, pick_up, FetchBackoff},
+        work::StopWorking,
+    },
+    building_material::Reserved,
+    equipment::{Equipment, ToolDefinition},
+    inventory::Inventory,
+    item::{spawn_item, Item, ItemDefinitions},
+    labor::job::EnabledLabors,
+};
+
+pub struct EquipPlugin;
+
+impl Plugin for EquipPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, tool_wanted.in_set(BigBrainSet::Scorers))
+            .add_systems(
+                PreUpdate,
+                (set_tool_target, equip).in_set(BigBrainSet::Actions),
+            );
+    }
+}
+
+/// Whether the worker would rather have `tool` than what it has equipped: it does the labor the
+/// tool is for and has no tool of its kind yet, or a slower one.
+fn wants_tool(
+    tool: &ToolDefinition,
+    equipment: &Equipment,
+    opt_enabled_labors: Option<&EnabledLabors>,
+    item_definitions: &ItemDefinitions,
+) -> bool {
+    let does_labor = opt_enabled_labors.map_or(true, |enabled_labors| {
+        enabled_labors.is_enabled(tool.kind.labor())
+    });
+    does_labor
+        && equipment
+            .tool_speed(tool.kind, item_definitions)
+            .map_or(true, |speed| tool.speed > speed)
+}
+
+/// Scores 1 while a tool the actor wants is lying around and the actor isn't backing off from
+/// fetching it.
+#[derive(Component, Debug, Clone, ScorerBuilder)]
+pub struct ToolWanted;
+
+fn tool_wanted(
+    mut scorer_query: Query<(&Actor, &mut Score, &ScorerSpan), With<ToolWanted>>,
+    actor_query: Query<(&Equipment, Option<&EnabledLabors>), Without<FetchBackoff>>,
+    item_query: Query<&Name, (With<Item>, Without<Reserved>)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+) {
+    for (actor, mut score, _span) in &mut scorer_query {
+        let (Some(item_definitions), Ok((equipment, opt_enabled_labors))) =
+            (item_definitions.as_deref(), actor_query.get(actor.0))
+        else {
+            score.set(0.0);
+            continue;
+        };
+        let wanted = item_query.iter().any(|name| {
+            item_definitions
+                .get(name.as_str())
+                .and_then(|definition| definition.tool)
+                .is_some_and(|tool| {
+                    wants_tool(&tool, equipment, opt_enabled_labors, item_definitions)
+                })
+        });
+        score.set(if wanted { 1.0 } else { 0.0 });
+    }
+}
+
+/// Drops the actor's work, fetches the closest tool it wants and equips it.
+pub fn equip_tool() -> StepsBuilder {
+    Steps::build()
+        .label("equip_tool")
+        .step(StopWorking)
+        .step(SetToolTarget)
+        .step(pick_up())
+        .step(Equip)
+}
+
+#[derive(Component, Debug, Clone, ActionBuilder)]
+pub struct SetToolTarget;
+
+fn set_tool_target(
+    mut commands: Commands,
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan, &Parent), With<SetToolTarget>>,
+    actor_query: Query<(&GlobalTransform, &Equipment, Option<&EnabledLabors>)>,
+    item_query: Query<(Entity, &Name, &GlobalTransform), (With<Item>, Without<Reserved>)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+) {
+    for (actor, mut action_state, span, steps) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Setting tool target");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let Some(item_definitions) = item_definitions.as_deref() else {
+                    error!("Item definitions not loaded");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Ok((transform, equipment, opt_enabled_labors)) = actor_query.get(actor.0) else {
+                    error!("Actor should have a global transform and equipment");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let actor_pos = transform.translation().xy();
+                let closest_tool = item_query
+                    .iter()
+                    .filter(|(_, name, _)| {
+                        item_definitions
+                            .get(name.as_str())
+                            .and_then(|definition| definition.tool)
+                            .is_some_and(|tool| {
+                                wants_tool(&tool, equipment, opt_enabled_labors, item_definitions)
+                            })
+                    })
+                    .min_by(|(_, _, a), (_, _, b)| {
+                        let a = a.translation().xy().distance_squared(actor_pos);
+                        let b = b.translation().xy().distance_squared(actor_pos);
+                        a.total_cmp(&b)
+                    });
+                let Some((item_entity, name, _)) = closest_tool else {
+                    info!("No tool to equip");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
+                info!(item = name.as_str(), "Setting tool target");
+                fetch_item(&mut commands, actor.0, item_entity, steps.get());
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Setting tool target cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Equips a tool out of the actor's inventory that it wants, dropping the tool it replaces.
+#[derive(Component, Debug, Clone, ActionBuilder)]
+pub struct Equip;
+
+fn equip(
+    mut commands: Commands,
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<Equip>>,
+    mut actor_query: Query<(
+        &GlobalTransform,
+        &mut Inventory,
+        &mut Equipment,
+        Option<&EnabledLabors>,
+    )>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (actor, mut action_state, span) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Starting equip");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let Some(item_definitions) = item_definitions.as_deref() else {
+                    error!("Item definitions not loaded");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Ok((transform, mut inventory, mut equipment, opt_enabled_labors)) = actor_query.get_mut(actor.0) else {
+                    error!("Actor should have a global transform, an inventory and equipment");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let wanted_tool = inventory.stacks().iter().find_map(|stack| {
+                    let tool = item_definitions.get(stack.item.as_str())?.tool?;
+                    wants_tool(&tool, &equipment, opt_enabled_labors, item_definitions)
+                        .then(|| (stack.item.clone(), tool))
+                });
+                let Some((item, tool)) = wanted_tool else {
+                    info!("No tool to equip in inventory");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
+                inventory.remove(&item, 1);
+                let replaced = equipment.equip(tool.kind, item.as_str().to_string());
+                info!(item = item.as_str(), kind = tool.kind.name(), "Equipped");
+                if let Some(definition) = replaced
+                    .as_deref()
+                    .and_then(|replaced| item_definitions.get(replaced))
+                {
+                    info!(item = definition.name.as_str(), "Dropped replaced tool");
+                    spawn_item(
+                        &mut commands,
+                        definition,
+                        1,
+                        transform.translation().xy(),
+                        &mut materials,
+                        &mut meshes,
+                    );
+                }
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Equip cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
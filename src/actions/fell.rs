@@ -18,7 +18,11 @@ use tracing::{debug, error, info};
 
 use crate::{
     actions::action_area::ActionArea,
+    equipment::{worker_tool_speed, Equipment, ToolKind},
     health::HealthDamageEvent,
+    item::ItemDefinitions,
+    labor::job::Labor,
+    skills::{worker_speed, Skills},
     tree::{Tree, TreeDestroyedEvent},
     util::get_entity_position,
 };
@@ -83,6 +87,11 @@ pub fn fell_action_area(target_position: Vec2) -> ActionArea {
     ])
 }
 
+/// Seconds between two felling ticks, before the speed of the feller's skill and axe are applied.
+pub const FELLING_TICK_SECONDS: f32 = 1.;
+/// Damage dealt to the tree every felling tick.
+pub const FELLING_DAMAGE: u32 = 20;
+
 #[derive(Component, Debug, Reflect)]
 pub struct FellingTimer {
     pub tree_entity: Entity,
@@ -97,6 +106,9 @@ fn fell(
     tree_query: Query<&Tree>,
     fell_timer_query: Query<&mut FellingTimer>,
     mut tree_destroyed_event_reader: EventReader<TreeDestroyedEvent>,
+    skills_query: Query<&Skills>,
+    equipment_query: Query<&Equipment>,
+    item_definitions: Option<Res<ItemDefinitions>>,
 ) {
     for (actor, mut action_state, span) in &mut fell_action_query {
         let _guard = span.span().enter();
@@ -140,9 +152,20 @@ fn fell(
                 if at_action_area(actor_position, &action_area) {
                     if fell_timer_query.get(actor.0).is_err() {
                         info!("Felling started");
+                        let woodcutting_speed =
+                            worker_speed(&skills_query, actor.0, Labor::Woodcutting)
+                                * worker_tool_speed(
+                                    &equipment_query,
+                                    item_definitions.as_deref(),
+                                    actor.0,
+                                    ToolKind::Axe,
+                                );
                         commands.entity(actor.0).insert(FellingTimer {
                             tree_entity: fell_target.0,
-                            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                            timer: Timer::from_seconds(
+                                FELLING_TICK_SECONDS / woodcutting_speed,
+                                TimerMode::Repeating,
+                            ),
                         });
                     }
                 } else {
@@ -183,7 +206,7 @@ fn felling_timer(
             info!(action=?action_entity, tree=?tree_entity, "Felling tick");
             tree_damage_event_writer.send(HealthDamageEvent {
                 entity: *tree_entity,
-                damage: 20,
+                damage: FELLING_DAMAGE,
             });
         }
     }
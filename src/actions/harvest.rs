@@ -0,0 +1,176 @@
+use bevy::{
+    ecs::system::{lifetimeless::SQuery, SystemParamItem},
+    math::Vec3Swizzles,
+    prelude::*,
+};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
+    thinker::{ActionSpan, Actor},
+    BigBrainSet,
+};
+
+use crate::{
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        move_to::{move_to_action_area, MoveToActionArea},
+    },
+    labor::job::Labor,
+    plant::{Plant, PlantHarvestedEvent, Ripe},
+    skills::{worker_speed, Skills},
+};
+
+pub struct HarvestPlugin;
+
+impl Plugin for HarvestPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Harvest>()
+            .register_type::<HarvestTarget>()
+            .register_type::<HarvestTimer>()
+            .add_systems(
+                PreUpdate,
+                (move_to_action_area::<HarvestTarget>, harvest).in_set(BigBrainSet::Actions),
+            )
+            .add_systems(Update, harvest_timer);
+    }
+}
+
+#[derive(Component, Clone, Debug, Reflect, ActionBuilder)]
+pub struct Harvest;
+
+/// Plant the actor harvests.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct HarvestTarget(pub Entity);
+
+impl HasActionArea for HarvestTarget {
+    fn action_area() -> ActionArea {
+        ActionArea(vec![Vec2::ZERO])
+    }
+}
+
+impl HasActionPosition for HarvestTarget {
+    type PositionParam = SQuery<&'static GlobalTransform>;
+
+    fn action_pos(
+        &self,
+        global_transform_query: &SystemParamItem<Self::PositionParam>,
+    ) -> Option<Vec2> {
+        global_transform_query
+            .get(self.0)
+            .map(|transform| transform.translation().xy())
+            .ok()
+    }
+}
+
+/// Seconds it takes an unskilled farmer to harvest a plant.
+pub const HARVEST_SECONDS: f32 = 3.;
+
+#[derive(Component, Debug, Reflect)]
+pub struct HarvestTimer {
+    pub plant: Entity,
+    pub timer: Timer,
+}
+
+fn harvest(
+    mut commands: Commands,
+    mut harvest_action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<Harvest>>,
+    global_transform_query: Query<&GlobalTransform>,
+    harvest_target_query: Query<&HarvestTarget>,
+    harvest_timer_query: Query<(), With<HarvestTimer>>,
+    plant_query: Query<Option<&Ripe>, With<Plant>>,
+    skills_query: Query<&Skills>,
+) {
+    for (actor, mut action_state, span) in &mut harvest_action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Starting harvesting");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let Ok(harvest_target) = harvest_target_query.get(actor.0) else {
+                    error!("No harvest target");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Ok(opt_ripe) = plant_query.get(harvest_target.0) else {
+                    info!("Plant no longer exists");
+                    commands
+                        .entity(actor.0)
+                        .remove::<HarvestTimer>()
+                        .remove::<HarvestTarget>();
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                if opt_ripe.is_none() {
+                    info!("Harvesting finished");
+                    commands
+                        .entity(actor.0)
+                        .remove::<HarvestTimer>()
+                        .remove::<HarvestTarget>();
+                    *action_state = ActionState::Success;
+                    continue;
+                }
+
+                let (Ok(actor_transform), Ok(plant_transform)) = (
+                    global_transform_query.get(actor.0),
+                    global_transform_query.get(harvest_target.0),
+                ) else {
+                    error!("Actor and plant should have a global transform");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let distance = actor_transform
+                    .translation()
+                    .xy()
+                    .distance(plant_transform.translation().xy());
+                if distance < 16. {
+                    if !harvest_timer_query.contains(actor.0) {
+                        info!("Harvesting started");
+                        let farming_speed = worker_speed(&skills_query, actor.0, Labor::Farming);
+                        commands.entity(actor.0).insert(HarvestTimer {
+                            plant: harvest_target.0,
+                            timer: Timer::from_seconds(
+                                HARVEST_SECONDS / farming_speed,
+                                TimerMode::Once,
+                            ),
+                        });
+                    }
+                } else {
+                    info!("Too far away to harvest");
+                    commands.entity(actor.0).remove::<HarvestTimer>();
+                    *action_state = ActionState::Failure;
+                }
+            }
+            ActionState::Cancelled => {
+                info!("Harvesting cancelled");
+                commands.entity(actor.0).remove::<HarvestTimer>();
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn harvest_timer(
+    time: Res<Time>,
+    mut harvest_timer_query: Query<(Entity, &mut HarvestTimer)>,
+    mut plant_harvested_events: EventWriter<PlantHarvestedEvent>,
+) {
+    for (worker, mut harvest_timer) in &mut harvest_timer_query {
+        if harvest_timer.timer.tick(time.delta()).just_finished() {
+            plant_harvested_events.send(PlantHarvestedEvent {
+                plant: harvest_timer.plant,
+                worker,
+            });
+        }
+    }
+}
+
+pub fn harvest_plant() -> StepsBuilder {
+    Steps::build()
+        .label("harvester")
+        .step(MoveToActionArea::<HarvestTarget>::builder())
+        .step(Harvest)
+}
@@ -1,14 +1,23 @@
 use bevy::prelude::{App, Plugin};
 
 pub mod action_area;
+pub mod build;
+pub mod craft;
 pub mod deliver;
 pub mod dig;
+pub mod do_build_job;
+pub mod do_craft_job;
 pub mod do_dig_job;
 pub mod do_fell_job;
+pub mod do_harvest_job;
+pub mod do_haul_job;
+pub mod equip;
 pub mod fell;
+pub mod harvest;
 pub mod meander;
 pub mod move_to;
 pub mod pickup;
+pub mod tend_needs;
 pub mod work;
 
 pub struct ActionsPlugin;
@@ -18,13 +27,24 @@ impl Plugin for ActionsPlugin {
         app.add_plugins((
             dig::DigPlugin,
             pickup::PickupPlugin,
+            build::BuildPlugin,
             deliver::DeliverPlugin,
             fell::FellPlugin,
             move_to::MoveToPlugin,
             work::WorkPlugin,
             do_fell_job::DoFellingJobPlugin,
             do_dig_job::DoDigJobPlugin,
+            do_haul_job::DoHaulJobPlugin,
+            do_build_job::DoBuildJobPlugin,
+            harvest::HarvestPlugin,
+            do_harvest_job::DoHarvestJobPlugin,
             meander::MeanderPlugin,
+            (
+                tend_needs::TendNeedsPlugin,
+                equip::EquipPlugin,
+                craft::CraftPlugin,
+                do_craft_job::DoCraftJobPlugin,
+            ),
         ));
     }
 }
@@ -3,8 +3,8 @@ use std::marker::PhantomData;
 use bevy::{
     math::Vec3Swizzles,
     prelude::{
-        App, Commands, Component, Entity, GlobalTransform, IntoSystemConfigs, Plugin, PreUpdate,
-        Query, Vec2, With,
+        App, Commands, Component, Entity, EventReader, GlobalTransform, IntoSystemConfigs, Mut,
+        Plugin, PreUpdate, Query, Vec2, With,
     },
     reflect::Reflect,
 };
@@ -18,7 +18,8 @@ use tracing::{debug, error, info};
 
 use crate::{
     movement::Walker,
-    pathfinding::{Path, Pathfinding},
+    path_request::{clear_path, failed_path_entities, PathFailedEvent, PathRequest, PathVersion},
+    pathfinding::Path,
     terrain::TerrainParam,
 };
 
@@ -46,23 +47,39 @@ pub struct MoveToPosition {
 pub struct Moving;
 
 fn move_to_position(
+    mut commands: Commands,
     mut move_to_query: Query<(
+        Entity,
         &Actor,
         &mut Walker,
         &mut ActionState,
         &MoveToPosition,
         &ActionSpan,
+        Option<&mut Path>,
+        Option<&PathRequest>,
     )>,
     global_transform_query: Query<&GlobalTransform>,
-    pathfinding: Pathfinding,
+    mut path_failed_events: EventReader<PathFailedEvent>,
     terrain: TerrainParam,
 ) {
-    for (actor, mut walker, mut action_state, move_to, span) in &mut move_to_query {
+    let failed_paths = failed_path_entities(&mut path_failed_events);
+    for (
+        action_entity,
+        actor,
+        mut walker,
+        mut action_state,
+        move_to,
+        span,
+        opt_path,
+        opt_request,
+    ) in &mut move_to_query
+    {
         let _guard = span.span().enter();
 
         match *action_state {
             ActionState::Requested => {
                 info!(traveller=?actor.0, "Requested to move to {:?}", move_to.destination);
+                clear_path(&mut commands, action_entity);
                 *action_state = ActionState::Executing;
             }
             ActionState::Executing => {
@@ -74,21 +91,32 @@ fn move_to_position(
 
                 if (actor_position - move_to.destination).length() < 1. {
                     info!("At destination");
+                    clear_path(&mut commands, action_entity);
                     *action_state = ActionState::Success;
                     continue;
                 }
 
-                let path = pathfinding.find_path(actor_position, move_to.destination);
-                if let Some(path) = path {
-                    info!("Path found to destination");
-                    follow_path(path, &mut walker, actor_position, &terrain);
-                } else {
+                if failed_paths.contains(&action_entity) {
                     error!("No path found to destination");
                     *action_state = ActionState::Failure;
+                } else if let Some(path) = opt_path {
+                    follow_requested_path(
+                        &mut commands,
+                        action_entity,
+                        path,
+                        &mut walker,
+                        actor_position,
+                        &terrain,
+                    );
+                } else if opt_request.is_none() {
+                    commands
+                        .entity(action_entity)
+                        .insert(PathRequest::new(actor_position, move_to.destination));
                 }
             }
             ActionState::Cancelled => {
                 info!("Cancelled");
+                clear_path(&mut commands, action_entity);
                 *action_state = ActionState::Failure;
             }
             _ => {}
@@ -103,23 +131,39 @@ pub struct FollowEntity {
 }
 
 fn follow_entity(
+    mut commands: Commands,
     mut follow_entity_query: Query<(
+        Entity,
         &Actor,
         &mut Walker,
         &mut ActionState,
         &FollowEntity,
         &ActionSpan,
+        Option<&mut Path>,
+        Option<&PathRequest>,
     )>,
     global_transform_query: Query<&GlobalTransform>,
-    pathfinding: Pathfinding,
+    mut path_failed_events: EventReader<PathFailedEvent>,
     terrain: TerrainParam,
 ) {
-    for (actor, mut walker, mut action_state, follow_entity, span) in &mut follow_entity_query {
+    let failed_paths = failed_path_entities(&mut path_failed_events);
+    for (
+        action_entity,
+        actor,
+        mut walker,
+        mut action_state,
+        follow_entity,
+        span,
+        opt_path,
+        opt_request,
+    ) in &mut follow_entity_query
+    {
         let _guard = span.span().enter();
 
         match *action_state {
             ActionState::Requested => {
                 info!(traveller=?actor.0, "Requested to follow {:?}", follow_entity.entity);
+                clear_path(&mut commands, action_entity);
                 *action_state = ActionState::Executing;
             }
             ActionState::Executing => {
@@ -137,20 +181,30 @@ fn follow_entity(
 
                 if (destination_position - actor_position).length() < follow_entity.distance {
                     info!("At destination");
+                    clear_path(&mut commands, action_entity);
                     *action_state = ActionState::Success;
-                } else {
-                    let path = pathfinding.find_path(actor_position, destination_position);
-                    if let Some(path) = path {
-                        info!("Path found to destination");
-                        follow_path(path, &mut walker, actor_position, &terrain);
-                    } else {
-                        error!("No path found to destination");
-                        *action_state = ActionState::Failure;
-                    }
+                } else if failed_paths.contains(&action_entity) {
+                    error!("No path found to destination");
+                    *action_state = ActionState::Failure;
+                } else if let Some(path) = opt_path {
+                    follow_requested_path(
+                        &mut commands,
+                        action_entity,
+                        path,
+                        &mut walker,
+                        actor_position,
+                        &terrain,
+                    );
+                } else if opt_request.is_none() {
+                    // The entity keeps moving, the path is requested again once it's walked
+                    commands
+                        .entity(action_entity)
+                        .insert(PathRequest::new(actor_position, destination_position));
                 }
             }
             ActionState::Cancelled => {
                 info!("Cancelled");
+                clear_path(&mut commands, action_entity);
                 *action_state = ActionState::Failure;
             }
             _ => {}
@@ -158,22 +212,46 @@ fn follow_entity(
     }
 }
 
+/// Follows the requested path of `action_entity`, removing it once it has been walked so a new
+/// one is requested.
+fn follow_requested_path(
+    commands: &mut Commands,
+    action_entity: Entity,
+    mut path: Mut<Path>,
+    walker: &mut Walker,
+    walker_position: Vec2,
+    terrain: &TerrainParam,
+) {
+    follow_path(&mut path, walker, walker_position, terrain);
+    if path.0.is_empty() {
+        commands
+            .entity(action_entity)
+            .remove::<(Path, PathVersion)>();
+    }
+}
+
 pub fn follow_path(
-    mut path: Path,
+    path: &mut Path,
     walker: &mut Walker,
     walker_position: Vec2,
     terrain: &TerrainParam,
 ) {
     const TOLERANCE: f32 = 3.;
-    let walker_tile_pos = terrain.global_to_tile_pos(walker_position).unwrap();
+    let (Some(settings), Some(walker_tile_pos)) = (
+        terrain.settings.as_deref(),
+        terrain.global_to_tile_pos(walker_position),
+    ) else {
+        walker.move_direction = None;
+        return;
+    };
     if let Some(&first_tile) = path.0.first() {
-        let first_tile_world_pos = terrain.tile_to_global_pos(first_tile.into());
+        let first_tile_world_pos = settings.tile_to_world(first_tile);
         let distance_to_first_tile = (first_tile_world_pos - walker_position).length();
         if distance_to_first_tile < TOLERANCE {
             path.0.remove(0);
         }
         if let Some(&second_tile) = path.0.get(1) {
-            let second_tile_world_pos = terrain.tile_to_global_pos(second_tile.into());
+            let second_tile_world_pos = settings.tile_to_world(second_tile);
             if is_between(walker_position, first_tile_world_pos, second_tile_world_pos) {
                 path.0.remove(0);
             }
@@ -183,7 +261,7 @@ pub fn follow_path(
     if let Some(&next_tile) = path.0.get(0) {
         let is_climbing = walker_tile_pos.y != next_tile.y && walker_tile_pos.x == next_tile.x;
         // when climbing, move to the center of the tile on both x and y axis
-        let next_tile_world_pos = terrain.tile_to_global_pos(next_tile.into());
+        let next_tile_world_pos = settings.tile_to_world(next_tile);
         if is_climbing {
             let distance = Vec2::new(
                 next_tile_world_pos.x - walker_position.x,
@@ -237,20 +315,33 @@ fn at_action_area(actor_position: Vec2, action_area: &ActionArea) -> bool {
 }
 
 pub fn move_to_action_area<T: GlobalActionArea + Component>(
-    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<MoveToActionArea<T>>>,
+    mut commands: Commands,
+    mut action_query: Query<
+        (
+            Entity,
+            &Actor,
+            &mut ActionState,
+            &ActionSpan,
+            Option<&mut Path>,
+            Option<&PathRequest>,
+        ),
+        With<MoveToActionArea<T>>,
+    >,
     target_query: Query<&T>,
     global_transform_query: Query<&GlobalTransform>,
     mut walker_query: Query<&mut Walker>,
-    pathfinding: Pathfinding,
+    mut path_failed_events: EventReader<PathFailedEvent>,
     terrain: TerrainParam,
     action_area_param: ActionAreaParam<T>,
 ) {
-    for (actor, mut action_state, span) in &mut action_query {
+    let failed_paths = failed_path_entities(&mut path_failed_events);
+    for (action_entity, actor, mut action_state, span, opt_path, opt_request) in &mut action_query {
         let _guard = span.span().enter();
 
         match *action_state {
             ActionState::Requested => {
                 info!(traveller=?actor.0, "Requested to move to action area");
+                clear_path(&mut commands, action_entity);
                 *action_state = ActionState::Executing;
             }
             ActionState::Executing => {
@@ -276,29 +367,35 @@ pub fn move_to_action_area<T: GlobalActionArea + Component>(
                         .expect("Actor should have a walker");
 
                     walker.move_direction = None;
+                    clear_path(&mut commands, action_entity);
                     *action_state = ActionState::Success;
-                } else {
-                    debug!("Moving to tree");
-                    let path = action_area
-                        .0
-                        .iter()
-                        .flat_map(|&tile| pathfinding.find_path(actor_position, tile))
-                        .min_by(|a, b| a.0.len().cmp(&b.0.len()));
-                    if let Some(path) = path {
-                        let mut walker = walker_query
-                            .get_mut(actor.0)
-                            .expect("Actor should have a walker");
-
-                        debug!("Following path to tree");
-                        follow_path(path, &mut walker, actor_position, &terrain);
-                    } else {
-                        error!(actor_position=?actor_position, action_area=?action_area, "No path found to tree");
-                        *action_state = ActionState::Failure;
-                    }
+                } else if failed_paths.contains(&action_entity) {
+                    error!(actor_position=?actor_position, action_area=?action_area, "No path found to tree");
+                    *action_state = ActionState::Failure;
+                } else if let Some(path) = opt_path {
+                    let mut walker = walker_query
+                        .get_mut(actor.0)
+                        .expect("Actor should have a walker");
+
+                    debug!("Following path to tree");
+                    follow_requested_path(
+                        &mut commands,
+                        action_entity,
+                        path,
+                        &mut walker,
+                        actor_position,
+                        &terrain,
+                    );
+                } else if opt_request.is_none() {
+                    debug!("Requesting path to tree");
+                    commands
+                        .entity(action_entity)
+                        .insert(PathRequest::to_any(actor_position, action_area.0));
                 }
             }
             ActionState::Cancelled => {
                 info!("Cancelled");
+                clear_path(&mut commands, action_entity);
                 *action_state = ActionState::Failure;
             }
             _ => {}
@@ -1,33 +1,115 @@
-use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy::{
+    ecs::system::{lifetimeless::SQuery, SystemParamItem},
+    math::Vec3Swizzles,
+    prelude::*,
+};
 use big_brain::{
-    prelude::{ActionBuilder, ActionState},
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, Steps},
     thinker::{ActionSpan, Actor},
     BigBrainSet,
 };
 
-use crate::labor::job::all_workers_eligible;
+use crate::{
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        move_to::{move_to_action_area, MoveToActionArea},
+    },
+    building_material::Reserved,
+    inventory::{pick_up_item, Inventory},
+    item::{Item, ItemDefinitions},
+};
 
 pub struct PickupPlugin;
 
 impl Plugin for PickupPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Pickup>()
-            .add_systems(Update, all_workers_eligible::<Pickup>)
-            .add_systems(PreUpdate, pickup.in_set(BigBrainSet::Actions));
+            .register_type::<PickupTarget>()
+            .add_systems(
+                PreUpdate,
+                (pickup, move_to_action_area::<PickupTarget>).in_set(BigBrainSet::Actions),
+            )
+            .add_systems(Update, (end_fetches, tick_fetch_backoffs));
     }
 }
 
+/// Picks up the actor's [`PickupTarget`] into its [`Inventory`].
 #[derive(Component, Debug, Clone, Reflect, ActionBuilder)]
-pub struct Pickup {
+pub struct Pickup;
+
+/// Up to `count` items of the item stack `entity` lying in the world.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct PickupTarget {
     pub entity: Entity,
+    pub count: u32,
+}
+
+/// Seconds an actor waits before fetching items for itself again after a fetch failed.
+pub const FETCH_BACKOFF_SECONDS: f32 = 30.;
+
+/// An item the actor reserved to pick up for itself with the steps `steps`.
+#[derive(Component, Debug)]
+pub struct Fetching {
+    pub item: Entity,
+    pub steps: Entity,
+}
+
+/// Keeps an actor from fetching items for itself until the timer finishes, so an item it can't
+/// reach doesn't keep it from doing anything else.
+#[derive(Component, Debug)]
+pub struct FetchBackoff(pub Timer);
+
+/// Reserves `item` and makes it the pickup target of `actor`, the reservation lasts as long as
+/// the steps `steps` run.
+pub fn fetch_item(commands: &mut Commands, actor: Entity, item: Entity, steps: Entity) {
+    commands.entity(item).insert(Reserved);
+    commands.entity(actor).insert((
+        PickupTarget {
+            entity: item,
+            count: 1,
+        },
+        Fetching { item, steps },
+    ));
+}
+
+impl HasActionArea for PickupTarget {
+    fn action_area() -> ActionArea {
+        ActionArea(vec![Vec2::ZERO])
+    }
+}
+
+impl HasActionPosition for PickupTarget {
+    type PositionParam = SQuery<&'static GlobalTransform>;
+
+    fn action_pos(
+        &self,
+        global_transform_query: &SystemParamItem<Self::PositionParam>,
+    ) -> Option<Vec2> {
+        global_transform_query
+            .get(self.entity)
+            .map(|transform| transform.translation().xy())
+            .ok()
+    }
+}
+
+pub fn pick_up() -> StepsBuilder {
+    Steps::build()
+        .label("pick_up")
+        .step(MoveToActionArea::<PickupTarget>::builder())
+        .step(Pickup)
 }
 
 fn pickup(
     mut commands: Commands,
-    mut pickup_query: Query<(&Actor, &Pickup, &mut ActionState, &ActionSpan)>,
+    mut pickup_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<Pickup>>,
+    pickup_target_query: Query<&PickupTarget>,
     global_transform_query: Query<&GlobalTransform>,
+    mut inventory_query: Query<&mut Inventory>,
+    mut item_query: Query<(&Name, &mut Item)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
 ) {
-    for (actor, pickup, mut action_state, span) in &mut pickup_query {
+    for (actor, mut action_state, span) in &mut pickup_query {
         let _guard = span.span().enter();
 
         match *action_state {
@@ -36,6 +118,25 @@ fn pickup(
                 *action_state = ActionState::Executing;
             }
             ActionState::Executing => {
+                let Ok(pickup) = pickup_target_query.get(actor.0) else {
+                    error!("No pickup target");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Some(item_definitions) = item_definitions.as_deref() else {
+                    error!("Item definitions not loaded");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let (Ok(mut inventory), Ok((name, mut item))) = (
+                    inventory_query.get_mut(actor.0),
+                    item_query.get_mut(pickup.entity),
+                ) else {
+                    info!("Nothing to pick up");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
                 let actor_position = global_transform_query
                     .get(actor.0)
                     .unwrap()
@@ -49,10 +150,27 @@ fn pickup(
                     .xy();
 
                 if actor_position.distance(pickup_position) < 16. {
-                    // Move item to worker inventory
-                    commands.entity(actor.0).add_child(pickup.entity);
-                    info!("Picked up");
-                    *action_state = ActionState::Success;
+                    let picked_up = pick_up_item(
+                        &mut commands,
+                        &mut inventory,
+                        item_definitions,
+                        pickup.entity,
+                        name,
+                        &mut item,
+                        pickup.count,
+                    );
+                    if item.count > 0 {
+                        // Whatever was left behind can be hauled by someone else
+                        commands.entity(pickup.entity).remove::<Reserved>();
+                    }
+                    commands.entity(actor.0).remove::<PickupTarget>();
+                    if picked_up > 0 {
+                        info!(item = name.as_str(), count = picked_up, "Picked up");
+                        *action_state = ActionState::Success;
+                    } else {
+                        info!(item = name.as_str(), "No room in inventory");
+                        *action_state = ActionState::Failure;
+                    }
                 } else {
                     info!("Too far away to pickup");
                     *action_state = ActionState::Failure;
@@ -66,3 +184,45 @@ fn pickup(
         }
     }
 }
+
+/// Frees the items of fetches whose steps ended before picking them up, backing the actor off
+/// from fetching again for a while.
+fn end_fetches(
+    mut commands: Commands,
+    actor_query: Query<(Entity, &Fetching, Option<&PickupTarget>)>,
+    entity_query: Query<Entity>,
+) {
+    for (actor, fetching, opt_pickup_target) in &actor_query {
+        if entity_query.contains(fetching.steps) {
+            continue;
+        }
+        commands.entity(actor).remove::<Fetching>();
+        if !opt_pickup_target.is_some_and(|pickup_target| pickup_target.entity == fetching.item) {
+            continue;
+        }
+
+        info!(?actor, item = ?fetching.item, "Fetching item failed, backing off");
+        commands
+            .entity(actor)
+            .remove::<PickupTarget>()
+            .insert(FetchBackoff(Timer::from_seconds(
+                FETCH_BACKOFF_SECONDS,
+                TimerMode::Once,
+            )));
+        if entity_query.contains(fetching.item) {
+            commands.entity(fetching.item).remove::<Reserved>();
+        }
+    }
+}
+
+fn tick_fetch_backoffs(
+    mut commands: Commands,
+    mut backoff_query: Query<(Entity, &mut FetchBackoff)>,
+    time: Res<Time>,
+) {
+    for (actor, mut backoff) in &mut backoff_query {
+        if backoff.0.tick(time.delta()).finished() {
+            commands.entity(actor).remove::<FetchBackoff>();
+        }
+    }
+}
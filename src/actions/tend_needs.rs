@@ -0,0 +1,293 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use big_brain::{
+    actions::StepsBuilder,
+    prelude::{ActionBuilder, ActionState, ScorerBuilder, Steps},
+    scorers::Score,
+    thinker::{ActionSpan, Actor, ScorerSpan},
+    BigBrainSet,
+};
+
+use crate::{
+    actions::{
+        pickup::{fetch_item, pick_up, FetchBackoff},
+        work::StopWorking,
+    },
+    building_material::Reserved,
+    inventory::Inventory,
+    item::{Item, ItemDefinitions},
+    movement::Walker,
+    needs::{NeedKind, Needs, Sleeping},
+};
+
+pub struct TendNeedsPlugin;
+
+impl Plugin for TendNeedsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (need_urgent, consumable_carried).in_set(BigBrainSet::Scorers),
+        )
+        .add_systems(
+            PreUpdate,
+            (set_consumable_target, consume, sleep).in_set(BigBrainSet::Actions),
+        );
+    }
+}
+
+/// Scores how badly the actor needs to take care of the need.
+///
+/// Hunger and thirst only score while there is something lying around to eat or drink and the
+/// actor isn't backing off from fetching it, sleeping actors keep scoring fatigue until they are
+/// rested.
+#[derive(Component, Debug, Clone, ScorerBuilder)]
+pub struct NeedUrgent(pub NeedKind);
+
+fn need_urgent(
+    mut scorer_query: Query<(&Actor, &mut Score, &ScorerSpan, &NeedUrgent)>,
+    needs_query: Query<(&Needs, Option<&Sleeping>, Option<&FetchBackoff>)>,
+    item_query: Query<&Name, (With<Item>, Without<Reserved>)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+) {
+    let available = |kind: NeedKind| {
+        item_definitions.as_deref().is_some_and(|item_definitions| {
+            item_query.iter().any(|name| {
+                item_definitions
+                    .get(name.as_str())
+                    .is_some_and(|definition| kind.satisfaction(definition) > 0.)
+            })
+        })
+    };
+
+    for (actor, mut score, _span, need_urgent) in &mut scorer_query {
+        let Ok((needs, opt_sleeping, opt_backoff)) = needs_query.get(actor.0) else {
+            score.set(0.0);
+            continue;
+        };
+        let kind = need_urgent.0;
+        let value = match kind {
+            NeedKind::Fatigue if opt_sleeping.is_some() => 1.0,
+            NeedKind::Fatigue => needs.get(kind),
+            NeedKind::Hunger | NeedKind::Thirst if opt_backoff.is_none() && available(kind) => {
+                needs.get(kind)
+            }
+            NeedKind::Hunger | NeedKind::Thirst => 0.0,
+        };
+        score.set(value.clamp(0., 1.));
+    }
+}
+
+/// Scores how badly the actor needs to take care of the need while it carries something that
+/// satisfies it, to be consumed with [`Consume`] right away.
+#[derive(Component, Debug, Clone, ScorerBuilder)]
+pub struct ConsumableCarried(pub NeedKind);
+
+fn consumable_carried(
+    mut scorer_query: Query<(&Actor, &mut Score, &ScorerSpan, &ConsumableCarried)>,
+    actor_query: Query<(&Needs, &Inventory)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+) {
+    for (actor, mut score, _span, consumable_carried) in &mut scorer_query {
+        let kind = consumable_carried.0;
+        let (Some(item_definitions), Ok((needs, inventory))) =
+            (item_definitions.as_deref(), actor_query.get(actor.0))
+        else {
+            score.set(0.0);
+            continue;
+        };
+        let carried = inventory.stacks().iter().any(|stack| {
+            item_definitions
+                .get(stack.item.as_str())
+                .is_some_and(|definition| kind.satisfaction(definition) > 0.)
+        });
+        let value = if carried { needs.get(kind) } else { 0.0 };
+        score.set(value.clamp(0., 1.));
+    }
+}
+
+/// Drops the actor's work, fetches the closest item satisfying the need and consumes it.
+pub fn consume_item(kind: NeedKind) -> StepsBuilder {
+    Steps::build()
+        .label("consume_item")
+        .step(StopWorking)
+        .step(SetConsumableTarget(kind))
+        .step(pick_up())
+        .step(Consume(kind))
+}
+
+/// Drops the actor's work and sleeps until rested.
+pub fn go_to_sleep() -> StepsBuilder {
+    Steps::build()
+        .label("go_to_sleep")
+        .step(StopWorking)
+        .step(Sleep)
+}
+
+#[derive(Component, Debug, Clone, ActionBuilder)]
+pub struct SetConsumableTarget(pub NeedKind);
+
+fn set_consumable_target(
+    mut commands: Commands,
+    mut action_query: Query<(
+        &Actor,
+        &mut ActionState,
+        &ActionSpan,
+        &SetConsumableTarget,
+        &Parent,
+    )>,
+    global_transform_query: Query<&GlobalTransform>,
+    item_query: Query<(Entity, &Name, &GlobalTransform), (With<Item>, Without<Reserved>)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+) {
+    for (actor, mut action_state, span, set_consumable_target, steps) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Setting consumable target");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let kind = set_consumable_target.0;
+                let Some(item_definitions) = item_definitions.as_deref() else {
+                    error!("Item definitions not loaded");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Ok(actor_pos) = global_transform_query.get(actor.0).map(|t| t.translation().xy()) else {
+                    error!("Actor should have a global transform");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let closest_item = item_query
+                    .iter()
+                    .filter(|(_, name, _)| {
+                        item_definitions
+                            .get(name.as_str())
+                            .is_some_and(|definition| kind.satisfaction(definition) > 0.)
+                    })
+                    .min_by(|(_, _, a), (_, _, b)| {
+                        let a = a.translation().xy().distance_squared(actor_pos);
+                        let b = b.translation().xy().distance_squared(actor_pos);
+                        a.total_cmp(&b)
+                    });
+                let Some((item_entity, name, _)) = closest_item else {
+                    info!(need = kind.name(), "Nothing to consume");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
+                info!(
+                    item = name.as_str(),
+                    need = kind.name(),
+                    "Setting consumable target"
+                );
+                fetch_item(&mut commands, actor.0, item_entity, steps.get());
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Setting consumable target cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Consumes a single item out of the actor's inventory that satisfies the need.
+#[derive(Component, Debug, Clone, ActionBuilder)]
+pub struct Consume(pub NeedKind);
+
+fn consume(
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan, &Consume)>,
+    mut actor_query: Query<(&mut Inventory, &mut Needs)>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+) {
+    for (actor, mut action_state, span, consume) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Starting consume");
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let kind = consume.0;
+                let Some(item_definitions) = item_definitions.as_deref() else {
+                    error!("Item definitions not loaded");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let Ok((mut inventory, mut needs)) = actor_query.get_mut(actor.0) else {
+                    error!("Actor should have an inventory and needs");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                let consumable = inventory.stacks().iter().find_map(|stack| {
+                    let definition = item_definitions.get(stack.item.as_str())?;
+                    let satisfaction = kind.satisfaction(definition);
+                    (satisfaction > 0.).then(|| (stack.item.clone(), satisfaction))
+                });
+                let Some((item, satisfaction)) = consumable else {
+                    info!(need = kind.name(), "Nothing to consume in inventory");
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+
+                inventory.remove(&item, 1);
+                needs.satisfy(kind, satisfaction);
+                info!(item = item.as_str(), need = kind.name(), "Consumed");
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                info!("Consume cancelled");
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sleeps on the spot until the actor's fatigue is gone.
+#[derive(Component, Debug, Clone, ActionBuilder)]
+pub struct Sleep;
+
+fn sleep(
+    mut commands: Commands,
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<Sleep>>,
+    mut walker_query: Query<&mut Walker>,
+    needs_query: Query<&Needs>,
+) {
+    for (actor, mut action_state, span) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                info!("Falling asleep");
+                if let Ok(mut walker) = walker_query.get_mut(actor.0) {
+                    walker.move_direction = None;
+                }
+                commands.entity(actor.0).insert(Sleeping);
+                *action_state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let Ok(needs) = needs_query.get(actor.0) else {
+                    error!("Actor should have needs");
+                    commands.entity(actor.0).remove::<Sleeping>();
+                    *action_state = ActionState::Failure;
+                    continue;
+                };
+                if needs.fatigue <= 0. {
+                    info!("Woke up rested");
+                    commands.entity(actor.0).remove::<Sleeping>();
+                    *action_state = ActionState::Success;
+                }
+            }
+            ActionState::Cancelled => {
+                info!("Sleep interrupted");
+                commands.entity(actor.0).remove::<Sleeping>();
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
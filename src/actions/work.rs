@@ -1,15 +1,18 @@
-use std::{fmt::Debug, marker::PhantomData};
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
 
 use bevy::{
     math::Vec3Swizzles,
     prelude::{
-        App, Commands, Component, Entity, GlobalTransform, IntoSystemConfigs, Plugin, PreUpdate,
-        Query, With, Without,
+        App, Commands, Component, Entity, EventReader, GlobalTransform, IntoSystemConfigs, Plugin,
+        PreUpdate, Query, Resource, With, Without,
     },
 };
 use big_brain::{
     actions::StepsBuilder,
-    prelude::{ActionBuilder, ActionState, FirstToScore, Highest, Measure, ScorerBuilder, Steps},
+    prelude::{
+        ActionBuilder, ActionState, ConcurrentMode, Concurrently, FirstToScore, Highest, Measure,
+        ScorerBuilder, Steps,
+    },
     scorers::{Score, WinningScorer, WinningScorerBuilder},
     thinker::{ActionSpan, Actor, ScorerSpan, Thinker, ThinkerBuilder},
     BigBrainSet,
@@ -17,69 +20,143 @@ use big_brain::{
 use tracing::{debug, error, info};
 
 use crate::{
-    actions::{action_area::ActionAreaReachable, do_dig_job::do_dig_job, do_fell_job::do_fell_job},
+    actions::{
+        action_area::ActionAreaReachable, do_build_job::do_build_job, do_craft_job::do_craft_job,
+        do_dig_job::do_dig_job, do_fell_job::do_fell_job, do_harvest_job::do_harvest_job,
+        do_haul_job::do_haul_job,
+    },
     labor::{
+        build_structure::ConstructionJob,
         chop_tree::FellingJob,
+        craft::CraftJob,
         dig_tile::DigJob,
-        job::{AssignedJob, AssignedWorker, CanceledJob, Job, JobManagerParams},
+        farm::HarvestJob,
+        haul::HaulRequest,
+        job::{AssignedJob, AssignedWorker, CanceledJob, JobManagerParams, JobPriorityParam},
     },
+    path_request::{clear_path, failed_path_entities, PathFailedEvent, PathRequest, PathVersion},
+    pathfinding::Path,
+    terrain::TerrainParam,
 };
 
-use super::action_area::{ActionAreaParam, GlobalActionArea};
+use super::action_area::{action_area_reachable, ActionAreaParam, GlobalActionArea};
 
 pub struct WorkPlugin;
 
 impl Plugin for WorkPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.add_plugins((
+            JobTypePlugin::<FellingJob>::new(do_fell_job),
+            JobTypePlugin::<DigJob>::new(do_dig_job),
+            JobTypePlugin::<HaulRequest>::new(do_haul_job),
+            JobTypePlugin::<ConstructionJob>::new(do_build_job),
+            JobTypePlugin::<HarvestJob>::new(do_harvest_job),
+            JobTypePlugin::<CraftJob>::new(do_craft_job),
+        ))
+        .add_systems(
             PreUpdate,
-            (
-                currently_assigned_job,
-                currently_assigned_job_type::<FellingJob>,
-                currently_assigned_job_type::<DigJob>,
-                assigned_job_unreachable::<FellingJob>,
-                assigned_job_unreachable::<DigJob>,
-            )
-                .in_set(BigBrainSet::Scorers),
+            currently_assigned_job.in_set(BigBrainSet::Scorers),
         )
         .add_systems(
             PreUpdate,
             (
                 check_job_canceled,
-                pick_job_shortest_path::<FellingJob>,
-                pick_job_shortest_path::<DigJob>,
                 complete_job,
                 cancel_job_assignment,
+                stop_working,
             )
                 .in_set(BigBrainSet::Actions),
         );
     }
 }
 
+/// Registers the scorer and action systems that let workers score, pick and execute jobs of
+/// type `T`, and adds the job type to the [`JobTypes`] that worker thinkers are built from.
+///
+/// Add one per job type, before any workers are spawned.
+pub struct JobTypePlugin<T> {
+    do_job_builder: Arc<dyn Fn() -> StepsBuilder + Send + Sync>,
+    job_type: PhantomData<T>,
+}
+
+impl<T> JobTypePlugin<T>
+where
+    T: Component + GlobalActionArea + Debug,
+{
+    /// Job type whose jobs are done with the steps built by `job_steps`.
+    pub fn new<A: ActionBuilder + 'static>(job_steps: fn() -> A) -> Self {
+        Self {
+            do_job_builder: Arc::new(move || do_job::<T, _>(job_steps())),
+            job_type: PhantomData,
+        }
+    }
+}
+
+impl<T> Plugin for JobTypePlugin<T>
+where
+    T: Component + GlobalActionArea + Debug,
+{
+    fn build(&self, app: &mut App) {
+        let do_job_builder = Arc::clone(&self.do_job_builder);
+        app.init_resource::<JobTypes>()
+            .world
+            .resource_mut::<JobTypes>()
+            .0
+            .push(JobType {
+                add_to_thinker: Arc::new(move |thinker: ThinkerBuilder| {
+                    thinker.when(job_scorer_builder::<T>(), do_job_builder())
+                }),
+                add_to_scorer: |scorer| {
+                    scorer.push(ActionAreaReachable::<T, Without<AssignedWorker>>::build())
+                },
+            });
+
+        app.add_systems(
+            PreUpdate,
+            (
+                currently_assigned_job_type::<T>,
+                assigned_job_unreachable::<T>,
+                action_area_reachable::<T, Without<AssignedWorker>>,
+            )
+                .in_set(BigBrainSet::Scorers),
+        )
+        .add_systems(
+            PreUpdate,
+            pick_job_shortest_path::<T>.in_set(BigBrainSet::Actions),
+        );
+    }
+}
+
+/// Job types workers can do, in the order their [`JobTypePlugin`]s were added.
+#[derive(Resource, Default)]
+pub struct JobTypes(Vec<JobType>);
+
+struct JobType {
+    /// Adds doing jobs of the type to a worker thinker.
+    add_to_thinker: Arc<dyn Fn(ThinkerBuilder) -> ThinkerBuilder + Send + Sync>,
+    /// Adds whether a job of the type is reachable to a worker scorer.
+    add_to_scorer: fn(WinningScorerBuilder) -> WinningScorerBuilder,
+}
+
 /// Create a worker thinker builder.
 ///
-/// This thinker builder will create a thinker that can do jobs.
-pub fn worker_thinker_builder() -> ThinkerBuilder {
+/// This thinker builder will create a thinker that can do jobs of all `job_types`.
+pub fn worker_thinker_builder(job_types: &JobTypes) -> ThinkerBuilder {
     info!("Building worker thinker");
-    Thinker::build()
+    let thinker = Thinker::build()
         .label("worker")
         .picker(Highest)
-        .when(AssignedJobUnreachable, CancelJobAssignment)
-        .when(
-            job_scorer_builder::<FellingJob>(),
-            do_job::<FellingJob, _>(do_fell_job()),
-        )
-        .when(
-            job_scorer_builder::<DigJob>(),
-            do_job::<DigJob, _>(do_dig_job()),
-        )
+        .when(AssignedJobUnreachable, CancelJobAssignment);
+    job_types.0.iter().fold(thinker, |thinker, job_type| {
+        (job_type.add_to_thinker)(thinker)
+    })
 }
 
 /// Create a job scorer builder.
 ///
 /// This scorer builder will score jobs of type T based on whether they are assigned to the actor or
 /// if they are reachable from the actor's current position
-fn job_scorer_builder<T>() -> WinningScorerBuilder
+pub fn job_scorer_builder<T>() -> WinningScorerBuilder
 where
     T: bevy::prelude::Component + GlobalActionArea + std::fmt::Debug,
 {
@@ -89,21 +166,32 @@ where
     dig_job_assigned_or_available
 }
 
-fn do_job<T: Component + Debug, A: ActionBuilder + 'static>(job_steps: A) -> StepsBuilder {
+/// Create a do job action builder.
+///
+/// Picks the job of type T with the shortest commute, runs `job_steps` and completes the job.
+/// `job_steps` are interrupted when the job is canceled or taken away from the worker.
+pub fn do_job<T: Component + Debug, A: ActionBuilder + 'static>(job_steps: A) -> StepsBuilder {
     info!("Building do_job action");
+    let interruptible_job_steps = Concurrently::build()
+        .mode(ConcurrentMode::Race)
+        .label("interruptible_job")
+        .push(CheckJobCanceled)
+        .push(job_steps);
     Steps::build()
         .label("do_job")
         .step(PickJobBuilder::<T>(PhantomData))
-        .step(job_steps)
+        .step(interruptible_job_steps)
         .step(CompleteJob)
 }
 
-pub fn worker_scorer_builder() -> WinningScorerBuilder {
+/// Create a worker scorer builder, scoring whether there is a job of any of `job_types` to do.
+pub fn worker_scorer_builder(job_types: &JobTypes) -> WinningScorerBuilder {
     info!("Building worker scorer");
-    WinningScorer::build(0.8)
-        .label("worker")
-        .push(ActionAreaReachable::<FellingJob, Without<AssignedWorker>>::build())
-        .push(ActionAreaReachable::<DigJob, Without<AssignedWorker>>::build())
+    let scorer = WinningScorer::build(0.8).label("worker");
+    job_types
+        .0
+        .iter()
+        .fold(scorer, |scorer, job_type| (job_type.add_to_scorer)(scorer))
         .push(CurrentlyAssignedJob)
 }
 #[derive(Component, Debug, Clone, ScorerBuilder)]
@@ -126,31 +214,60 @@ fn currently_assigned_job(
 pub struct AssignedJobUnreachable;
 
 fn assigned_job_unreachable<T>(
-    mut actor_query: Query<(&Actor, &mut Score, &ScorerSpan), With<AssignedJobUnreachable>>,
+    mut commands: Commands,
+    mut actor_query: Query<
+        (
+            Entity,
+            &Actor,
+            &mut Score,
+            &ScorerSpan,
+            Option<&Path>,
+            Option<&PathRequest>,
+        ),
+        With<AssignedJobUnreachable>,
+    >,
     assigned_job_query: Query<&AssignedJob>,
     job_query: Query<&T>,
     global_transform_query: Query<&GlobalTransform>,
+    mut path_failed_events: EventReader<PathFailedEvent>,
     action_area_param: ActionAreaParam<T>,
 ) where
     T: Component + GlobalActionArea + std::fmt::Debug,
 {
-    for (actor, mut score, _span) in &mut actor_query {
-        let Ok(actor_pos) = global_transform_query.get(actor.0).map(|t| t.translation().xy()) else {
-            error!("Actor should have a global transform");
+    let failed_paths = failed_path_entities(&mut path_failed_events);
+    for (scorer_entity, actor, mut score, _span, opt_path, opt_request) in &mut actor_query {
+        let Ok(assigned_job) = assigned_job_query.get(actor.0) else {
+            if opt_path.is_some() || opt_request.is_some() {
+                clear_path(&mut commands, scorer_entity);
+            }
             score.set(0.0);
             continue;
         };
-        let path_to_job = assigned_job_query
-            .get(actor.0)
-            .and_then(|assigned_job| job_query.get(assigned_job.0))
-            .ok()
-            .and_then(|job| action_area_param.path_to_action_area(actor_pos, job));
+        // Every job type has its own system, only the one of the assigned job's type scores it
+        let Ok(job) = job_query.get(assigned_job.0) else { continue; };
 
-        if path_to_job.is_none() {
+        if failed_paths.contains(&scorer_entity) {
             score.set(1.0);
-        } else {
+        } else if opt_path.is_some() {
             score.set(0.0);
+        } else if opt_request.is_some() {
+            // Keep the last score until the search is done
+            continue;
         }
+
+        let Ok(actor_pos) = global_transform_query.get(actor.0).map(|t| t.translation().xy()) else {
+            error!("Actor should have a global transform");
+            score.set(0.0);
+            continue;
+        };
+        let Some(action_area) = action_area_param.global_action_area(job) else {
+            score.set(1.0);
+            continue;
+        };
+        commands
+            .entity(scorer_entity)
+            .remove::<(Path, PathVersion)>()
+            .insert(PathRequest::to_any(actor_pos, action_area.0));
     }
 }
 
@@ -190,6 +307,35 @@ fn cancel_job_assignment(
     }
 }
 
+/// Gives up the actor's assigned job, if any, so someone else can take it while the actor is busy
+/// with something else.
+#[derive(Component, Debug, Clone, ActionBuilder)]
+pub struct StopWorking;
+
+fn stop_working(
+    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<StopWorking>>,
+    assigned_job_query: Query<&AssignedJob>,
+    mut job_manager_params: JobManagerParams,
+) {
+    for (actor, mut action_state, span) in &mut action_query {
+        let _guard = span.span().enter();
+
+        match *action_state {
+            ActionState::Requested => {
+                if let Ok(assigned_job) = assigned_job_query.get(actor.0) {
+                    info!(job = ?assigned_job.0, "Stopping work");
+                    job_manager_params.cancel_job_assignment(assigned_job.0, actor.0);
+                }
+                *action_state = ActionState::Success;
+            }
+            ActionState::Cancelled => {
+                *action_state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct CurrentlyAssignedJobType<T: Component>(PhantomData<T>);
 
@@ -252,54 +398,126 @@ where
 #[derive(Component, Debug, Clone)]
 pub struct PickJob<T: Component>(std::marker::PhantomData<T>);
 
+/// Priority of the jobs a job picker is looking for, lowered when none of them can be reached.
+#[derive(Component, Debug)]
+pub struct PickJobPriority(u32);
+
+/// Picks the closest job of type `T` the actor is eligible for among the jobs of the highest
+/// priority, falling back to lower priorities when none of them can be reached.
 pub fn pick_job_shortest_path<T: Component + GlobalActionArea>(
-    mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<PickJob<T>>>,
-    job_query: Query<(Entity, &T), Without<AssignedWorker>>,
+    mut commands: Commands,
+    mut action_query: Query<
+        (
+            Entity,
+            &Actor,
+            &mut ActionState,
+            &ActionSpan,
+            Option<&Path>,
+            Option<&PathRequest>,
+            Option<&PickJobPriority>,
+        ),
+        With<PickJob<T>>,
+    >,
     global_transform_query: Query<&GlobalTransform>,
+    mut path_failed_events: EventReader<PathFailedEvent>,
     mut job_manager_params: JobManagerParams,
-    action_area_param: ActionAreaParam<T>,
+    action_area_param: ActionAreaParam<T, Without<AssignedWorker>>,
+    job_priority_param: JobPriorityParam,
+    terrain: TerrainParam,
 ) {
-    let jobs: Vec<_> = job_query.iter().collect();
-    if jobs.is_empty() {
-        // No jobs to pick from
-        return;
-    }
+    let failed_paths = failed_path_entities(&mut path_failed_events);
 
-    for (actor, mut action_state, span) in &mut action_query {
+    for (action_entity, actor, mut action_state, span, opt_path, opt_request, opt_priority) in
+        &mut action_query
+    {
         let _guard = span.span().enter();
 
         match *action_state {
             ActionState::Requested => {
                 info!("Starting picking job");
+                clear_path(&mut commands, action_entity);
+                commands.entity(action_entity).remove::<PickJobPriority>();
                 *action_state = ActionState::Executing;
             }
             ActionState::Executing => {
-                info!("Picking job");
-                let Ok(actor_position) = global_transform_query.get(actor.0).map(|t| t.translation().xy()) else {
-                    error!("Actor should have a global transform");
-                    *action_state = ActionState::Failure;
-                    continue;
-                };
-
-                let shortest_path_job = jobs
-                    .iter()
-                    .flat_map(|(job_entity, job)| {
-                        let path = action_area_param.path_to_action_area(actor_position, job)?;
-                        Some((path, *job_entity))
+                let job_priorities: Vec<_> = action_area_param
+                    .eligible_actions(actor.0)
+                    .map(|(job_entity, job)| {
+                        (job_entity, job, job_priority_param.priority(job_entity))
                     })
-                    .min_by_key(|(path, _)| path.0.len())
-                    .map(|(_, job_entity)| job_entity);
-                if let Some(job_entity) = shortest_path_job {
-                    info!(job=?job_entity, "Picked job with shortest commute");
-                    job_manager_params.assign_job(job_entity, actor.0);
-                    *action_state = ActionState::Success;
-                } else {
-                    info!("No jobs left to pick");
-                    *action_state = ActionState::Failure;
+                    .collect();
+
+                if failed_paths.contains(&action_entity) {
+                    let Some(failed_priority) = opt_priority.map(|priority| priority.0) else {
+                        info!("No jobs left to pick");
+                        *action_state = ActionState::Failure;
+                        continue;
+                    };
+                    let next_priority = job_priorities
+                        .iter()
+                        .map(|(_, _, priority)| *priority)
+                        .filter(|priority| *priority < failed_priority)
+                        .max();
+                    if let Some(next_priority) = next_priority {
+                        debug!(priority = next_priority, "Looking for lower priority jobs");
+                        clear_path(&mut commands, action_entity);
+                        commands
+                            .entity(action_entity)
+                            .insert(PickJobPriority(next_priority));
+                    } else {
+                        info!("No jobs left to pick");
+                        *action_state = ActionState::Failure;
+                    }
+                } else if let Some(path) = opt_path {
+                    // The path leads to the closest action area, pick the job it belongs to
+                    let shortest_path_job = path.0.last().and_then(|&end_tile_pos| {
+                        action_area_param.action_at_tile(actor.0, end_tile_pos, &terrain)
+                    });
+                    if let Some(job_entity) = shortest_path_job {
+                        info!(job=?job_entity, "Picked job with shortest commute");
+                        job_manager_params.assign_job(job_entity, actor.0);
+                        clear_path(&mut commands, action_entity);
+                        commands.entity(action_entity).remove::<PickJobPriority>();
+                        *action_state = ActionState::Success;
+                    } else {
+                        // The job was taken while searching, look for the next closest one
+                        commands
+                            .entity(action_entity)
+                            .remove::<(Path, PathVersion, PickJobPriority)>();
+                    }
+                } else if opt_request.is_none() {
+                    let Some(priority) = opt_priority.map(|priority| priority.0).or_else(|| {
+                        job_priorities
+                            .iter()
+                            .map(|(_, _, priority)| *priority)
+                            .max()
+                    }) else {
+                        // No jobs to pick from
+                        continue;
+                    };
+                    info!(priority, "Picking job");
+                    let Ok(actor_position) = global_transform_query.get(actor.0).map(|t| t.translation().xy()) else {
+                        error!("Actor should have a global transform");
+                        *action_state = ActionState::Failure;
+                        continue;
+                    };
+
+                    let action_area_positions: Vec<_> = job_priorities
+                        .iter()
+                        .filter(|(_, _, job_priority)| *job_priority == priority)
+                        .flat_map(|(_, job, _)| action_area_param.global_action_area(job))
+                        .flat_map(|action_area| action_area.0)
+                        .collect();
+                    commands.entity(action_entity).insert((
+                        PickJobPriority(priority),
+                        PathRequest::to_any(actor_position, action_area_positions),
+                    ));
                 }
             }
             ActionState::Cancelled => {
                 info!("Pickup cancelled");
+                clear_path(&mut commands, action_entity);
+                commands.entity(action_entity).remove::<PickJobPriority>();
                 *action_state = ActionState::Failure;
             }
             _ => {}
@@ -314,6 +532,7 @@ pub fn complete_job(
     mut commands: Commands,
     mut action_query: Query<(&Actor, &mut ActionState, &ActionSpan), With<CompleteJob>>,
     assigned_job_query: Query<&AssignedJob>,
+    canceled_jobs_query: Query<(), With<CanceledJob>>,
     mut job_manager_params: JobManagerParams,
 ) {
     for (actor, mut action_state, span) in &mut action_query {
@@ -327,6 +546,12 @@ pub fn complete_job(
             ActionState::Executing => {
                 info!("Completing job");
                 if let Ok(assigned_job) = assigned_job_query.get(actor.0) {
+                    if canceled_jobs_query.contains(assigned_job.0) {
+                        info!(job=?assigned_job, "Job was canceled");
+                        commands.entity(actor.0).remove::<AssignedJob>();
+                        *action_state = ActionState::Failure;
+                        continue;
+                    }
                     info!(job=?assigned_job, "Completing job");
                     job_manager_params.complete_job(assigned_job.0, actor.0);
                     commands.entity(actor.0).remove::<AssignedJob>();
@@ -345,6 +570,8 @@ pub fn complete_job(
     }
 }
 
+/// Succeeds once the actor's job is canceled or no longer assigned to it, racing the job's steps
+/// to interrupt them.
 #[derive(Component, Debug, Clone, ActionBuilder)]
 pub struct CheckJobCanceled;
 
@@ -369,7 +596,8 @@ fn check_job_canceled(
                         *action_state = ActionState::Success;
                     }
                 } else {
-                    *action_state = ActionState::Cancelled;
+                    info!("Job is no longer assigned");
+                    *action_state = ActionState::Success;
                 }
             }
             ActionState::Cancelled => {
@@ -4,6 +4,8 @@ use bevy::{
     utils::{HashMap, HashSet},
 };
 
+use crate::labor::stockpile::Stockpiled;
+
 pub struct BuildingMaterialPlugin;
 
 impl Plugin for BuildingMaterialPlugin {
@@ -65,10 +67,17 @@ pub struct Reserved;
 #[derive(SystemParam)]
 pub struct BuildingMaterialLocator<'w, 's> {
     building_material_registry: ResMut<'w, BuildingMaterialRegistry>,
-    query: Query<'w, 's, &'static GlobalTransform, (With<BuildingMaterial>, Without<Reserved>)>,
+    query: Query<
+        'w,
+        's,
+        (&'static GlobalTransform, Option<&'static Stockpiled>),
+        (With<BuildingMaterial>, Without<Reserved>),
+    >,
 }
 
 impl BuildingMaterialLocator<'_, '_> {
+    /// Closest unreserved building material named `name`, items lying in a stockpile are taken
+    /// before loose ones.
     pub fn get_closest(&self, name: &Name, pos: Vec3) -> Option<Entity> {
         self.building_material_registry
             .get_all(name)
@@ -78,13 +87,19 @@ impl BuildingMaterialLocator<'_, '_> {
                     .filter_map(|entity| {
                         self.query
                             .get(*entity)
-                            .map(|transform| {
-                                (entity, transform.translation().distance_squared(pos))
+                            .map(|(transform, opt_stockpiled)| {
+                                (
+                                    entity,
+                                    opt_stockpiled.is_none(),
+                                    transform.translation().distance_squared(pos),
+                                )
                             })
                             .ok()
                     })
-                    .min_by(|(_, dist1), (_, dist2)| dist1.partial_cmp(dist2).unwrap())
-                    .map(|(entity, _)| *entity)
+                    .min_by(|(_, loose1, dist1), (_, loose2, dist2)| {
+                        loose1.cmp(loose2).then(dist1.total_cmp(dist2))
+                    })
+                    .map(|(entity, _, _)| *entity)
             })
     }
 }
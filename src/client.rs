@@ -0,0 +1,36 @@
+use bevy::app::{PluginGroup, PluginGroupBuilder};
+use bevy_ecs_tilemap::TilemapPlugin;
+use bevy_egui::EguiPlugin;
+
+use crate::{
+    crafting_panel::CraftingPanelPlugin, cursor_position::CursorPositionPlugin, debug::DebugPlugin,
+    designation_layer::DesignationLayerPlugin, dwarf_panel::DwarfPanelPlugin,
+    hovered_tile::HoveredTilePlugin, labor_panel::LaborPanelPlugin, main_camera::MainCameraPlugin,
+    pan_zoom_camera2d::PanZoomCamera2dPlugin, stockpile_panel::StockpilePanelPlugin,
+    toolbar::ToolbarPlugin,
+};
+
+/// Rendering, input and UI for playing the simulation interactively.
+///
+/// Expects `DefaultPlugins` and [`SimulationPlugins`](crate::SimulationPlugins) to be added as
+/// well.
+pub struct ClientPlugins;
+
+impl PluginGroup for ClientPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(TilemapPlugin)
+            .add(EguiPlugin)
+            .add(DebugPlugin)
+            .add(CursorPositionPlugin)
+            .add(PanZoomCamera2dPlugin)
+            .add(HoveredTilePlugin)
+            .add(MainCameraPlugin)
+            .add(DesignationLayerPlugin)
+            .add(ToolbarPlugin)
+            .add(LaborPanelPlugin)
+            .add(DwarfPanelPlugin)
+            .add(CraftingPanelPlugin)
+            .add(StockpilePanelPlugin)
+    }
+}
@@ -1,52 +1,67 @@
 use bevy::{math::Vec3Swizzles, prelude::*, utils::HashSet};
-use bevy_ecs_tilemap::tiles::TilePos;
+use bevy_rapier2d::prelude::Group;
 
-use crate::terrain::{Terrain, TerrainParam};
+use crate::{structure::PlacedStructure, terrain::TerrainParam};
 
 pub struct ClimbablePlugin;
 
 impl Plugin for ClimbablePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (create_climbable_map, update_climbable_map));
+        app.init_resource::<ClimbableMap>()
+            .add_event::<ClimbableChangedEvent>()
+            .add_systems(Update, update_climbable_map);
     }
 }
 
 #[derive(Component)]
 pub struct Climbable;
 
-#[derive(Component, Default)]
-pub struct ClimbableMap(HashSet<TilePos>);
+pub const CLIMBABLE_COLLISION_GROUP: Group = Group::GROUP_6;
+
+/// World tile positions that can be climbed, across all terrain chunks.
+#[derive(Resource, Default, Clone)]
+pub struct ClimbableMap(HashSet<IVec2>);
 
 impl ClimbableMap {
-    pub fn mark_climbable(&mut self, tile_pos: TilePos) {
+    pub fn mark_climbable(&mut self, tile_pos: IVec2) {
         self.0.insert(tile_pos);
     }
 
-    pub fn is_climbable(&self, tile_pos: TilePos) -> bool {
+    pub fn is_climbable(&self, tile_pos: IVec2) -> bool {
         self.0.contains(&tile_pos)
     }
-}
 
-fn create_climbable_map(
-    mut commands: Commands,
-    mut terrain_query: Query<Entity, (Added<Terrain>, Without<ClimbableMap>)>,
-) {
-    for entity in &mut terrain_query {
-        commands.entity(entity).insert(ClimbableMap::default());
+    pub fn iter(&self) -> impl Iterator<Item = &IVec2> {
+        self.0.iter()
     }
 }
 
+/// Sent when a tile becomes climbable.
+#[derive(Event)]
+pub struct ClimbableChangedEvent {
+    pub tile_pos: IVec2,
+}
+
+/// Marks the tiles of added climbables, every tile of their footprint for structures.
 fn update_climbable_map(
-    mut climbable_map: Query<&mut ClimbableMap, With<Terrain>>,
+    mut climbable_map: ResMut<ClimbableMap>,
     terrain: TerrainParam,
-    addded_climbabes: Query<&GlobalTransform, Added<Climbable>>,
+    addded_climbabes: Query<(&GlobalTransform, Option<&PlacedStructure>), Added<Climbable>>,
+    mut climbable_changed_events: EventWriter<ClimbableChangedEvent>,
 ) {
-    let Ok(mut climbable_map) = climbable_map.get_single_mut() else { return; };
-    for added_climbable in &addded_climbabes {
-        if let Some(climbable_tile_pos) =
-            terrain.global_to_tile_pos(added_climbable.translation().xy())
-        {
+    for (added_climbable, opt_structure) in &addded_climbabes {
+        let climbable_tiles: Vec<IVec2> = match opt_structure {
+            Some(structure) => structure.tiles().collect(),
+            None => terrain
+                .global_to_tile_pos(added_climbable.translation().xy())
+                .into_iter()
+                .collect(),
+        };
+        for climbable_tile_pos in climbable_tiles {
             climbable_map.mark_climbable(climbable_tile_pos);
+            climbable_changed_events.send(ClimbableChangedEvent {
+                tile_pos: climbable_tile_pos,
+            });
         }
     }
 }
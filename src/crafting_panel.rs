@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    item::ItemDefinitions,
+    labor::{
+        build_structure::BuildingMaterialsNeeded,
+        craft::{spawn_craft_order, CraftOrder, Workshop},
+    },
+};
+
+pub struct CraftingPanelPlugin;
+
+impl Plugin for CraftingPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            crafting_panel.run_if(resource_exists::<ItemDefinitions>()),
+        );
+    }
+}
+
+/// Orders the items that have a recipe at the workshop with the fewest open orders, and lists the
+/// open orders.
+fn crafting_panel(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    workshop_query: Query<Entity, With<Workshop>>,
+    order_query: Query<(&CraftOrder, &Parent, Option<&BuildingMaterialsNeeded>)>,
+    item_definitions: Res<ItemDefinitions>,
+) {
+    egui::Window::new("Crafting").show(contexts.ctx_mut(), |ui| {
+        let least_busy_workshop = workshop_query.iter().min_by_key(|workshop| {
+            order_query
+                .iter()
+                .filter(|(_, parent, _)| parent.get() == *workshop)
+                .count()
+        });
+        let Some(workshop) = least_busy_workshop else {
+            ui.label("Build a workshop to craft items");
+            return;
+        };
+
+        for definition in &item_definitions.0 {
+            let Some(recipe) = &definition.recipe else { continue; };
+            ui.horizontal(|ui| {
+                ui.label(definition.name.as_str());
+                let materials = recipe
+                    .materials
+                    .iter()
+                    .map(|(item, amount)| format!("{amount} {item}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ui.label(materials);
+                if ui.button("Order").clicked() {
+                    let order = spawn_craft_order(
+                        &mut commands,
+                        workshop,
+                        definition.name.clone(),
+                        recipe.materials_needed(),
+                    );
+                    info!(
+                        item = definition.name.as_str(),
+                        ?workshop,
+                        ?order,
+                        "Ordered crafting"
+                    );
+                }
+            });
+        }
+
+        ui.separator();
+        for (order, _, opt_materials_needed) in &order_query {
+            let waiting = opt_materials_needed
+                .map_or(false, |materials_needed| !materials_needed.all_delivered());
+            let status = if waiting {
+                "waiting for materials"
+            } else {
+                "ready to craft"
+            };
+            ui.label(format!("{}: {status}", order.item));
+        }
+    });
+}
@@ -29,7 +29,10 @@ impl Plugin for PathfindingDebugPlugin {
                     .run_if(in_state(PathfindingDebugState::Enabled))
                     .in_set(DebugSet),
             )
-            .add_systems(OnEnter(MainState::Game), setup_walkable_layer)
+            .add_systems(
+                Update,
+                setup_walkable_layer.run_if(in_state(MainState::Game)),
+            )
             .add_systems(OnExit(PathfindingDebugState::Enabled), despawn_debug_nodes);
     }
 }
@@ -40,62 +43,63 @@ struct WalkableLayer;
 fn setup_walkable_layer(
     mut commands: Commands,
     config: Res<TerrainSettings>,
+    new_chunk_query: Query<Entity, (With<Terrain>, Added<TileStorage>)>,
     asset_server: Res<AssetServer>,
 ) {
-    let texture_handle = asset_server.load("textures/terrain.png");
-
-    let tile_size = TilemapTileSize {
-        x: config.cell_size,
-        y: config.cell_size,
-    };
-
-    let grid_size = tile_size.into();
-
-    let tilemap_size = TilemapSize {
-        x: config.width,
-        y: config.height,
-    };
-
-    let storage = TileStorage::empty(tilemap_size);
-
-    let map_transform = Transform::from_translation(Vec3::new(
-        -(config.width as f32 * config.cell_size / 2.),
-        -(config.height as f32 * config.cell_size / 2.),
-        20.,
-    ));
-
-    commands.spawn((
-        Name::new("Walkable Layer"),
-        WalkableLayer,
-        TilemapBundle {
-            grid_size,
-            map_type: TilemapType::Square,
-            size: tilemap_size,
-            storage,
-            texture: TilemapTexture::Single(texture_handle),
-            tile_size,
-            transform: map_transform,
-            ..default()
-        },
-    ));
+    for chunk_entity in &new_chunk_query {
+        let texture_handle = asset_server.load("textures/terrain.png");
+
+        let tile_size = TilemapTileSize {
+            x: config.cell_size,
+            y: config.cell_size,
+        };
+
+        let grid_size = tile_size.into();
+
+        let tilemap_size = TilemapSize {
+            x: config.width,
+            y: config.height,
+        };
+
+        let storage = TileStorage::empty(tilemap_size);
+
+        commands.entity(chunk_entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("Walkable Layer"),
+                WalkableLayer,
+                TilemapBundle {
+                    grid_size,
+                    map_type: TilemapType::Square,
+                    size: tilemap_size,
+                    storage,
+                    texture: TilemapTexture::Single(texture_handle),
+                    tile_size,
+                    transform: Transform::from_xyz(0., 0., 20.),
+                    ..default()
+                },
+            ));
+        });
+    }
 }
 
 fn highlight_walkable_tiles(
     mut commands: Commands,
-    mut tilemap_query: Query<(Entity, &mut TileStorage), (With<WalkableLayer>, Without<Terrain>)>,
+    mut tilemap_query: Query<
+        (Entity, &Parent, &mut TileStorage),
+        (With<WalkableLayer>, Without<Terrain>),
+    >,
     config: Res<TerrainSettings>,
     terrain: TerrainParam,
 ) {
-    let terrain_data = terrain.terrain_data_query.single();
-
     const WALKABLE_COLOR: Color = Color::rgba(0., 1., 0., 0.3);
 
     // for each tile, check if it's walkable and set the tile to the correct color
-    for (tilemap_entity, mut storage) in &mut tilemap_query {
+    for (tilemap_entity, chunk_entity, mut storage) in &mut tilemap_query {
+        let Ok((_, chunk, _, _)) = terrain.chunk_query.get(chunk_entity.get()) else { continue; };
         for x in 0..config.width {
             for y in 0..config.height {
                 let tile_pos = TilePos::new(x, y);
-                let walkable = can_stand(terrain_data, tile_pos);
+                let walkable = can_stand(&terrain, config.chunk_to_tile(chunk.0, tile_pos));
 
                 if walkable && storage.get(&tile_pos).is_none() {
                     let tile_entity = commands
@@ -155,7 +159,7 @@ fn render_paths(
         }
         let mut debug_nodes = Vec::new();
         for node in path.0.iter() {
-            let node_position = terrain.tile_to_global_pos((*node).into());
+            let Some(node_position) = terrain.tile_to_global_pos(*node) else { continue; };
             let debug_node_id = commands
                 .spawn((
                     Name::new("Pathfinding debug node"),
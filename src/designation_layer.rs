@@ -6,21 +6,31 @@ use bevy_ecs_tilemap::{
 };
 
 use crate::{
-    main_state::MainState, terrain::TileDestroyedEvent, terrain_settings::TerrainSettings,
+    main_state::MainState,
+    terrain::{Terrain, TileDestroyedEvent},
+    terrain_settings::TerrainSettings,
 };
 
 pub struct DesignationLayerPlugin;
 
 impl Plugin for DesignationLayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(MainState::Game), setup_designation_layer)
+        app.init_resource::<DesignationArea>()
+            .add_systems(
+                Update,
+                preview_designation_area
+                    .run_if(in_state(MainState::Game))
+                    .run_if(resource_exists::<TerrainSettings>()),
+            )
             .add_systems(
                 Update,
                 (
+                    setup_designation_layer,
                     apply_deferred,
                     highlight_designated_tile,
                     unhighlight_designated_tile,
                     highlight_designated_mesh,
+                    unhighlight_designated_mesh,
                 )
                     .chain()
                     .run_if(in_state(MainState::Game))
@@ -37,59 +47,201 @@ struct HoverLayer;
 
 pub const DESIGNATION_LAYER_Z: f32 = 1.0;
 
+/// Spawns a designation layer as a child of every new terrain chunk.
 fn setup_designation_layer(
     mut commands: Commands,
     config: Res<TerrainSettings>,
-
+    new_chunk_query: Query<Entity, (With<Terrain>, Added<TileStorage>)>,
     asset_server: Res<AssetServer>,
 ) {
-    let texture_handle = asset_server.load("textures/terrain.png");
-    let tile_size = TilemapTileSize {
-        x: config.cell_size,
-        y: config.cell_size,
-    };
-    let grid_size = tile_size.into();
+    for chunk_entity in &new_chunk_query {
+        let texture_handle = asset_server.load("textures/terrain.png");
+        let tile_size = TilemapTileSize {
+            x: config.cell_size,
+            y: config.cell_size,
+        };
+        let grid_size = tile_size.into();
 
-    let tilemap_size = TilemapSize {
-        x: config.width,
-        y: config.height,
-    };
+        let tilemap_size = TilemapSize {
+            x: config.width,
+            y: config.height,
+        };
 
-    let storage = TileStorage::empty(tilemap_size);
-
-    let map_transform = Transform::from_translation(Vec3::new(
-        -(config.width as f32 * config.cell_size / 2.) + 0.5 * config.cell_size,
-        -(config.height as f32 * config.cell_size / 2.) + 0.5 * config.cell_size,
-        DESIGNATION_LAYER_Z,
-    ));
-    commands.spawn((
-        HoverLayer,
-        Name::new("Designated Tile layer"),
-        TilemapBundle {
-            grid_size,
-            map_type: TilemapType::Square,
-            size: tilemap_size,
-            storage,
-            texture: TilemapTexture::Single(texture_handle),
-            tile_size,
-            transform: map_transform,
-            ..default()
-        },
-    ));
+        let storage = TileStorage::empty(tilemap_size);
+
+        commands.entity(chunk_entity).with_children(|parent| {
+            parent.spawn((
+                HoverLayer,
+                Name::new("Designated Tile layer"),
+                TilemapBundle {
+                    grid_size,
+                    map_type: TilemapType::Square,
+                    size: tilemap_size,
+                    storage,
+                    texture: TilemapTexture::Single(texture_handle),
+                    tile_size,
+                    transform: Transform::from_xyz(0., 0., DESIGNATION_LAYER_Z),
+                    ..default()
+                },
+            ));
+        });
+    }
 }
 
 #[derive(Component)]
 pub struct Designated;
 
+/// Tiles from `min` to `max` inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileArea {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl TileArea {
+    pub fn new(corner: IVec2, opposite_corner: IVec2) -> Self {
+        Self {
+            min: corner.min(opposite_corner),
+            max: corner.max(opposite_corner),
+        }
+    }
+
+    pub fn contains(&self, tile_pos: IVec2) -> bool {
+        tile_pos.cmpge(self.min).all() && tile_pos.cmple(self.max).all()
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = IVec2> {
+        let TileArea { min, max } = *self;
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+    }
+
+    /// Center and size of the area in world units.
+    pub fn world_rect(&self, terrain_settings: &TerrainSettings) -> (Vec2, Vec2) {
+        let center = (terrain_settings.tile_to_world(self.min)
+            + terrain_settings.tile_to_world(self.max))
+            / 2.;
+        let size = (self.max - self.min + IVec2::ONE).as_vec2() * terrain_settings.cell_size;
+        (center, size)
+    }
+}
+
+/// How a dragged out [`DesignationArea`] changes the designations in it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DesignationMode {
+    #[default]
+    Add,
+    /// Dragged while holding Ctrl, removes the designations in the area.
+    Remove,
+}
+
+/// Area a designation tool is dragging out, from the tile the drag started at to the tile under
+/// the cursor.
+#[derive(Resource, Default, Debug)]
+pub struct DesignationArea {
+    start: Option<IVec2>,
+    end: IVec2,
+    pub mode: DesignationMode,
+}
+
+impl DesignationArea {
+    /// Follows a drag with the left mouse button to `tile_pos`, returns the area when the button
+    /// is released.
+    pub fn drag(
+        &mut self,
+        mouse_button_input: &Input<MouseButton>,
+        keyboard_input: &Input<KeyCode>,
+        tile_pos: IVec2,
+    ) -> Option<TileArea> {
+        if mouse_button_input.just_pressed(MouseButton::Left) {
+            self.start = Some(tile_pos);
+        }
+        self.end = tile_pos;
+        self.mode = if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+            DesignationMode::Remove
+        } else {
+            DesignationMode::Add
+        };
+        if mouse_button_input.just_released(MouseButton::Left) {
+            let start = self.start.take()?;
+            return Some(TileArea::new(start, tile_pos));
+        }
+        None
+    }
+
+    /// The area dragged out so far, if a drag is in progress.
+    pub fn area(&self) -> Option<TileArea> {
+        self.start.map(|start| TileArea::new(start, self.end))
+    }
+
+    pub fn clear(&mut self) {
+        self.start = None;
+    }
+}
+
+/// Stops a drag when its tool is switched off.
+pub fn clear_designation_area(mut designation_area: ResMut<DesignationArea>) {
+    designation_area.clear();
+}
+
+#[derive(Component)]
+struct DesignationPreview;
+
+const ADD_PREVIEW_COLOR: Color = Color::rgba(1., 1., 0.2, 0.3);
+const REMOVE_PREVIEW_COLOR: Color = Color::rgba(1., 0.3, 0.3, 0.3);
+
+/// Shows the area being dragged out, tinted by whether it adds or removes designations.
+fn preview_designation_area(
+    mut commands: Commands,
+    designation_area: Res<DesignationArea>,
+    terrain_settings: Res<TerrainSettings>,
+    mut preview_query: Query<(Entity, &mut Transform, &mut Sprite), With<DesignationPreview>>,
+) {
+    let Some(area) = designation_area.area() else {
+        for (preview_entity, _, _) in &preview_query {
+            commands.entity(preview_entity).despawn_recursive();
+        }
+        return;
+    };
+
+    let (center, size) = area.world_rect(&terrain_settings);
+    let color = match designation_area.mode {
+        DesignationMode::Add => ADD_PREVIEW_COLOR,
+        DesignationMode::Remove => REMOVE_PREVIEW_COLOR,
+    };
+    let transform = Transform::from_translation(center.extend(DESIGNATION_LAYER_Z));
+    if let Ok((_, mut preview_transform, mut sprite)) = preview_query.get_single_mut() {
+        *preview_transform = transform;
+        sprite.color = color;
+        sprite.custom_size = Some(size);
+    } else {
+        commands.spawn((
+            DesignationPreview,
+            Name::new("Designation preview"),
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform,
+                ..default()
+            },
+        ));
+    }
+}
+
 const HIGHLIGHT_COLOR: Color = Color::rgba(1., 1., 0.2, 0.2);
 
 fn highlight_designated_tile(
     mut commands: Commands,
-    mut tile_query: Query<&TilePos, Added<Designated>>,
-    mut tilemap_query: Query<(Entity, &mut TileStorage), With<HoverLayer>>,
+    mut tile_query: Query<(&TilePos, &TilemapId), Added<Designated>>,
+    mut tilemap_query: Query<(Entity, &Parent, &mut TileStorage), With<HoverLayer>>,
 ) {
-    for (tilemap_entity, mut tile_storage) in &mut tilemap_query {
-        for tile_pos in &mut tile_query {
+    for (tilemap_entity, chunk_entity, mut tile_storage) in &mut tilemap_query {
+        for (tile_pos, tile_tilemap_id) in &mut tile_query {
+            if tile_tilemap_id.0 != chunk_entity.get() {
+                continue;
+            }
             let tile_entity = commands
                 .spawn(TileBundle {
                     position: *tile_pos,
@@ -107,26 +259,32 @@ fn highlight_designated_tile(
 fn unhighlight_designated_tile(
     mut commands: Commands,
     mut designation_tiles_removed: RemovedComponents<Designated>,
-    tile_query: Query<&TilePos>,
-    mut tilemap_query: Query<&mut TileStorage, With<HoverLayer>>,
+    tile_query: Query<(&TilePos, &TilemapId)>,
+    mut tilemap_query: Query<(&Parent, &mut TileStorage), With<HoverLayer>>,
     mut destroyed_tiles: EventReader<TileDestroyedEvent>,
 ) {
-    let mut tile_storage = tilemap_query.single_mut();
-    for undesignation_tile_entity in designation_tiles_removed.iter() {
-        if let Ok(tile_pos) = tile_query.get(undesignation_tile_entity) {
+    let removed_tiles = designation_tiles_removed
+        .iter()
+        .filter_map(|undesignation_tile_entity| tile_query.get(undesignation_tile_entity).ok())
+        .map(|(tile_pos, tilemap_id)| (tilemap_id.0, *tile_pos))
+        .chain(
+            destroyed_tiles
+                .iter()
+                .map(|destroyed_tile| (destroyed_tile.chunk, destroyed_tile.tile_pos)),
+        )
+        .collect::<Vec<_>>();
+
+    for (chunk_entity, mut tile_storage) in &mut tilemap_query {
+        for (tile_chunk_entity, tile_pos) in &removed_tiles {
+            if *tile_chunk_entity != chunk_entity.get() {
+                continue;
+            }
             if let Some(hover_tile_entity) = tile_storage.get(tile_pos) {
                 commands.entity(hover_tile_entity).despawn_recursive();
                 tile_storage.remove(tile_pos);
             }
         }
     }
-
-    for destroyed_tile in destroyed_tiles.iter() {
-        if let Some(hover_tile_entity) = tile_storage.get(&destroyed_tile.tile_pos) {
-            commands.entity(hover_tile_entity).despawn_recursive();
-            tile_storage.remove(&destroyed_tile.tile_pos);
-        }
-    }
 }
 
 fn highlight_designated_mesh(
@@ -139,3 +297,16 @@ fn highlight_designated_mesh(
         }
     }
 }
+
+fn unhighlight_designated_mesh(
+    mut undesignated_entities: RemovedComponents<Designated>,
+    materials_query: Query<&Handle<ColorMaterial>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for entity in undesignated_entities.iter() {
+        let Ok(material_handle) = materials_query.get(entity) else { continue; };
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.color.set_a(1.0);
+        }
+    }
+}
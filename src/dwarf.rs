@@ -1,125 +1,243 @@
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::{math::Vec3Swizzles, prelude::*, sprite::MaterialMesh2dBundle};
 use bevy_rapier2d::{
     control::KinematicCharacterController,
-    prelude::{
-        CharacterAutostep, Collider, CollisionGroups, Group, QueryFilter, RapierContext, RigidBody,
-    },
+    prelude::{CharacterAutostep, Collider, CollisionGroups, Group, RigidBody},
 };
 use big_brain::{
     prelude::FirstToScore,
     thinker::{Thinker, ThinkerBuilder},
 };
-use rand::{seq::IteratorRandom, SeedableRng};
-use rand_xoshiro::Xoshiro256StarStar;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     actions::{
+        equip::{equip_tool, ToolWanted},
         meander::Meander,
-        work::{worker_scorer_builder, worker_thinker_builder},
+        tend_needs::{consume_item, go_to_sleep, ConsumableCarried, Consume, NeedUrgent},
+        work::{worker_scorer_builder, worker_thinker_builder, JobTypes},
     },
-    labor::job::Worker,
-    main_state::MainState,
+    equipment::Equipment,
+    health::{Health, HealthSet},
+    inventory::{drop_items, Inventory},
+    item::{spawn_item, ItemDefinitions},
+    labor::job::{AssignedJob, EnabledLabors, JobManagerParams, Worker},
     movement::{Climber, Jumper, Walker},
-    terrain::{TerrainSet, TERRAIN_COLLISION_GROUP},
-    terrain_settings::TerrainSettings,
+    needs::{NeedKind, Needs, URGENT_NEED},
+    skills::Skills,
+    terrain::{ChunkLoader, TERRAIN_COLLISION_GROUP},
 };
 
 pub struct DwarfPlugin;
 
 impl Plugin for DwarfPlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<DwarvesState>()
-            .add_systems(OnEnter(MainState::Game), spawn_dwarves.after(TerrainSet));
+        app.register_type::<Attributes>().add_systems(
+            Update,
+            dwarf_deaths
+                .after(HealthSet)
+                .run_if(resource_exists::<ItemDefinitions>()),
+        );
     }
 }
 
-#[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
-pub enum DwarvesState {
-    #[default]
-    Spawning,
-    Spawned,
-}
-
 #[derive(Component)]
 pub struct Dwarf;
 
 pub const DWARF_COLLISION_GROUP: Group = Group::GROUP_2;
+pub const DWARF_SIZE: Vec2 = Vec2::new(12., 12.);
+pub const DWARF_INVENTORY_SLOTS: usize = 4;
+pub const DWARF_MAX_CARRY_WEIGHT: u32 = 50;
+pub const DWARF_MAX_HEALTH: u32 = 100;
 
-fn spawn_dwarves(
-    mut commands: Commands,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    rapier_context: Res<RapierContext>,
-    terrain_settings: Res<TerrainSettings>,
-    mut dwarves_state: ResMut<NextState<DwarvesState>>,
-) {
-    let mut rng = Xoshiro256StarStar::seed_from_u64(terrain_settings.seed as u64);
-    let possible_x_pos: Vec<f32> = (-5..=5)
-        .map(|x| x as f32 * terrain_settings.cell_size)
-        .choose_multiple(&mut rng, 1);
-    for x in possible_x_pos {
-        // pick location within the 10 center cells of the map
-        // y location is always the top of the map
-        let y = terrain_settings.cell_size * terrain_settings.height as f32 / 2.0;
+pub const MIN_ATTRIBUTE: u32 = 1;
+pub const MAX_ATTRIBUTE: u32 = 10;
+pub const AVERAGE_ATTRIBUTE: u32 = 5;
 
-        let ray_dir = Vec2::new(0.0, -1.0);
-        let max_toi = terrain_settings.cell_size * terrain_settings.height as f32;
-        let filter = QueryFilter::default();
+/// Innate physical traits of a dwarf, from [`MIN_ATTRIBUTE`] to [`MAX_ATTRIBUTE`].
+#[derive(Component, Debug, Clone, Copy, Reflect, Serialize, Deserialize)]
+pub struct Attributes {
+    /// Raises how much the dwarf can carry.
+    pub strength: u32,
+    /// Raises the dwarf's health.
+    pub toughness: u32,
+    /// Slows down how fast the dwarf gets tired.
+    pub endurance: u32,
+}
 
-        if let Some((_entity, hit)) =
-            rapier_context.cast_ray(Vec2::new(x, y), ray_dir, max_toi, true, filter)
-        {
-            spawn_dwarf(&mut commands, x, y - hit, &mut materials, &mut meshes);
+impl Default for Attributes {
+    fn default() -> Self {
+        Self {
+            strength: AVERAGE_ATTRIBUTE,
+            toughness: AVERAGE_ATTRIBUTE,
+            endurance: AVERAGE_ATTRIBUTE,
         }
     }
-    dwarves_state.set(DwarvesState::Spawned);
 }
 
-fn spawn_dwarf(
+impl Attributes {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self {
+            strength: rng.gen_range(MIN_ATTRIBUTE..=MAX_ATTRIBUTE),
+            toughness: rng.gen_range(MIN_ATTRIBUTE..=MAX_ATTRIBUTE),
+            endurance: rng.gen_range(MIN_ATTRIBUTE..=MAX_ATTRIBUTE),
+        }
+    }
+
+    /// [`DWARF_MAX_CARRY_WEIGHT`] for average strength.
+    pub fn max_carry_weight(&self) -> u32 {
+        DWARF_MAX_CARRY_WEIGHT * (AVERAGE_ATTRIBUTE + self.strength) / (2 * AVERAGE_ATTRIBUTE)
+    }
+
+    /// [`DWARF_MAX_HEALTH`] for average toughness.
+    pub fn max_health(&self) -> u32 {
+        DWARF_MAX_HEALTH * (AVERAGE_ATTRIBUTE + self.toughness) / (2 * AVERAGE_ATTRIBUTE)
+    }
+
+    /// Multiplies how fast the dwarf gets tired, 1 for average endurance.
+    pub fn fatigue_multiplier(&self) -> f32 {
+        (3 * AVERAGE_ATTRIBUTE - self.endurance) as f32 / (2 * AVERAGE_ATTRIBUTE) as f32
+    }
+}
+
+pub fn spawn_dwarf(
     commands: &mut Commands,
+    name: &str,
     x: f32,
     y: f32,
+    attributes: Attributes,
+    job_types: &JobTypes,
     materials: &mut ResMut<Assets<ColorMaterial>>,
     meshes: &mut ResMut<Assets<Mesh>>,
-) {
-    let dwarf_size = Vec2::new(12., 12.);
-    commands.spawn((
-        Dwarf,
-        Name::new("Dwarf"),
-        MaterialMesh2dBundle {
-            transform: Transform::from_xyz(x, y + dwarf_size.y / 2., 3.),
-            material: materials.add(Color::WHITE.into()),
-            mesh: meshes.add(Mesh::from(shape::Quad::new(dwarf_size))).into(),
-            ..default()
-        },
-        RigidBody::KinematicPositionBased,
-        Collider::round_cuboid(dwarf_size.x * 0.416, dwarf_size.y * 0.416, 0.01),
-        CollisionGroups::new(DWARF_COLLISION_GROUP, TERRAIN_COLLISION_GROUP),
-        KinematicCharacterController {
-            filter_groups: Some(CollisionGroups::new(
-                DWARF_COLLISION_GROUP,
-                TERRAIN_COLLISION_GROUP,
-            )),
-            autostep: Some(CharacterAutostep {
-                max_height: bevy_rapier2d::prelude::CharacterLength::Absolute(16.),
-                min_width: bevy_rapier2d::prelude::CharacterLength::Absolute(16.),
-                include_dynamic_bodies: true,
-            }),
-            ..default()
-        },
-        Worker,
-        build_dwarf_thinker(),
-        Walker::default(),
-        Jumper::default(),
-        Climber,
-    ));
+) -> Entity {
+    let dwarf_size = DWARF_SIZE;
+    commands
+        .spawn((
+            Dwarf,
+            Name::new(name.to_string()),
+            MaterialMesh2dBundle {
+                transform: Transform::from_xyz(x, y + dwarf_size.y / 2., 3.),
+                material: materials.add(Color::WHITE.into()),
+                mesh: meshes.add(Mesh::from(shape::Quad::new(dwarf_size))).into(),
+                ..default()
+            },
+            RigidBody::KinematicPositionBased,
+            Collider::round_cuboid(dwarf_size.x * 0.416, dwarf_size.y * 0.416, 0.01),
+            CollisionGroups::new(DWARF_COLLISION_GROUP, TERRAIN_COLLISION_GROUP),
+            KinematicCharacterController {
+                filter_groups: Some(CollisionGroups::new(
+                    DWARF_COLLISION_GROUP,
+                    TERRAIN_COLLISION_GROUP,
+                )),
+                autostep: Some(CharacterAutostep {
+                    max_height: bevy_rapier2d::prelude::CharacterLength::Absolute(16.),
+                    min_width: bevy_rapier2d::prelude::CharacterLength::Absolute(16.),
+                    include_dynamic_bodies: true,
+                }),
+                ..default()
+            },
+            (
+                Worker,
+                EnabledLabors::default(),
+                Skills::default(),
+                Equipment::default(),
+            ),
+            (
+                attributes,
+                Health(attributes.max_health()),
+                Needs::default(),
+            ),
+            build_dwarf_thinker(job_types),
+            Walker::default(),
+            Jumper::default(),
+            Climber,
+            ChunkLoader,
+            Inventory::new(DWARF_INVENTORY_SLOTS, attributes.max_carry_weight()),
+        ))
+        .id()
 }
 
-fn build_dwarf_thinker() -> ThinkerBuilder {
+fn build_dwarf_thinker(job_types: &JobTypes) -> ThinkerBuilder {
     info!("Building Dwarf Thinker");
     Thinker::build()
         .label("Dwarf")
-        .picker(FirstToScore::new(0.8))
-        .when(worker_scorer_builder(), worker_thinker_builder())
+        .picker(FirstToScore::new(URGENT_NEED))
+        .when(
+            ConsumableCarried(NeedKind::Thirst),
+            Consume(NeedKind::Thirst),
+        )
+        .when(
+            ConsumableCarried(NeedKind::Hunger),
+            Consume(NeedKind::Hunger),
+        )
+        .when(NeedUrgent(NeedKind::Thirst), consume_item(NeedKind::Thirst))
+        .when(NeedUrgent(NeedKind::Hunger), consume_item(NeedKind::Hunger))
+        .when(NeedUrgent(NeedKind::Fatigue), go_to_sleep())
+        .when(ToolWanted, equip_tool())
+        .when(
+            worker_scorer_builder(job_types),
+            worker_thinker_builder(job_types),
+        )
         .otherwise(Meander)
 }
+
+/// Despawns dwarves that ran out of health, giving up their job and dropping what they carried and
+/// their tools.
+fn dwarf_deaths(
+    mut commands: Commands,
+    mut dwarf_query: Query<
+        (
+            Entity,
+            &Name,
+            &Health,
+            &GlobalTransform,
+            Option<&AssignedJob>,
+            Option<&mut Inventory>,
+            Option<&Equipment>,
+        ),
+        (With<Dwarf>, Changed<Health>),
+    >,
+    item_definitions: Res<ItemDefinitions>,
+    mut job_manager_params: JobManagerParams,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (dwarf_entity, name, health, transform, opt_assigned_job, opt_inventory, opt_equipment) in
+        &mut dwarf_query
+    {
+        if health.0 > 0 {
+            continue;
+        }
+        info!(dwarf = ?dwarf_entity, name = name.as_str(), "Dwarf died");
+        if let Some(assigned_job) = opt_assigned_job {
+            job_manager_params.cancel_job_assignment(assigned_job.0, dwarf_entity);
+        }
+        if let Some(mut inventory) = opt_inventory {
+            for stack in inventory.stacks().to_vec() {
+                drop_items(
+                    &mut commands,
+                    &mut inventory,
+                    &item_definitions,
+                    &stack,
+                    transform.translation().xy(),
+                    &mut materials,
+                    &mut meshes,
+                );
+            }
+        }
+        if let Some(equipment) = opt_equipment {
+            for tool in equipment.0.values() {
+                let Some(definition) = item_definitions.get(tool) else { continue; };
+                spawn_item(
+                    &mut commands,
+                    definition,
+                    1,
+                    transform.translation().xy(),
+                    &mut materials,
+                    &mut meshes,
+                );
+            }
+        }
+        commands.entity(dwarf_entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    dwarf::{Attributes, Dwarf},
+    equipment::{Equipment, ToolKind},
+    health::Health,
+    needs::{NeedKind, Needs},
+    skills::{experience_for_level, Skills, MAX_SKILL_LEVEL, SKILLED_LABORS},
+};
+
+pub struct DwarfPanelPlugin;
+
+impl Plugin for DwarfPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, dwarf_panel);
+    }
+}
+
+/// Health, attributes, needs, equipment and skills of every dwarf.
+fn dwarf_panel(
+    mut contexts: EguiContexts,
+    dwarf_query: Query<
+        (
+            Entity,
+            &Name,
+            Option<&Health>,
+            Option<&Attributes>,
+            Option<&Needs>,
+            Option<&Skills>,
+            Option<&Equipment>,
+        ),
+        With<Dwarf>,
+    >,
+) {
+    egui::Window::new("Dwarves").show(contexts.ctx_mut(), |ui| {
+        for (dwarf, name, opt_health, opt_attributes, opt_needs, opt_skills, opt_equipment) in
+            &dwarf_query
+        {
+            egui::CollapsingHeader::new(name.as_str())
+                .id_source(dwarf)
+                .show(ui, |ui| {
+                    if let (Some(health), Some(attributes)) = (opt_health, opt_attributes) {
+                        ui.label(format!("Health {}/{}", health.0, attributes.max_health()));
+                        ui.label(format!(
+                            "Strength {}, toughness {}, endurance {}",
+                            attributes.strength, attributes.toughness, attributes.endurance
+                        ));
+                    }
+
+                    if let Some(needs) = opt_needs {
+                        for kind in NeedKind::ALL {
+                            ui.horizontal(|ui| {
+                                ui.label(kind.name());
+                                ui.add(egui::ProgressBar::new(needs.get(kind)));
+                            });
+                        }
+                    }
+
+                    if let Some(equipment) = opt_equipment {
+                        for kind in ToolKind::ALL {
+                            ui.label(format!(
+                                "{}: {}",
+                                kind.name(),
+                                equipment.get(kind).unwrap_or("None")
+                            ));
+                        }
+                    }
+
+                    let Some(skills) = opt_skills else { return; };
+                    egui::Grid::new(("skills", dwarf)).show(ui, |ui| {
+                        for labor in SKILLED_LABORS {
+                            let level = skills.level(labor);
+                            ui.label(labor.name());
+                            ui.label(format!("Level {level}"));
+                            if level < MAX_SKILL_LEVEL {
+                                // Progress from the current level to the next one
+                                let level_experience = experience_for_level(level);
+                                let progress = (skills.experience(labor) - level_experience) as f32
+                                    / (experience_for_level(level + 1) - level_experience) as f32;
+                                ui.add(egui::ProgressBar::new(progress));
+                            } else {
+                                ui.label("Mastered");
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+        }
+    });
+}
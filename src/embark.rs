@@ -0,0 +1,260 @@
+use bevy::{
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::HashSet,
+};
+use bevy_common_assets::ron::RonAssetPlugin;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+
+use crate::{
+    actions::work::JobTypes,
+    dwarf::{spawn_dwarf, Attributes},
+    item::{spawn_item, ItemDefinitions},
+    main_state::MainState,
+    save::LoadedSave,
+    skills::{experience_for_level, Skills, SKILLED_LABORS},
+    terrain::{TerrainParam, TerrainSet, TerrainTiles},
+    terrain_settings::TerrainSettings,
+};
+
+pub struct EmbarkPlugin;
+
+impl Plugin for EmbarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<EmbarkSettings>::new(&["embark.ron"]))
+            .add_asset::<EmbarkSettings>()
+            .add_state::<EmbarkState>()
+            .add_systems(OnEnter(EmbarkState::Loading), load_embark_settings)
+            .add_systems(
+                Update,
+                setup_embark_settings.run_if(in_state(EmbarkState::Loading)),
+            )
+            .add_systems(
+                OnEnter(MainState::Game),
+                spawn_starting_party
+                    .after(TerrainSet)
+                    .run_if(not(resource_exists::<LoadedSave>())),
+            );
+    }
+}
+
+/// What a new colony starts out with.
+#[derive(serde::Deserialize, Resource, Clone, Debug, TypeUuid, TypePath)]
+#[uuid = "0f3c6f43-5a9e-4c1b-9d0e-8a7b2f64c1d5"]
+pub struct EmbarkSettings {
+    pub dwarves: usize,
+    /// Columns of tiles, from the first to the last inclusive, the party is spawned on.
+    pub spawn_columns: (i32, i32),
+    /// Names and counts of the items brought along.
+    pub starting_items: Vec<(String, u32)>,
+}
+
+#[derive(Resource)]
+struct EmbarkSettingsHandle(Handle<EmbarkSettings>);
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EmbarkState {
+    #[default]
+    Loading,
+    Loaded,
+}
+
+fn load_embark_settings(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let embark_settings = asset_server.load("base.embark.ron");
+    commands.insert_resource(EmbarkSettingsHandle(embark_settings));
+}
+
+fn setup_embark_settings(
+    mut commands: Commands,
+    embark_settings: Res<EmbarkSettingsHandle>,
+    embark_settings_assets: Res<Assets<EmbarkSettings>>,
+    mut state: ResMut<NextState<EmbarkState>>,
+) {
+    if let Some(embark_settings) = embark_settings_assets.get(&embark_settings.0) {
+        commands.insert_resource(embark_settings.clone());
+        info!("Embark settings loaded");
+        state.set(EmbarkState::Loaded);
+    }
+}
+
+const FIRST_NAME_STARTS: [&str; 12] = [
+    "Ur", "Kad", "Bom", "Ast", "Dor", "Mel", "Zon", "Tob", "Ing", "Ed", "Lok", "Vuc",
+];
+const FIRST_NAME_ENDS: [&str; 10] = [
+    "ist", "ol", "rek", "ath", "ul", "is", "an", "ush", "im", "or",
+];
+const SURNAME_STARTS: [&str; 10] = [
+    "Copper", "Iron", "Stone", "Coal", "Granite", "Ale", "Deep", "Anvil", "Oak", "Silver",
+];
+const SURNAME_ENDS: [&str; 10] = [
+    "beard", "fist", "helm", "delver", "hammer", "brow", "shield", "mantle", "digger", "axe",
+];
+
+/// Skill level every dwarf of the starting party has in one random labor.
+pub const STARTING_SKILL_LEVEL: u32 = 2;
+
+/// Random names tried before falling back to numbering them.
+const NAME_ATTEMPTS: usize = 100;
+
+/// Generates a name none of the `taken` names has.
+///
+/// Once random names keep colliding, e.g. because most names are taken, the last one tried gets
+/// the lowest number that makes it unique.
+pub fn generate_dwarf_name(rng: &mut impl Rng, taken: &HashSet<String>) -> String {
+    let mut name = String::new();
+    for _ in 0..NAME_ATTEMPTS {
+        name = format!(
+            "{}{} {}{}",
+            FIRST_NAME_STARTS.choose(rng).unwrap(),
+            FIRST_NAME_ENDS.choose(rng).unwrap(),
+            SURNAME_STARTS.choose(rng).unwrap(),
+            SURNAME_ENDS.choose(rng).unwrap(),
+        );
+        if !taken.contains(&name) {
+            return name;
+        }
+    }
+    (2..)
+        .map(|number| format!("{name} {number}"))
+        .find(|numbered_name| !taken.contains(numbered_name))
+        .unwrap()
+}
+
+/// World tile position of the open tile right above the topmost solid tile of column `x`.
+///
+/// Columns that are solid all the way up have no room to stand in and return `None`.
+fn column_surface(
+    terrain: &TerrainParam,
+    terrain_settings: &TerrainSettings,
+    x: i32,
+) -> Option<IVec2> {
+    let top = terrain_settings
+        .world_to_tile(Vec2::new(
+            0.,
+            terrain_settings.cell_size * terrain_settings.height as f32 / 2.,
+        ))
+        .y;
+    let bottom = top - terrain_settings.height as i32;
+    if terrain.get_tile(IVec2::new(x, top)) != Some(0) {
+        return None;
+    }
+    (bottom..top)
+        .rev()
+        .map(|y| IVec2::new(x, y))
+        .find(|tile_pos| terrain.get_tile(*tile_pos).map_or(false, |tile| tile != 0))
+        .map(|solid_tile| solid_tile + IVec2::Y)
+}
+
+/// Spawns the dwarves and starting items of [`EmbarkSettings`] on the surface of the spawn
+/// columns.
+fn spawn_starting_party(
+    mut commands: Commands,
+    embark_settings: Res<EmbarkSettings>,
+    terrain: TerrainParam,
+    terrain_settings: Res<TerrainSettings>,
+    item_definitions: Res<ItemDefinitions>,
+    job_types: Res<JobTypes>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let mut rng = Xoshiro256StarStar::seed_from_u64(terrain_settings.seed as u64);
+    let (first_column, last_column) = embark_settings.spawn_columns;
+    let mut surfaces: Vec<IVec2> = (first_column..=last_column)
+        .filter_map(|x| column_surface(&terrain, &terrain_settings, x))
+        .collect();
+    if surfaces.is_empty() {
+        error!(
+            first_column,
+            last_column, "No room to spawn the starting party in"
+        );
+        return;
+    }
+    surfaces.shuffle(&mut rng);
+    // Bottom center of the tile the party stands on
+    let mut spawn_points = surfaces.iter().cycle().map(|surface| {
+        terrain_settings.tile_to_world(*surface) - Vec2::new(0., terrain_settings.cell_size / 2.)
+    });
+
+    let mut names = HashSet::new();
+    for _ in 0..embark_settings.dwarves {
+        let name = generate_dwarf_name(&mut rng, &names);
+        let attributes = Attributes::random(&mut rng);
+        let specialty = *SKILLED_LABORS.choose(&mut rng).unwrap();
+        let mut skills = Skills::default();
+        skills.add_experience(specialty, experience_for_level(STARTING_SKILL_LEVEL));
+        let position = spawn_points.next().unwrap();
+        let dwarf_entity = spawn_dwarf(
+            &mut commands,
+            &name,
+            position.x,
+            position.y,
+            attributes,
+            &job_types,
+            &mut materials,
+            &mut meshes,
+        );
+        commands.entity(dwarf_entity).insert(skills);
+        info!(
+            dwarf = ?dwarf_entity,
+            name = name.as_str(),
+            ?attributes,
+            specialty = specialty.name(),
+            "Dwarf arrived"
+        );
+        names.insert(name);
+    }
+
+    for (item, count) in &embark_settings.starting_items {
+        let Some(definition) = item_definitions.get(item) else {
+            error!("Item {} is not defined", item);
+            continue;
+        };
+        let mut left = *count;
+        while left > 0 {
+            let stack = left.min(definition.max_stack.max(1));
+            let position = spawn_points.next().unwrap();
+            spawn_item(
+                &mut commands,
+                definition,
+                stack,
+                position + Vec2::new(0., definition.size.y / 2.),
+                &mut materials,
+                &mut meshes,
+            );
+            left -= stack;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{
+        generate_dwarf_name, FIRST_NAME_ENDS, FIRST_NAME_STARTS, SURNAME_ENDS, SURNAME_STARTS,
+    };
+
+    #[test]
+    fn numbers_names_once_all_are_taken() {
+        let mut taken = HashSet::new();
+        for first_start in FIRST_NAME_STARTS {
+            for first_end in FIRST_NAME_ENDS {
+                for surname_start in SURNAME_STARTS {
+                    for surname_end in SURNAME_ENDS {
+                        taken.insert(format!(
+                            "{first_start}{first_end} {surname_start}{surname_end}"
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(0);
+        let name = generate_dwarf_name(&mut rng, &taken);
+        assert!(!taken.contains(&name));
+        assert!(name.ends_with(" 2"));
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{item::ItemDefinitions, labor::job::Labor};
+
+pub struct EquipmentPlugin;
+
+impl Plugin for EquipmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ToolKind>().register_type::<Equipment>();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+}
+
+impl ToolKind {
+    pub const ALL: [ToolKind; 2] = [ToolKind::Pickaxe, ToolKind::Axe];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolKind::Pickaxe => "Pickaxe",
+            ToolKind::Axe => "Axe",
+        }
+    }
+
+    /// Labor that can't be done without a tool of this kind.
+    pub fn labor(&self) -> Labor {
+        match self {
+            ToolKind::Pickaxe => Labor::Mining,
+            ToolKind::Axe => Labor::Woodcutting,
+        }
+    }
+
+    /// Kind of tool needed to do the work of `labor`, if any.
+    pub fn required_for(labor: Labor) -> Option<ToolKind> {
+        ToolKind::ALL.into_iter().find(|kind| kind.labor() == labor)
+    }
+}
+
+/// Makes an item a tool.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct ToolDefinition {
+    pub kind: ToolKind,
+    /// Multiplies how fast the work of the tool's labor is done, depends on the tool's material
+    /// and make.
+    pub speed: f32,
+}
+
+/// Tools a worker has equipped, by item name, with a slot for every kind of tool.
+#[derive(Component, Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct Equipment(pub HashMap<ToolKind, String>);
+
+impl Equipment {
+    pub fn get(&self, kind: ToolKind) -> Option<&str> {
+        self.0.get(&kind).map(String::as_str)
+    }
+
+    /// Puts `item` into the slot of `kind`, returning the tool that was in it before.
+    pub fn equip(&mut self, kind: ToolKind, item: String) -> Option<String> {
+        self.0.insert(kind, item)
+    }
+
+    /// Speed of the equipped tool of `kind`, if one is equipped.
+    pub fn tool_speed(&self, kind: ToolKind, item_definitions: &ItemDefinitions) -> Option<f32> {
+        self.get(kind)
+            .and_then(|item| item_definitions.get(item))
+            .and_then(|definition| definition.tool)
+            .map(|tool| tool.speed)
+    }
+}
+
+/// Whether a worker with `opt_equipment` has the tool the work of `labor` needs, if it needs one.
+pub fn has_tool_for(opt_equipment: Option<&Equipment>, labor: Labor) -> bool {
+    ToolKind::required_for(labor).map_or(true, |kind| {
+        opt_equipment.is_some_and(|equipment| equipment.0.contains_key(&kind))
+    })
+}
+
+/// Speed multiplier the equipped tool of `kind` gives `worker`, 1 without one.
+pub fn worker_tool_speed(
+    equipment_query: &Query<&Equipment>,
+    item_definitions: Option<&ItemDefinitions>,
+    worker: Entity,
+    kind: ToolKind,
+) -> f32 {
+    equipment_query
+        .get(worker)
+        .ok()
+        .zip(item_definitions)
+        .and_then(|(equipment, item_definitions)| equipment.tool_speed(kind, item_definitions))
+        .unwrap_or(1.)
+}
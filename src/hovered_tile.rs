@@ -8,7 +8,7 @@ use bevy_ecs_tilemap::{
 use crate::{
     cursor_position::LastCursorPosition,
     main_state::MainState,
-    terrain::{TerrainParam, TerrainSet, TileDestroyedEvent},
+    terrain::{Terrain, TerrainParam, TerrainSet, TileDestroyedEvent},
     terrain_settings::TerrainSettings,
 };
 
@@ -16,20 +16,20 @@ pub struct HoveredTilePlugin;
 
 impl Plugin for HoveredTilePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(MainState::Game), setup_hovered_layer)
-            .add_systems(
-                Update,
-                (
-                    hovered_tile,
-                    apply_deferred,
-                    highlight_hovered_tile,
-                    unhighlight_hovered_tile,
-                )
-                    .chain()
-                    .run_if(in_state(MainState::Game))
-                    .in_set(HoveredTileSet)
-                    .after(TerrainSet),
-            );
+        app.add_systems(
+            Update,
+            (
+                setup_hovered_layer,
+                hovered_tile,
+                apply_deferred,
+                highlight_hovered_tile,
+                unhighlight_hovered_tile,
+            )
+                .chain()
+                .run_if(in_state(MainState::Game))
+                .in_set(HoveredTileSet)
+                .after(TerrainSet),
+        );
     }
 }
 
@@ -39,45 +39,45 @@ pub struct HoveredTileSet;
 #[derive(Component)]
 struct HoverLayer;
 
+/// Spawns a hover layer as a child of every new terrain chunk.
 fn setup_hovered_layer(
     mut commands: Commands,
     config: Res<TerrainSettings>,
-
+    new_chunk_query: Query<Entity, (With<Terrain>, Added<TileStorage>)>,
     asset_server: Res<AssetServer>,
 ) {
-    let texture_handle = asset_server.load("textures/terrain.png");
-    let tile_size = TilemapTileSize {
-        x: config.cell_size,
-        y: config.cell_size,
-    };
-    let grid_size = tile_size.into();
-
-    let tilemap_size = TilemapSize {
-        x: config.width,
-        y: config.height,
-    };
-
-    let storage = TileStorage::empty(tilemap_size);
-
-    let map_transform = Transform::from_translation(Vec3::new(
-        -(config.width as f32 * config.cell_size / 2.) + 0.5 * config.cell_size,
-        -(config.height as f32 * config.cell_size / 2.) + 0.5 * config.cell_size,
-        1.0,
-    ));
-    commands.spawn((
-        HoverLayer,
-        Name::new("Hovered Tile layer"),
-        TilemapBundle {
-            grid_size,
-            map_type: TilemapType::Square,
-            size: tilemap_size,
-            storage,
-            texture: TilemapTexture::Single(texture_handle),
-            tile_size,
-            transform: map_transform,
-            ..default()
-        },
-    ));
+    for chunk_entity in &new_chunk_query {
+        let texture_handle = asset_server.load("textures/terrain.png");
+        let tile_size = TilemapTileSize {
+            x: config.cell_size,
+            y: config.cell_size,
+        };
+        let grid_size = tile_size.into();
+
+        let tilemap_size = TilemapSize {
+            x: config.width,
+            y: config.height,
+        };
+
+        let storage = TileStorage::empty(tilemap_size);
+
+        commands.entity(chunk_entity).with_children(|parent| {
+            parent.spawn((
+                HoverLayer,
+                Name::new("Hovered Tile layer"),
+                TilemapBundle {
+                    grid_size,
+                    map_type: TilemapType::Square,
+                    size: tilemap_size,
+                    storage,
+                    texture: TilemapTexture::Single(texture_handle),
+                    tile_size,
+                    transform: Transform::from_xyz(0., 0., 1.0),
+                    ..default()
+                },
+            ));
+        });
+    }
 }
 
 #[derive(Component)]
@@ -111,11 +111,14 @@ const HIGHLIGHT_COLOR: Color = Color::rgba(1., 1., 0.2, 0.2);
 
 fn highlight_hovered_tile(
     mut commands: Commands,
-    mut tile_query: Query<&TilePos, Added<HoveredTile>>,
-    mut tilemap_query: Query<(Entity, &mut TileStorage), With<HoverLayer>>,
+    mut tile_query: Query<(&TilePos, &TilemapId), Added<HoveredTile>>,
+    mut tilemap_query: Query<(Entity, &Parent, &mut TileStorage), With<HoverLayer>>,
 ) {
-    for (tilemap_entity, mut tile_storage) in &mut tilemap_query {
-        for tile_pos in &mut tile_query {
+    for (tilemap_entity, chunk_entity, mut tile_storage) in &mut tilemap_query {
+        for (tile_pos, tile_tilemap_id) in &mut tile_query {
+            if tile_tilemap_id.0 != chunk_entity.get() {
+                continue;
+            }
             let tile_entity = commands
                 .spawn((
                     Name::new("Hovered tile"),
@@ -136,24 +139,31 @@ fn highlight_hovered_tile(
 fn unhighlight_hovered_tile(
     mut commands: Commands,
     mut hovered_tiles_removed: RemovedComponents<HoveredTile>,
-    tile_query: Query<&TilePos>,
-    mut hoverlayer_tilemap_query: Query<&mut TileStorage, With<HoverLayer>>,
+    tile_query: Query<(&TilePos, &TilemapId)>,
+    mut hoverlayer_tilemap_query: Query<(&Parent, &mut TileStorage), With<HoverLayer>>,
     mut destroyed_tiles: EventReader<TileDestroyedEvent>,
 ) {
-    let mut hovertile_storage = hoverlayer_tilemap_query.single_mut();
-    for unhovered_tile_entity in hovered_tiles_removed.iter() {
-        if let Ok(tile_pos) = tile_query.get(unhovered_tile_entity) {
+    let removed_tiles = hovered_tiles_removed
+        .iter()
+        // Tiles that were destroyed can no longer be queried, they are handled below
+        .filter_map(|unhovered_tile_entity| tile_query.get(unhovered_tile_entity).ok())
+        .map(|(tile_pos, tilemap_id)| (tilemap_id.0, *tile_pos))
+        .chain(
+            destroyed_tiles
+                .iter()
+                .map(|destroyed_tile| (destroyed_tile.chunk, destroyed_tile.tile_pos)),
+        )
+        .collect::<Vec<_>>();
+
+    for (chunk_entity, mut hovertile_storage) in &mut hoverlayer_tilemap_query {
+        for (tile_chunk_entity, tile_pos) in &removed_tiles {
+            if *tile_chunk_entity != chunk_entity.get() {
+                continue;
+            }
             if let Some(hover_tile_entity) = hovertile_storage.get(tile_pos) {
                 commands.entity(hover_tile_entity).despawn_recursive();
                 hovertile_storage.remove(tile_pos);
             }
-        } // else: tile was destroyed and tile_pos can no longer be queried
-    }
-    // remove destroyed tiles from hoverlayer
-    for destroyed_tile in destroyed_tiles.iter() {
-        if let Some(hovertile_entity) = hovertile_storage.get(&destroyed_tile.tile_pos) {
-            commands.entity(hovertile_entity).despawn_recursive();
-            hovertile_storage.remove(&destroyed_tile.tile_pos);
         }
     }
 }
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+
+use crate::item::{spawn_item, Item, ItemDefinitions};
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ItemStack>()
+            .register_type::<Inventory>();
+    }
+}
+
+/// A number of items of the same type.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect)]
+pub struct ItemStack {
+    pub item: Name,
+    pub count: u32,
+}
+
+/// Items carried or stored by an entity.
+///
+/// Every stack takes up a slot, a slot holds at most the item's `max_stack`. The total weight of
+/// all items can't exceed `max_weight`. Items without an
+/// [`ItemDefinition`](crate::item::ItemDefinition) can't be stored.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct Inventory {
+    stacks: Vec<ItemStack>,
+    pub slots: usize,
+    pub max_weight: u32,
+}
+
+impl Inventory {
+    pub fn new(slots: usize, max_weight: u32) -> Self {
+        Self {
+            stacks: vec![],
+            slots,
+            max_weight,
+        }
+    }
+
+    pub fn stacks(&self) -> &[ItemStack] {
+        &self.stacks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    /// Number of `item` in the inventory, across all stacks.
+    pub fn count(&self, item: &Name) -> u32 {
+        self.stacks
+            .iter()
+            .filter(|stack| stack.item == *item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    pub fn weight(&self, item_definitions: &ItemDefinitions) -> u32 {
+        self.stacks
+            .iter()
+            .map(|stack| {
+                item_definitions
+                    .get(stack.item.as_str())
+                    .map_or(0, |definition| definition.weight * stack.count)
+            })
+            .sum()
+    }
+
+    /// Number of `item` that can still be added.
+    pub fn capacity_for(&self, item_definitions: &ItemDefinitions, item: &Name) -> u32 {
+        let Some(definition) = item_definitions.get(item.as_str()) else {
+            return 0;
+        };
+
+        let free_slots = self.slots.saturating_sub(self.stacks.len()) as u32;
+        let room_in_stacks: u32 = self
+            .stacks
+            .iter()
+            .filter(|stack| stack.item == *item)
+            .map(|stack| definition.max_stack.saturating_sub(stack.count))
+            .sum();
+        let slot_capacity = room_in_stacks + free_slots * definition.max_stack;
+
+        if definition.weight == 0 {
+            slot_capacity
+        } else {
+            let weight_left = self
+                .max_weight
+                .saturating_sub(self.weight(item_definitions));
+            slot_capacity.min(weight_left / definition.weight)
+        }
+    }
+
+    /// Adds up to `count` of `item`, filling existing stacks first. Returns the number added.
+    pub fn add(&mut self, item_definitions: &ItemDefinitions, item: &Name, count: u32) -> u32 {
+        let added = count.min(self.capacity_for(item_definitions, item));
+        let Some(definition) = item_definitions.get(item.as_str()) else {
+            return 0;
+        };
+
+        let mut remaining = added;
+        for stack in self.stacks.iter_mut().filter(|stack| stack.item == *item) {
+            let amount = remaining.min(definition.max_stack.saturating_sub(stack.count));
+            stack.count += amount;
+            remaining -= amount;
+        }
+        while remaining > 0 {
+            let amount = remaining.min(definition.max_stack);
+            self.stacks.push(ItemStack {
+                item: item.clone(),
+                count: amount,
+            });
+            remaining -= amount;
+        }
+        added
+    }
+
+    /// Removes up to `count` of `item`, emptying the smallest stacks first. Returns the number
+    /// removed.
+    pub fn remove(&mut self, item: &Name, count: u32) -> u32 {
+        self.stacks.sort_by_key(|stack| stack.count);
+        let mut remaining = count;
+        for stack in self.stacks.iter_mut().filter(|stack| stack.item == *item) {
+            let amount = remaining.min(stack.count);
+            stack.count -= amount;
+            remaining -= amount;
+        }
+        self.stacks.retain(|stack| stack.count > 0);
+        count - remaining
+    }
+}
+
+/// Moves up to `count` of `item` from one inventory to another. Returns the number moved.
+pub fn transfer(
+    from: &mut Inventory,
+    to: &mut Inventory,
+    item_definitions: &ItemDefinitions,
+    item: &Name,
+    count: u32,
+) -> u32 {
+    let amount = count
+        .min(from.count(item))
+        .min(to.capacity_for(item_definitions, item));
+    from.remove(item, amount);
+    to.add(item_definitions, item, amount)
+}
+
+/// Picks up as many of up to `count` items of the stack `item_entity` lying in the world as fit
+/// in `inventory`, despawning the stack once it's empty. Returns the number picked up.
+pub fn pick_up_item(
+    commands: &mut Commands,
+    inventory: &mut Inventory,
+    item_definitions: &ItemDefinitions,
+    item_entity: Entity,
+    name: &Name,
+    item: &mut Item,
+    count: u32,
+) -> u32 {
+    let picked_up = inventory.add(item_definitions, name, item.count.min(count));
+    item.count -= picked_up;
+    if item.count == 0 {
+        commands.entity(item_entity).despawn_recursive();
+    }
+    picked_up
+}
+
+/// Removes up to `stack.count` of `stack.item` from `inventory` and drops them at `position`.
+pub fn drop_items(
+    commands: &mut Commands,
+    inventory: &mut Inventory,
+    item_definitions: &ItemDefinitions,
+    stack: &ItemStack,
+    position: Vec2,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> Option<Entity> {
+    let definition = item_definitions.get(stack.item.as_str())?;
+    let dropped = inventory.remove(&stack.item, stack.count);
+    if dropped == 0 {
+        return None;
+    }
+    Some(spawn_item(
+        commands, definition, dropped, position, materials, meshes,
+    ))
+}
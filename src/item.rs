@@ -0,0 +1,131 @@
+use bevy::{
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    sprite::MaterialMesh2dBundle,
+};
+use bevy_common_assets::ron::RonAssetPlugin;
+use bevy_rapier2d::prelude::{Collider, CollisionGroups, RigidBody};
+
+use crate::{
+    building_material::BuildingMaterial, equipment::ToolDefinition, labor::craft::Recipe,
+    terrain::TERRAIN_COLLISION_GROUP, tree::OBJECT_COLLISION_GROUP,
+};
+
+pub struct ItemPlugin;
+
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<Items>::new(&["items.ron"]))
+            .add_asset::<Items>()
+            .add_state::<ItemsState>()
+            .register_type::<Item>()
+            .add_systems(OnEnter(ItemsState::Loading), load_items)
+            .add_systems(Update, setup_items.run_if(in_state(ItemsState::Loading)));
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub color: Color,
+    /// Size of a stack of the item lying in the world.
+    pub size: Vec2,
+    /// Weight of a single item, counted against the weight limit of inventories.
+    pub weight: u32,
+    /// Number of items that fit in a single inventory slot.
+    pub max_stack: u32,
+    /// How much eating a single item satisfies hunger.
+    #[serde(default)]
+    pub nutrition: f32,
+    /// How much drinking a single item satisfies thirst.
+    #[serde(default)]
+    pub hydration: f32,
+    /// Whether the item is a tool workers can equip.
+    #[serde(default)]
+    pub tool: Option<ToolDefinition>,
+    /// How the item is crafted at a workshop, if it can be.
+    #[serde(default)]
+    pub recipe: Option<Recipe>,
+    /// Whether constructions and craft orders can use the item as material.
+    #[serde(default)]
+    pub building_material: bool,
+}
+
+#[derive(serde::Deserialize, TypeUuid, TypePath)]
+#[uuid = "5adc4836-1036-425a-bcec-19c7de8174b0"]
+struct Items(Vec<ItemDefinition>);
+
+#[derive(Resource)]
+struct ItemsHandle(Handle<Items>);
+
+fn load_items(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let items = asset_server.load("base.items.ron");
+    commands.insert_resource(ItemsHandle(items));
+}
+
+#[derive(Resource)]
+pub struct ItemDefinitions(pub Vec<ItemDefinition>);
+
+impl ItemDefinitions {
+    pub fn get(&self, name: &str) -> Option<&ItemDefinition> {
+        self.0.iter().find(|definition| definition.name == name)
+    }
+}
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ItemsState {
+    #[default]
+    Loading,
+    Loaded,
+}
+
+fn setup_items(
+    mut commands: Commands,
+    items: Res<ItemsHandle>,
+    items_assets: Res<Assets<Items>>,
+    mut state: ResMut<NextState<ItemsState>>,
+) {
+    if let Some(items) = items_assets.get(&items.0) {
+        commands.insert_resource(ItemDefinitions(items.0.clone()));
+        info!("Items loaded");
+        state.set(ItemsState::Loaded);
+    }
+}
+
+/// A stack of items lying in the world. The item type is the entity's [`Name`].
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct Item {
+    pub count: u32,
+}
+
+pub const ITEM_LAYER_Z: f32 = 2.;
+
+/// Spawns a stack of `count` items at `position`.
+pub fn spawn_item(
+    commands: &mut Commands,
+    definition: &ItemDefinition,
+    count: u32,
+    position: Vec2,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> Entity {
+    let mut item_commands = commands.spawn((
+        Item { count },
+        Name::new(definition.name.clone()),
+        MaterialMesh2dBundle {
+            transform: Transform::from_translation(position.extend(ITEM_LAYER_Z)),
+            material: materials.add(definition.color.into()),
+            mesh: meshes
+                .add(Mesh::from(shape::Quad::new(definition.size)))
+                .into(),
+            ..default()
+        },
+        RigidBody::Fixed,
+        Collider::cuboid(definition.size.x / 2., definition.size.y / 2.),
+        CollisionGroups::new(OBJECT_COLLISION_GROUP, TERRAIN_COLLISION_GROUP),
+    ));
+    if definition.building_material {
+        item_commands.insert(BuildingMaterial);
+    }
+    item_commands.id()
+}
@@ -1,20 +1,32 @@
 use std::vec;
 
-use bevy::prelude::*;
-use bevy_ecs_tilemap::prelude::TilemapGridSize;
+use bevy::{ecs::system::SystemParamItem, prelude::*, utils::HashSet};
 use bevy_rapier2d::prelude::Group;
 
 use crate::{
-    actions::action_area::ActionArea,
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        build::{BuildActionSystemParam, BuildTarget},
+    },
     building_material::{BuildingMaterial, BuildingMaterialLocator, Reserved},
     cursor_position::LastCursorPosition,
-    hovered_tile::{HoveredTile, HoveredTileSet},
-    labor::job::{all_workers_eligible, AssignedJob, Job, Worker},
-    ladder::spawn_ladder,
-    terrain::Terrain,
+    labor::{
+        dig_tile::{spawn_dig_job, DigJob},
+        job::{
+            DesignationPriority, Job, JobAssignmentSet, JobPriority, Labor, PrerequisiteCanceled,
+            Prerequisites,
+        },
+    },
+    material::MaterialProperties,
+    structure::{
+        footprint_tiles, spawn_structure, PlacedStructure, StructureDefinition,
+        StructureDefinitions,
+    },
+    terrain::{TerrainParam, TerrainSet},
+    terrain_settings::TerrainSettings,
 };
 
-use super::{haul::HaulRequest, job::CompletedJob, job::JobCompletedEvent};
+use super::{haul::HaulRequest, job::CompletedJob};
 
 pub struct BuildStructurePlugin;
 
@@ -23,22 +35,28 @@ impl Plugin for BuildStructurePlugin {
         app.add_state::<BuildToolState>()
             .add_event::<ConstructionCompletedEvent>()
             .register_type::<ConstructionJob>()
+            .init_resource::<SelectedStructure>()
+            .init_resource::<ConstructionDesignation>()
+            .add_systems(OnExit(BuildToolState::Placing), clear_ghosts)
             .add_systems(
                 Update,
                 designate_construction
                     .run_if(state_exists_and_equals(BuildToolState::Placing))
-                    .before(HoveredTileSet),
+                    .run_if(resource_exists::<StructureDefinitions>())
+                    .run_if(resource_exists::<MaterialProperties>())
+                    .after(TerrainSet),
             )
             .add_systems(
                 Update,
                 (
                     designate_building_materials,
                     materials_delivered,
-                    all_workers_eligible::<ConstructionJob>,
-                    start_building,
-                    build_timer,
+                    request_construction_jobs.run_if(resource_exists::<TerrainSettings>()),
+                    despawn_completed_construction_jobs,
+                    cancel_undiggable_constructions,
                     finish_building,
-                ),
+                )
+                    .before(JobAssignmentSet),
             );
     }
 }
@@ -50,12 +68,20 @@ pub enum BuildToolState {
     Placing,
 }
 
+/// Name of the structure definition the build tool places.
+#[derive(Resource, Default)]
+pub struct SelectedStructure(pub Option<String>);
+
 #[derive(Component)]
 pub struct Structure;
 
 #[derive(Component)]
 pub struct Ghost;
 
+/// Tile a line of structures being dragged out started at.
+#[derive(Resource, Default)]
+struct ConstructionDesignation(Option<IVec2>);
+
 pub const CONSTRUCTION_COLLISION_GROUP: Group = Group::GROUP_7;
 
 #[derive(Component, Default)]
@@ -92,6 +118,10 @@ impl BuildingMaterialsNeeded {
         &self.0
     }
 
+    pub fn all_delivered(&self) -> bool {
+        self.0.iter().all(|(_, amount)| *amount == 0)
+    }
+
     pub fn deliver_resource(&mut self, resource: &Name, amount: u32) {
         for (name, count) in &mut self.0 {
             if name == resource {
@@ -103,62 +133,184 @@ impl BuildingMaterialsNeeded {
 
 pub const BUILDING_LAYER_Z: f32 = 2.0;
 
+const VALID_GHOST_COLOR: Color = Color::rgba(0.4, 1.0, 0.4, 0.5);
+const INVALID_GHOST_COLOR: Color = Color::rgba(1.0, 0.3, 0.3, 0.5);
+
+/// Removes the ghosts and any unfinished drag when switching to another tool.
+fn clear_ghosts(
+    mut commands: Commands,
+    mut designation: ResMut<ConstructionDesignation>,
+    ghost_query: Query<Entity, With<Ghost>>,
+) {
+    designation.0 = None;
+    for ghost_entity in &ghost_query {
+        commands.entity(ghost_entity).despawn_recursive();
+    }
+}
+
+/// Shows ghosts of the selected structure in a line from where the drag started to the cursor,
+/// tinted by whether they can be placed, and places the valid ones when the drag ends.
+///
+/// Structures can be placed over diggable tiles, they wait for the tiles to be dug out first.
 fn designate_construction(
     mut commands: Commands,
     mouse_button_input: Res<Input<MouseButton>>,
     cursor_position: Res<LastCursorPosition>,
-    hovered_tile_query: Query<&HoveredTile>,
-    terrain_query: Query<&TilemapGridSize, With<Terrain>>,
+    mut designation: ResMut<ConstructionDesignation>,
     ghost_query: Query<Entity, With<Ghost>>,
-    _materials: ResMut<Assets<ColorMaterial>>,
-    _meshes: ResMut<Assets<Mesh>>,
+    structure_query: Query<&PlacedStructure, Without<Ghost>>,
     asset_server: Res<AssetServer>,
+    terrain: TerrainParam,
+    terrain_settings: Res<TerrainSettings>,
+    selected_structure: Res<SelectedStructure>,
+    structure_definitions: Res<StructureDefinitions>,
+    designation_priority: Res<DesignationPriority>,
+    material_properties: Res<MaterialProperties>,
+    dig_job_query: Query<(Entity, &DigJob), With<Job>>,
 ) {
-    let tilemap_grid_size = terrain_query.single();
-    // round the cursor_position to the nearest tile
-    let rounded_cursor_position = Vec2::new(
-        (cursor_position.0.x / tilemap_grid_size.x).round() * tilemap_grid_size.x,
-        (cursor_position.0.y / tilemap_grid_size.y).round() * tilemap_grid_size.y,
-    );
-
     // Delete all ghosts
     for ghost_entity in &ghost_query {
         commands.entity(ghost_entity).despawn_recursive();
     }
 
-    let ladder = spawn_ladder(
-        &mut commands,
-        &asset_server,
-        rounded_cursor_position.extend(BUILDING_LAYER_Z),
-    );
-    if mouse_button_input.just_pressed(MouseButton::Left) && hovered_tile_query.is_empty() {
-        commands.entity(ladder).insert((
-            UnderConstruction::default(),
-            BuildingMaterialsNeeded::new(vec![(Name::new("Log"), 1)]),
-        ));
-    } else {
-        commands.entity(ladder).insert(Ghost);
+    let Some(definition) = selected_structure
+        .0
+        .as_deref()
+        .and_then(|name| structure_definitions.get(name))
+    else {
+        return;
+    };
+    let tile_pos = terrain_settings.world_to_tile(cursor_position.0);
+
+    if mouse_button_input.just_pressed(MouseButton::Left) {
+        designation.0 = Some(tile_pos);
+    }
+    let start_tile_pos = designation.0.unwrap_or(tile_pos);
+    let place =
+        mouse_button_input.just_released(MouseButton::Left) && designation.0.take().is_some();
+
+    let mut occupied_tiles: HashSet<IVec2> = structure_query
+        .iter()
+        .flat_map(|structure| structure.tiles())
+        .collect();
+    for origin in placement_line(start_tile_pos, tile_pos, definition) {
+        let valid = definition.can_place(origin, &terrain, &occupied_tiles, &material_properties);
+        let structure = spawn_structure(
+            &mut commands,
+            &asset_server,
+            &terrain_settings,
+            definition,
+            origin,
+        );
+        // Ghosts further along the line are tinted as if the valid ones before them were placed
+        if valid {
+            occupied_tiles.extend(footprint_tiles(origin, definition.footprint));
+        }
+        if place && valid {
+            info!(
+                structure = definition.name.as_str(),
+                ?origin,
+                "Designated construction"
+            );
+            let mut structure_commands = commands.entity(structure);
+            structure_commands
+                .insert((UnderConstruction::default(), definition.materials_needed()));
+            if let Some(priority) = designation_priority.0 {
+                structure_commands.insert(JobPriority(priority));
+            }
+
+            let dig_jobs: HashSet<Entity> = definition
+                .tiles_to_dig(origin, &terrain)
+                .into_iter()
+                .filter_map(|tile_pos| {
+                    let tile_entity = terrain.get_tile_entity(tile_pos)?;
+                    let tile_translation = terrain.tile_to_global_pos(tile_pos)?;
+                    let designated_job = dig_job_query
+                        .iter()
+                        .find(|(_, dig_job)| dig_job.0 == tile_entity)
+                        .map(|(job_entity, _)| job_entity);
+                    Some(designated_job.unwrap_or_else(|| {
+                        spawn_dig_job(
+                            &mut commands,
+                            tile_entity,
+                            tile_translation,
+                            designation_priority.0.map(JobPriority),
+                        )
+                    }))
+                })
+                .collect();
+            if !dig_jobs.is_empty() {
+                info!(dig_jobs = dig_jobs.len(), "Construction waits for digging");
+                commands.entity(structure).insert(Prerequisites(dig_jobs));
+            }
+        } else {
+            let color = if valid {
+                VALID_GHOST_COLOR
+            } else {
+                INVALID_GHOST_COLOR
+            };
+            commands.entity(structure).insert((
+                Ghost,
+                Sprite {
+                    color,
+                    custom_size: Some(definition.size(terrain_settings.cell_size)),
+                    ..default()
+                },
+            ));
+        }
     }
 }
 
+/// Origins of structures in a line from `start` towards `end`, one footprint apart, along
+/// whichever axis the line covers more of.
+///
+/// Sorted bottom to top, so structures that need support below can stand on the ones placed
+/// before them.
+fn placement_line(start: IVec2, end: IVec2, definition: &StructureDefinition) -> Vec<IVec2> {
+    let delta = end - start;
+    let footprint = definition.footprint.as_ivec2();
+    let (step, count) = if delta.x.abs() >= delta.y.abs() {
+        (
+            IVec2::new(footprint.x * delta.x.signum(), 0),
+            delta.x.abs() / footprint.x,
+        )
+    } else {
+        (
+            IVec2::new(0, footprint.y * delta.y.signum()),
+            delta.y.abs() / footprint.y,
+        )
+    };
+    let mut origins: Vec<IVec2> = (0..=count).map(|i| start + step * i).collect();
+    origins.sort_by_key(|origin| (origin.y, origin.x));
+    origins
+}
+
 #[derive(Component)]
 struct WaitingForResources;
 
 fn designate_building_materials(
     mut commands: Commands,
     construction_query: Query<
-        (Entity, &GlobalTransform, &mut BuildingMaterialsNeeded),
-        Without<WaitingForResources>,
+        (
+            Entity,
+            &GlobalTransform,
+            &mut BuildingMaterialsNeeded,
+            Option<&JobPriority>,
+        ),
+        (Without<WaitingForResources>, Without<Prerequisites>),
     >,
     building_material_locator: BuildingMaterialLocator,
     building_material_query: Query<&GlobalTransform, With<BuildingMaterial>>,
 ) {
-    for (construction_entity, construction_transform, resources_needed) in
+    for (construction_entity, construction_transform, resources_needed, opt_priority) in
         &mut construction_query.iter()
     {
         let mut closest_resource = None;
         let mut closest_distance = f32::MAX;
-        for (resource_name, _amount) in resources_needed.0.iter() {
+        for (resource_name, amount) in resources_needed.0.iter() {
+            if *amount == 0 {
+                continue;
+            }
             if let Some(resource_entity) = building_material_locator
                 .get_closest(resource_name, construction_transform.translation())
             {
@@ -183,13 +335,16 @@ fn designate_building_materials(
                 let y = resource_transform.translation().y;
                 ActionArea(vec![Vec2::new(x, y)])
             };
-            let haul_job = commands
-                .spawn((
-                    Job,
-                    HaulRequest::request_entity(resource_entity, construction_entity),
-                    pickup_area,
-                ))
-                .id();
+            let mut haul_commands = commands.spawn((
+                Job,
+                HaulRequest::request_entity(resource_entity, construction_entity),
+                Labor::Hauling,
+                pickup_area,
+            ));
+            if let Some(priority) = opt_priority {
+                haul_commands.insert(*priority);
+            }
+            let haul_job = haul_commands.id();
 
             commands
                 .entity(construction_entity)
@@ -201,69 +356,127 @@ fn designate_building_materials(
     }
 }
 
+/// Work on the construction site `0`, requested once all of its building materials arrived.
 #[derive(Component, Debug, Clone, Reflect)]
-struct ConstructionJob(Entity);
+pub struct ConstructionJob(pub Entity);
+
+impl HasActionArea for ConstructionJob {
+    fn action_area() -> ActionArea {
+        BuildTarget::action_area()
+    }
+}
 
-#[derive(Component, Default, Debug)]
-struct Builder;
+impl HasActionPosition for ConstructionJob {
+    type PositionParam = BuildActionSystemParam<'static, 'static>;
 
+    fn action_pos(
+        &self,
+        build_action_param: &SystemParamItem<Self::PositionParam>,
+    ) -> Option<Vec2> {
+        BuildTarget(self.0).action_pos(build_action_param)
+    }
+}
+
+/// Stops construction sites from waiting once their haul is done, or gone, so the next material
+/// can be requested. Sites that got everything they need stop needing building materials.
 fn materials_delivered(
-    _commands: Commands,
-    _construction_query: Query<
-        (&GlobalTransform, &mut BuildingMaterialsNeeded),
+    mut commands: Commands,
+    construction_query: Query<
+        (Entity, &BuildingMaterialsNeeded, Option<&Children>),
         With<WaitingForResources>,
     >,
-    mut job_completed_event_reader: EventReader<JobCompletedEvent>,
-    _building_material_query: Query<&Name, With<BuildingMaterial>>,
+    haul_job_query: Query<(), (With<HaulRequest>, With<Job>)>,
 ) {
-    for _job in job_completed_event_reader.iter() {}
-}
-
-#[derive(Component)]
-struct Constructing(Entity);
-
-#[derive(Component)]
-struct BuildTimer(Timer);
+    for (construction_entity, materials_needed, opt_children) in &construction_query {
+        let hauling = opt_children.map_or(false, |children| {
+            children.iter().any(|child| haul_job_query.contains(*child))
+        });
+        if hauling {
+            continue;
+        }
 
-impl Default for BuildTimer {
-    fn default() -> Self {
-        Self(Timer::from_seconds(1.0, TimerMode::Repeating))
+        let mut construction_commands = commands.entity(construction_entity);
+        construction_commands.remove::<WaitingForResources>();
+        if materials_needed.all_delivered() {
+            info!(construction_site = ?construction_entity, "All building materials delivered");
+            construction_commands.remove::<BuildingMaterialsNeeded>();
+        }
     }
 }
 
-fn start_building(
+/// Requests a construction job for every construction site that has all its building materials
+/// and nobody building it yet.
+fn request_construction_jobs(
     mut commands: Commands,
-    worker_query: Query<(Entity, &AssignedJob), (Without<Constructing>, With<Builder>)>,
-    construction_job_query: Query<&ConstructionJob>,
+    construction_query: Query<
+        (
+            Entity,
+            &PlacedStructure,
+            Option<&BuildingMaterialsNeeded>,
+            Option<&JobPriority>,
+            Option<&Children>,
+        ),
+        (
+            With<UnderConstruction>,
+            Without<Ghost>,
+            Without<Prerequisites>,
+        ),
+    >,
+    construction_job_query: Query<(), (With<ConstructionJob>, With<Job>)>,
+    terrain_settings: Res<TerrainSettings>,
 ) {
-    for (worker_entity, assigned_job) in &worker_query {
-        if let Ok(construction_entity) = construction_job_query.get(assigned_job.0).map(|job| job.0)
+    for (construction_entity, structure, opt_materials_needed, opt_priority, opt_children) in
+        &construction_query
+    {
+        if opt_materials_needed.map_or(false, |materials_needed| !materials_needed.all_delivered())
         {
-            commands
-                .entity(worker_entity)
-                .insert((BuildTimer::default(), Constructing(construction_entity)));
+            continue;
+        }
+        let requested = opt_children.map_or(false, |children| {
+            children
+                .iter()
+                .any(|child| construction_job_query.contains(*child))
+        });
+        if requested {
+            continue;
         }
+
+        let action_area =
+            BuildTarget::action_area().offset(terrain_settings.tile_to_world(structure.origin));
+        let mut job_commands = commands.spawn((
+            Job,
+            ConstructionJob(construction_entity),
+            Labor::Construction,
+            action_area,
+        ));
+        if let Some(priority) = opt_priority {
+            job_commands.insert(*priority);
+        }
+        let construction_job = job_commands.id();
+        commands
+            .entity(construction_entity)
+            .add_child(construction_job);
+        info!(construction_site = ?construction_entity, job = ?construction_job, "Requested construction");
     }
 }
 
-fn build_timer(
-    time: Res<Time>,
-    mut constructing_worker_query: Query<
-        (&mut BuildTimer, &AssignedJob),
-        (With<Worker>, With<Constructing>),
-    >,
-    construction_job_query: Query<&ConstructionJob>,
-    mut construction_site_query: Query<&mut UnderConstruction>,
+fn despawn_completed_construction_jobs(
+    mut commands: Commands,
+    completed_job_query: Query<Entity, (With<ConstructionJob>, With<CompletedJob>)>,
 ) {
-    for (mut timer, job) in &mut constructing_worker_query {
-        if timer.0.tick(time.delta()).just_finished() {
-            if let Ok(mut construction_site) = construction_job_query
-                .get(job.0)
-                .and_then(|cj| construction_site_query.get_mut(cj.0))
-            {
-                construction_site.add_progress(20);
-            }
-        }
+    for job_entity in &completed_job_query {
+        commands.entity(job_entity).despawn_recursive();
+    }
+}
+
+/// Removes construction sites whose tiles can't be dug out anymore.
+fn cancel_undiggable_constructions(
+    mut commands: Commands,
+    canceled_query: Query<Entity, (With<UnderConstruction>, With<PrerequisiteCanceled>)>,
+) {
+    for construction_entity in &canceled_query {
+        info!(construction_site = ?construction_entity, "Digging canceled, removing construction");
+        commands.entity(construction_entity).despawn_recursive();
     }
 }
 
@@ -276,7 +489,6 @@ fn finish_building(
     mut commands: Commands,
     construction_site_query: Query<(Entity, &UnderConstruction), Changed<UnderConstruction>>,
     mut construction_completed_event_writer: EventWriter<ConstructionCompletedEvent>,
-    construction_job_query: Query<&ConstructionJob>,
 ) {
     for (construction_site_entity, construction_site) in &mut construction_site_query.iter() {
         if construction_site.finished() {
@@ -285,13 +497,6 @@ fn finish_building(
                 .remove::<UnderConstruction>()
                 .insert(Structure);
 
-            if let Some(construction_job) = construction_job_query
-                .iter()
-                .find(|cj| cj.0 == construction_site_entity)
-            {
-                commands.entity(construction_job.0).insert(CompletedJob);
-            }
-
             construction_completed_event_writer.send(ConstructionCompletedEvent {
                 construction_site: construction_site_entity,
             });
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{CollisionGroups, RapierContext};
+
+use crate::{
+    building_material::Reserved,
+    cursor_position::LastCursorPosition,
+    designation_layer::Designated,
+    item::{spawn_item, ItemDefinitions},
+    labor::{
+        build_structure::{BuildingMaterialsNeeded, Ghost, UnderConstruction},
+        chop_tree::{FellingJob, PICKER_COLLISION_GROUP},
+        dig_tile::DigJob,
+        haul::{HaulItem, HaulRequest},
+        job::{AssignedWorker, Job, JobAssignmentSet, JobManagerParams},
+    },
+    structure::{PlacedStructure, StructureDefinitions},
+    terrain::TerrainParam,
+    terrain_settings::TerrainSettings,
+    tree::TREE_COLLISION_GROUP,
+};
+
+pub struct CancelPlugin;
+
+impl Plugin for CancelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<CancelToolState>()
+            .register_type::<CancelToolState>()
+            .add_systems(
+                Update,
+                (cancel_designations, cancel_constructions)
+                    .run_if(state_exists_and_equals(CancelToolState::Canceling))
+                    .run_if(resource_exists::<TerrainSettings>())
+                    .before(JobAssignmentSet),
+            );
+    }
+}
+
+#[derive(States, Default, Reflect, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum CancelToolState {
+    #[default]
+    Inactive,
+    Canceling,
+}
+
+/// Cancels the dig and felling orders under the cursor when clicked.
+fn cancel_designations(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_position: Res<LastCursorPosition>,
+    rapier_context: Res<RapierContext>,
+    terrain: TerrainParam,
+    terrain_settings: Res<TerrainSettings>,
+    parent_query: Query<&Parent>,
+    dig_job_query: Query<(Entity, &DigJob, Option<&AssignedWorker>), With<Job>>,
+    felling_job_query: Query<(Entity, &FellingJob, Option<&AssignedWorker>), With<Job>>,
+    mut job_manager_params: JobManagerParams,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let tile_pos = terrain_settings.world_to_tile(cursor_position.0);
+
+    if let Some(tile_entity) = terrain.get_tile_entity(tile_pos) {
+        for (job_entity, _, opt_worker) in dig_job_query
+            .iter()
+            .filter(|(_, dig_job, _)| dig_job.0 == tile_entity)
+        {
+            info!(job = ?job_entity, ?tile_pos, "Canceled dig job");
+            job_manager_params.cancel_job_and_assignment(job_entity, opt_worker);
+            commands.entity(tile_entity).remove::<Designated>();
+        }
+    }
+
+    rapier_context.intersections_with_point(
+        cursor_position.0,
+        CollisionGroups::new(PICKER_COLLISION_GROUP, TREE_COLLISION_GROUP).into(),
+        |hit_entity| {
+            let Ok(tree_entity) = parent_query.get(hit_entity).map(|parent| parent.get()) else { return true; };
+            for (job_entity, _, opt_worker) in felling_job_query
+                .iter()
+                .filter(|(_, felling_job, _)| felling_job.0 == tree_entity)
+            {
+                info!(job = ?job_entity, tree = ?tree_entity, "Canceled felling job");
+                job_manager_params.cancel_job_and_assignment(job_entity, opt_worker);
+                commands.entity(tree_entity).remove::<Designated>();
+            }
+            true
+        },
+    );
+}
+
+/// Cancels the construction under the cursor when clicked.
+///
+/// The materials delivered to the site are dropped where it stood and the ones not picked up yet
+/// are freed, hauls on their way there drop their items where the hauler stands.
+fn cancel_constructions(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor_position: Res<LastCursorPosition>,
+    terrain_settings: Res<TerrainSettings>,
+    construction_query: Query<
+        (
+            Entity,
+            &PlacedStructure,
+            Option<&BuildingMaterialsNeeded>,
+            Option<&Children>,
+        ),
+        (With<UnderConstruction>, Without<Ghost>),
+    >,
+    construction_job_query: Query<(Option<&HaulRequest>, Option<&AssignedWorker>)>,
+    reserved_query: Query<(), With<Reserved>>,
+    mut job_manager_params: JobManagerParams,
+    structure_definitions: Option<Res<StructureDefinitions>>,
+    item_definitions: Option<Res<ItemDefinitions>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let tile_pos = terrain_settings.world_to_tile(cursor_position.0);
+
+    for (construction_entity, structure, opt_materials_needed, opt_children) in &construction_query
+    {
+        if !structure
+            .tiles()
+            .any(|structure_tile| structure_tile == tile_pos)
+        {
+            continue;
+        }
+        for &job_entity in opt_children.into_iter().flatten() {
+            let Ok((opt_haul_request, opt_worker)) = construction_job_query.get(job_entity) else { continue; };
+            if let Some(worker) = opt_worker {
+                job_manager_params.cancel_job_assignment(job_entity, worker.0);
+            }
+            // Materials that weren't picked up yet can be used elsewhere
+            if let Some(HaulRequest {
+                load: HaulItem::Entity(load),
+                ..
+            }) = opt_haul_request
+            {
+                if reserved_query.contains(*load) {
+                    commands.entity(*load).remove::<Reserved>();
+                }
+            }
+        }
+
+        let definition = structure_definitions
+            .as_deref()
+            .and_then(|structure_definitions| structure_definitions.get(&structure.name));
+        if let (Some(definition), Some(materials_needed), Some(item_definitions)) = (
+            definition,
+            opt_materials_needed,
+            item_definitions.as_deref(),
+        ) {
+            let position = terrain_settings.tile_to_world(structure.origin);
+            for (item, amount) in &definition.materials {
+                let missing = materials_needed
+                    .resources_needed()
+                    .iter()
+                    .filter(|(name, _)| name.as_str() == item)
+                    .map(|(_, missing)| *missing)
+                    .sum::<u32>();
+                let delivered = amount.saturating_sub(missing);
+                let Some(item_definition) = item_definitions.get(item) else { continue; };
+                if delivered == 0 {
+                    continue;
+                }
+                info!(
+                    item = item.as_str(),
+                    count = delivered,
+                    "Dropped delivered materials"
+                );
+                spawn_item(
+                    &mut commands,
+                    item_definition,
+                    delivered,
+                    position,
+                    &mut materials,
+                    &mut meshes,
+                );
+            }
+        }
+        info!(construction_site = ?construction_entity, "Canceled construction");
+        commands.entity(construction_entity).despawn_recursive();
+    }
+}
@@ -2,9 +2,10 @@ use bevy::{
     ecs::system::{lifetimeless::SQuery, StaticSystemParam, SystemParamItem},
     math::Vec3Swizzles,
     prelude::*,
+    utils::HashSet,
 };
 
-use bevy_rapier2d::prelude::{CollisionGroups, Group, RapierContext};
+use bevy_rapier2d::prelude::{Collider, CollisionGroups, Group, RapierContext};
 
 use crate::{
     actions::{
@@ -12,12 +13,13 @@ use crate::{
         fell::{Fell, FellTarget},
     },
     cursor_position::LastCursorPosition,
-    designation_layer::Designated,
-    labor::job::{all_workers_eligible, Job},
+    designation_layer::{clear_designation_area, Designated, DesignationArea, DesignationMode},
+    labor::job::{DesignationPriority, Job, JobPriority, Labor},
+    terrain_settings::TerrainSettings,
     tree::{Tree, TREE_COLLISION_GROUP},
 };
 
-use super::job::{JobAssignmentSet, JobManagerParams};
+use super::job::{AssignedWorker, JobAssignmentSet, JobManagerParams};
 
 pub struct ChopTreePlugin;
 
@@ -26,11 +28,17 @@ impl Plugin for ChopTreePlugin {
         app.add_event::<FellingCompleteEvent>()
             .add_state::<FellingToolState>()
             .register_type::<FellingJob>()
+            .init_resource::<DesignationArea>()
+            .add_systems(
+                OnExit(FellingToolState::Designating),
+                clear_designation_area,
+            )
             .add_systems(
                 Update,
                 (
-                    mark_trees.run_if(state_exists_and_equals(FellingToolState::Designating)),
-                    all_workers_eligible::<FellingJob>,
+                    designate_trees
+                        .run_if(state_exists_and_equals(FellingToolState::Designating))
+                        .run_if(resource_exists::<TerrainSettings>()),
                     cancel_felling_jobs,
                 )
                     .before(JobAssignmentSet),
@@ -67,52 +75,98 @@ pub enum FellingToolState {
 
 pub const PICKER_COLLISION_GROUP: Group = Group::GROUP_4;
 
-fn mark_trees(
+/// Marks every tree reaching into the dragged out area for felling, or cancels their felling jobs
+/// when dragged in [`DesignationMode::Remove`].
+fn designate_trees(
     mut commands: Commands,
     mouse_button_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
     cursor_position: Res<LastCursorPosition>,
+    mut designation_area: ResMut<DesignationArea>,
+    terrain_settings: Res<TerrainSettings>,
     rapier_context: Res<RapierContext>,
     parent_query: Query<&Parent>,
-    tree_query: Query<&GlobalTransform, With<Tree>>,
+    tree_query: Query<(&GlobalTransform, Option<&Designated>), With<Tree>>,
+    felling_job_query: Query<(Entity, &FellingJob, Option<&AssignedWorker>), With<Job>>,
+    designation_priority: Res<DesignationPriority>,
+    mut job_manager_params: JobManagerParams,
 ) {
-    if mouse_button_input.just_pressed(MouseButton::Left) {
-        rapier_context.intersections_with_point(
-            Vec2::new(cursor_position.0.x, cursor_position.0.y),
-            CollisionGroups::new(PICKER_COLLISION_GROUP, TREE_COLLISION_GROUP).into(),
-            |hit_entity| {
-                let Ok((tree_entity, tree_transform)) =
-                    parent_query.get(hit_entity).and_then(|parent| {
-                        tree_query
-                            .get(**parent)
-                            .map(|tree_transform| (**parent, tree_transform))
-                    }) else {
-                    error!("Tree entity not found");
-                    return true;
-                };
-                let tree_translation = tree_transform.translation().xy();
-                commands.entity(tree_entity).insert(Designated);
-                let action_area = ActionArea(vec![
-                            Vec2::new(tree_translation.x - 16., tree_translation.y),
-                            Vec2::new(tree_translation.x + 16., tree_translation.y),
-                        ]);
-                let job_entity = commands
-                    .spawn((
-                        Job,
-                        FellingJob(tree_entity),
-                        action_area.clone(),
-                    ))
-                    .id();
-                info!(job = ?job_entity, tree=?tree_entity, action_area=?action_area, "Marked tree for felling");
-                false
-            },
-        );
+    let tile_pos = terrain_settings.world_to_tile(cursor_position.0);
+    let Some(area) = designation_area.drag(&mouse_button_input, &keyboard_input, tile_pos) else { return; };
+
+    let (center, size) = area.world_rect(&terrain_settings);
+    let mut trees = HashSet::new();
+    rapier_context.intersections_with_shape(
+        center,
+        0.,
+        &Collider::cuboid(size.x / 2., size.y / 2.),
+        CollisionGroups::new(PICKER_COLLISION_GROUP, TREE_COLLISION_GROUP).into(),
+        |hit_entity| {
+            if let Ok(parent) = parent_query.get(hit_entity) {
+                trees.insert(parent.get());
+            }
+            true
+        },
+    );
+
+    for tree_entity in trees {
+        let Ok((tree_transform, opt_designated)) = tree_query.get(tree_entity) else {
+            error!("Tree entity not found");
+            continue;
+        };
+        match designation_area.mode {
+            DesignationMode::Add => {
+                if opt_designated.is_some() {
+                    continue;
+                }
+                let job_entity = spawn_felling_job(
+                    &mut commands,
+                    tree_entity,
+                    tree_transform.translation().xy(),
+                    designation_priority.0.map(JobPriority),
+                );
+                info!(job = ?job_entity, tree = ?tree_entity, "Marked tree for felling");
+            }
+            DesignationMode::Remove => {
+                for (job_entity, _, opt_worker) in felling_job_query
+                    .iter()
+                    .filter(|(_, felling_job, _)| felling_job.0 == tree_entity)
+                {
+                    info!(job = ?job_entity, tree = ?tree_entity, "Canceled felling job");
+                    job_manager_params.cancel_job_and_assignment(job_entity, opt_worker);
+                    commands.entity(tree_entity).remove::<Designated>();
+                }
+            }
+        }
+    }
+}
+
+pub fn spawn_felling_job(
+    commands: &mut Commands,
+    tree_entity: Entity,
+    tree_translation: Vec2,
+    priority: Option<JobPriority>,
+) -> Entity {
+    commands.entity(tree_entity).insert(Designated);
+    let action_area = ActionArea(vec![
+        Vec2::new(tree_translation.x - 16., tree_translation.y),
+        Vec2::new(tree_translation.x + 16., tree_translation.y),
+    ]);
+    let mut job_commands = commands.spawn((
+        Job,
+        FellingJob(tree_entity),
+        Labor::Woodcutting,
+        action_area,
+    ));
+    if let Some(priority) = priority {
+        job_commands.insert(priority);
     }
+    job_commands.id()
 }
 
 #[derive(Event)]
 pub struct FellingCompleteEvent {
     pub job: Entity,
-    pub parent_job: Option<Entity>,
     pub worker: Entity,
     pub tree: Entity,
 }
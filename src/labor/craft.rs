@@ -0,0 +1,161 @@
+use bevy::{
+    ecs::system::{lifetimeless::SQuery, SystemParamItem},
+    math::Vec3Swizzles,
+    prelude::*,
+};
+
+use crate::{
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        craft::CraftTarget,
+    },
+    labor::{
+        build_structure::BuildingMaterialsNeeded,
+        job::{CompletedJob, Job, JobAssignmentSet, Labor},
+    },
+};
+
+pub struct CraftPlugin;
+
+impl Plugin for CraftPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Workshop>()
+            .register_type::<CraftOrder>()
+            .register_type::<CraftJob>()
+            .add_systems(
+                Update,
+                (request_craft_jobs, despawn_completed_craft_orders).before(JobAssignmentSet),
+            );
+    }
+}
+
+/// Materials and work it takes to craft an item.
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct Recipe {
+    /// Items, by name, that have to be delivered to the workshop before the item can be crafted.
+    pub materials: Vec<(String, u32)>,
+    /// Seconds of work it takes to craft the item.
+    pub craft_time: f32,
+}
+
+impl Recipe {
+    pub fn materials_needed(&self) -> BuildingMaterialsNeeded {
+        BuildingMaterialsNeeded::new(
+            self.materials
+                .iter()
+                .map(|(name, amount)| (Name::new(name.clone()), *amount))
+                .collect(),
+        )
+    }
+}
+
+/// A finished structure items can be ordered at.
+#[derive(Component, Debug, Reflect)]
+pub struct Workshop;
+
+/// Order to craft a single `item` at the workshop that is the order's parent.
+///
+/// The order needs the materials of the item's recipe hauled to it, like a construction site,
+/// before a crafting job is requested.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct CraftOrder {
+    pub item: String,
+}
+
+/// A craft order whose item was crafted, despawned once its job is completed.
+#[derive(Component, Debug)]
+pub struct Crafted;
+
+/// Crafts the item of the craft order `0`.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct CraftJob(pub Entity);
+
+impl HasActionArea for CraftJob {
+    fn action_area() -> ActionArea {
+        CraftTarget::action_area()
+    }
+}
+
+impl HasActionPosition for CraftJob {
+    type PositionParam = SQuery<&'static GlobalTransform>;
+
+    fn action_pos(
+        &self,
+        global_transform_query: &SystemParamItem<Self::PositionParam>,
+    ) -> Option<Vec2> {
+        CraftTarget(self.0).action_pos(global_transform_query)
+    }
+}
+
+/// Orders `item` at `workshop`, waiting for `materials_needed` to be delivered.
+pub fn spawn_craft_order(
+    commands: &mut Commands,
+    workshop: Entity,
+    item: String,
+    materials_needed: BuildingMaterialsNeeded,
+) -> Entity {
+    let order = commands
+        .spawn((
+            Name::new(format!("Craft {item}")),
+            CraftOrder { item },
+            materials_needed,
+            TransformBundle::default(),
+        ))
+        .id();
+    commands.entity(workshop).add_child(order);
+    order
+}
+
+/// Requests a craft job for every order that has all its materials and no job yet.
+fn request_craft_jobs(
+    mut commands: Commands,
+    order_query: Query<
+        (
+            Entity,
+            &Parent,
+            Option<&BuildingMaterialsNeeded>,
+            Option<&Children>,
+        ),
+        (With<CraftOrder>, Without<Crafted>),
+    >,
+    workshop_query: Query<&GlobalTransform, With<Workshop>>,
+    craft_job_query: Query<(), (With<CraftJob>, With<Job>)>,
+) {
+    for (order_entity, workshop, opt_materials_needed, opt_children) in &order_query {
+        if opt_materials_needed.map_or(false, |materials_needed| !materials_needed.all_delivered())
+        {
+            continue;
+        }
+        let requested = opt_children.map_or(false, |children| {
+            children
+                .iter()
+                .any(|child| craft_job_query.contains(*child))
+        });
+        if requested {
+            continue;
+        }
+        // Orders sit at the center of their workshop
+        let Ok(workshop_transform) = workshop_query.get(workshop.get()) else { continue; };
+
+        let craft_job = commands
+            .spawn((
+                Job,
+                CraftJob(order_entity),
+                Labor::Crafting,
+                CraftJob::action_area().offset(workshop_transform.translation().xy()),
+            ))
+            .id();
+        commands.entity(order_entity).add_child(craft_job);
+        info!(order = ?order_entity, job = ?craft_job, "Requested crafting");
+    }
+}
+
+/// Despawns orders, and their jobs, once their item is crafted and the job completed.
+fn despawn_completed_craft_orders(
+    mut commands: Commands,
+    completed_job_query: Query<&CraftJob, With<CompletedJob>>,
+) {
+    for craft_job in &completed_job_query {
+        commands.entity(craft_job.0).despawn_recursive();
+    }
+}
@@ -3,17 +3,18 @@ use bevy::{
     math::Vec3Swizzles,
     prelude::*,
 };
-use bevy_ecs_tilemap::tiles::TilePos;
 
 use crate::{
     actions::{
         action_area::{ActionArea, HasActionArea, HasActionPosition},
         dig::{Dig, DigActionSystemParam, DigTarget},
     },
-    designation_layer::Designated,
-    hovered_tile::HoveredTile,
-    labor::job::{all_workers_eligible, Job},
-    terrain::TerrainParam,
+    cursor_position::LastCursorPosition,
+    designation_layer::{clear_designation_area, Designated, DesignationArea, DesignationMode},
+    labor::job::{DesignationPriority, Job, JobManagerParams, JobPriority, Labor},
+    material::MaterialProperties,
+    terrain::{TerrainParam, TerrainTiles},
+    terrain_settings::TerrainSettings,
 };
 
 use super::job::{AssignedWorker, CompletedJob};
@@ -26,11 +27,14 @@ impl Plugin for DigPlugin {
             .register_type::<DigJob>()
             .register_type::<DigToolState>()
             .add_state::<DigToolState>()
+            .init_resource::<DesignationArea>()
+            .add_systems(OnExit(DigToolState::Designating), clear_designation_area)
             .add_systems(
                 Update,
                 (
-                    designate_dig.run_if(state_exists_and_equals(DigToolState::Designating)),
-                    all_workers_eligible::<DigJob>,
+                    designate_dig
+                        .run_if(state_exists_and_equals(DigToolState::Designating))
+                        .run_if(resource_exists::<TerrainSettings>()),
                     schedule_dig_action,
                     finish_digjob,
                 ),
@@ -62,45 +66,97 @@ pub enum DigToolState {
     Designating,
 }
 
+/// Designates every diggable tile in the dragged out area, or cancels the dig jobs in it when
+/// dragged in [`DesignationMode::Remove`].
 fn designate_dig(
     mut commands: Commands,
     mouse_button_input: Res<Input<MouseButton>>,
-    tile_query: Query<(Entity, &TilePos), With<HoveredTile>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    cursor_position: Res<LastCursorPosition>,
+    mut designation_area: ResMut<DesignationArea>,
     terrain: TerrainParam,
+    terrain_settings: Res<TerrainSettings>,
+    material_properties: Res<MaterialProperties>,
+    designation_priority: Res<DesignationPriority>,
+    designated_query: Query<(), With<Designated>>,
+    dig_job_query: Query<(Entity, &DigJob, Option<&AssignedWorker>), With<Job>>,
+    mut job_manager_params: JobManagerParams,
 ) {
-    if mouse_button_input.just_pressed(MouseButton::Left) {
-        for (tile_entity, tile_pos) in &tile_query {
-            let tile_translation = terrain.tile_to_global_pos(*tile_pos);
-            let x = tile_translation.x;
-            let y = tile_translation.y;
-            commands.entity(tile_entity).insert(Designated);
-            let job_entity = commands
-                .spawn((
-                    Job,
-                    DigJob(tile_entity),
-                    ActionArea(vec![
-                        // West
-                        Vec2::new(x - 16., y),
-                        // East
-                        Vec2::new(x + 16., y),
-                        // South
-                        Vec2::new(x, y - 16.),
-                        // Northwest
-                        Vec2::new(x - 16., y + 16.),
-                        // Southwest
-                        Vec2::new(x - 16., y - 16.),
-                        // Southeast
-                        Vec2::new(x + 16., y - 16.),
-                        // Northeast
-                        Vec2::new(x + 16., y + 16.),
-                    ]),
-                ))
-                .id();
-            info!(job=?job_entity, tile=?tile_entity, tile_pos=?tile_pos,  "Designated dig job");
+    let tile_pos = terrain_settings.world_to_tile(cursor_position.0);
+    let Some(area) = designation_area.drag(&mouse_button_input, &keyboard_input, tile_pos) else { return; };
+
+    match designation_area.mode {
+        DesignationMode::Add => {
+            let mut designated = 0;
+            for tile_pos in area.tiles() {
+                let Some(tile_entity) = terrain.get_tile_entity(tile_pos) else { continue; };
+                let diggable = terrain
+                    .get_tile(tile_pos)
+                    .map_or(false, |material| material_properties.diggable(material));
+                if !diggable || designated_query.contains(tile_entity) {
+                    continue;
+                }
+                let Some(tile_translation) = terrain.tile_to_global_pos(tile_pos) else { continue; };
+                spawn_dig_job(
+                    &mut commands,
+                    tile_entity,
+                    tile_translation,
+                    designation_priority.0.map(JobPriority),
+                );
+                designated += 1;
+            }
+            info!(min = ?area.min, max = ?area.max, designated, "Designated dig area");
+        }
+        DesignationMode::Remove => {
+            for (job_entity, dig_job, opt_worker) in &dig_job_query {
+                let Some(tile_pos) = terrain.get_entity_tile_pos(dig_job.0) else { continue; };
+                if !area.contains(tile_pos) {
+                    continue;
+                }
+                info!(job = ?job_entity, ?tile_pos, "Canceled dig job");
+                job_manager_params.cancel_job_and_assignment(job_entity, opt_worker);
+                commands.entity(dig_job.0).remove::<Designated>();
+            }
         }
     }
 }
 
+pub fn spawn_dig_job(
+    commands: &mut Commands,
+    tile_entity: Entity,
+    tile_translation: Vec2,
+    priority: Option<JobPriority>,
+) -> Entity {
+    let x = tile_translation.x;
+    let y = tile_translation.y;
+    commands.entity(tile_entity).insert(Designated);
+    let mut job_commands = commands.spawn((
+        Job,
+        DigJob(tile_entity),
+        Labor::Mining,
+        ActionArea(vec![
+            // West
+            Vec2::new(x - 16., y),
+            // East
+            Vec2::new(x + 16., y),
+            // South
+            Vec2::new(x, y - 16.),
+            // Northwest
+            Vec2::new(x - 16., y + 16.),
+            // Southwest
+            Vec2::new(x - 16., y - 16.),
+            // Southeast
+            Vec2::new(x + 16., y - 16.),
+            // Northeast
+            Vec2::new(x + 16., y + 16.),
+        ]),
+    ));
+    if let Some(priority) = priority {
+        job_commands.insert(priority);
+    }
+    job_commands.id()
+}
+
 #[derive(Component)]
 struct AwaitingDig(pub Entity);
 
@@ -119,7 +175,6 @@ fn schedule_dig_action(
 #[derive(Event)]
 pub struct DigJobCompleteEvent {
     pub job: Entity,
-    pub parent_job: Option<Entity>,
     pub worker: Entity,
     pub tile: Entity,
 }
@@ -0,0 +1,287 @@
+use bevy::{
+    ecs::system::{lifetimeless::SQuery, SystemParamItem},
+    math::Vec3Swizzles,
+    prelude::*,
+    sprite::MaterialMesh2dBundle,
+    utils::HashSet,
+};
+
+use crate::{
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        harvest::HarvestTarget,
+    },
+    cursor_position::LastCursorPosition,
+    designation_layer::{clear_designation_area, DesignationArea, DesignationMode},
+    labor::job::{
+        AssignedWorker, CanceledJob, CompletedJob, Job, JobAssignmentSet, JobManagerParams, Labor,
+    },
+    material::MaterialProperties,
+    plant::{spawn_plant, Plant, PlantKind, Ripe, PLANT_LAYER_Z},
+    terrain::{TerrainChunk, TerrainParam, TerrainTiles, TileDestroyedEvent, TilePlacedEvent},
+    terrain_settings::TerrainSettings,
+};
+
+pub struct FarmPlugin;
+
+impl Plugin for FarmPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<FarmToolState>()
+            .register_type::<FarmToolState>()
+            .register_type::<FarmPlot>()
+            .register_type::<HarvestJob>()
+            .init_resource::<DesignationArea>()
+            .add_systems(OnExit(FarmToolState::Designating), clear_designation_area)
+            .add_systems(
+                Update,
+                (
+                    designate_farm_plots
+                        .run_if(state_exists_and_equals(FarmToolState::Designating))
+                        .run_if(resource_exists::<TerrainSettings>())
+                        .run_if(resource_exists::<MaterialProperties>()),
+                    remove_lost_farm_plots.run_if(resource_exists::<TerrainSettings>()),
+                    request_harvests,
+                    cancel_harvest_jobs,
+                    despawn_finished_harvest_jobs,
+                )
+                    .before(JobAssignmentSet),
+            );
+    }
+}
+
+#[derive(States, Default, Reflect, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum FarmToolState {
+    #[default]
+    Inactive,
+    Designating,
+}
+
+/// A dirt tile crops are grown on. The crop is a child of the plot.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct FarmPlot(pub IVec2);
+
+/// Harvests the ripe plant `0`.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct HarvestJob(pub Entity);
+
+impl HasActionArea for HarvestJob {
+    fn action_area() -> ActionArea {
+        HarvestTarget::action_area()
+    }
+}
+
+impl HasActionPosition for HarvestJob {
+    type PositionParam = SQuery<&'static GlobalTransform>;
+
+    fn action_pos(
+        &self,
+        global_transform_query: &SystemParamItem<Self::PositionParam>,
+    ) -> Option<Vec2> {
+        HarvestTarget(self.0).action_pos(global_transform_query)
+    }
+}
+
+/// Material of the tiles farm plots can be designated on.
+pub const FARM_SOIL_MATERIAL: &str = "Dirt";
+pub const FARM_CROP: PlantKind = PlantKind::Potato;
+pub const FARM_PLOT_LAYER_Z: f32 = 0.5;
+const FARM_PLOT_COLOR: Color = Color::rgba(0.4, 0.6, 0.1, 0.3);
+
+/// Turns every dirt tile with open air above it in the dragged out area into a farm plot, or
+/// removes the farm plots in it when dragged in [`DesignationMode::Remove`].
+fn designate_farm_plots(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    cursor_position: Res<LastCursorPosition>,
+    mut designation_area: ResMut<DesignationArea>,
+    terrain: TerrainParam,
+    terrain_settings: Res<TerrainSettings>,
+    material_properties: Res<MaterialProperties>,
+    farm_plot_query: Query<(Entity, &FarmPlot)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let tile_pos = terrain_settings.world_to_tile(cursor_position.0);
+    let Some(area) = designation_area.drag(&mouse_button_input, &keyboard_input, tile_pos) else { return; };
+
+    match designation_area.mode {
+        DesignationMode::Add => {
+            let Some(soil) = material_properties.find(FARM_SOIL_MATERIAL) else {
+                error!("Material {} is not defined", FARM_SOIL_MATERIAL);
+                return;
+            };
+            let existing_plots: HashSet<IVec2> =
+                farm_plot_query.iter().map(|(_, plot)| plot.0).collect();
+            let mut designated = 0;
+            for tile_pos in area.tiles() {
+                let open_air = terrain.get_tile(tile_pos + IVec2::Y) == Some(0);
+                if terrain.get_tile(tile_pos) != Some(soil)
+                    || !open_air
+                    || existing_plots.contains(&tile_pos)
+                {
+                    continue;
+                }
+                spawn_farm_plot(
+                    &mut commands,
+                    tile_pos,
+                    0.,
+                    &terrain_settings,
+                    &mut materials,
+                    &mut meshes,
+                );
+                designated += 1;
+            }
+            info!(min = ?area.min, max = ?area.max, designated, "Designated farm plots");
+        }
+        DesignationMode::Remove => {
+            for (plot_entity, plot) in &farm_plot_query {
+                if area.contains(plot.0) {
+                    info!(tile_pos = ?plot.0, "Removed farm plot");
+                    commands.entity(plot_entity).despawn_recursive();
+                }
+            }
+        }
+    }
+}
+
+/// Spawns a farm plot on the soil tile `tile_pos`, with a crop grown up to `crop_growth` on top.
+pub fn spawn_farm_plot(
+    commands: &mut Commands,
+    tile_pos: IVec2,
+    crop_growth: f32,
+    terrain_settings: &TerrainSettings,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> Entity {
+    let cell_size = terrain_settings.cell_size;
+    let center = terrain_settings.tile_to_world(tile_pos);
+    // The crop stands on top of the soil tile
+    let crop = spawn_plant(
+        commands,
+        FARM_CROP,
+        crop_growth,
+        Vec3::new(
+            0.,
+            (cell_size + FARM_CROP.size().y) / 2.,
+            PLANT_LAYER_Z - FARM_PLOT_LAYER_Z,
+        ),
+        materials,
+        meshes,
+    );
+    commands
+        .spawn((
+            Name::new("Farm plot"),
+            FarmPlot(tile_pos),
+            MaterialMesh2dBundle {
+                transform: Transform::from_translation(center.extend(FARM_PLOT_LAYER_Z)),
+                material: materials.add(FARM_PLOT_COLOR.into()),
+                mesh: meshes
+                    .add(Mesh::from(shape::Quad::new(Vec2::splat(cell_size))))
+                    .into(),
+                ..default()
+            },
+        ))
+        .add_child(crop)
+        .id()
+}
+
+/// Removes farm plots whose soil was dug out or that got covered up.
+fn remove_lost_farm_plots(
+    mut commands: Commands,
+    mut destroyed_tiles: EventReader<TileDestroyedEvent>,
+    mut placed_tiles: EventReader<TilePlacedEvent>,
+    terrain_settings: Res<TerrainSettings>,
+    chunk_query: Query<&TerrainChunk>,
+    farm_plot_query: Query<(Entity, &FarmPlot)>,
+) {
+    let lost_tiles: HashSet<IVec2> = destroyed_tiles
+        .iter()
+        .map(|destroyed_tile| (destroyed_tile.chunk, destroyed_tile.tile_pos, 0))
+        .chain(
+            placed_tiles
+                .iter()
+                .map(|placed_tile| (placed_tile.chunk, placed_tile.tile_pos, -1)),
+        )
+        .filter_map(|(chunk_entity, chunk_tile_pos, offset)| {
+            let chunk = chunk_query.get(chunk_entity).ok()?;
+            let tile_pos = terrain_settings.chunk_to_tile(chunk.0, chunk_tile_pos);
+            // Tiles placed above a plot cover it up
+            Some(tile_pos + IVec2::new(0, offset))
+        })
+        .collect();
+    if lost_tiles.is_empty() {
+        return;
+    }
+
+    for (plot_entity, plot) in &farm_plot_query {
+        if lost_tiles.contains(&plot.0) {
+            info!(tile_pos = ?plot.0, "Farm plot lost");
+            commands.entity(plot_entity).despawn_recursive();
+        }
+    }
+}
+
+/// Requests a harvest of every ripe plant, wild or farmed, that doesn't have one yet.
+fn request_harvests(
+    mut commands: Commands,
+    ripe_plant_query: Query<(Entity, &GlobalTransform), (With<Plant>, With<Ripe>)>,
+    harvest_job_query: Query<&HarvestJob, With<Job>>,
+) {
+    let requested: HashSet<Entity> = harvest_job_query.iter().map(|job| job.0).collect();
+    for (plant_entity, transform) in &ripe_plant_query {
+        if requested.contains(&plant_entity) {
+            continue;
+        }
+        let job_entity =
+            spawn_harvest_job(&mut commands, plant_entity, transform.translation().xy());
+        info!(job = ?job_entity, plant = ?plant_entity, "Requested harvest");
+    }
+}
+
+pub fn spawn_harvest_job(
+    commands: &mut Commands,
+    plant_entity: Entity,
+    plant_translation: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            Job,
+            HarvestJob(plant_entity),
+            Labor::Farming,
+            ActionArea(vec![plant_translation]),
+        ))
+        .id()
+}
+
+fn cancel_harvest_jobs(
+    mut job_manager_params: JobManagerParams,
+    job_query: Query<(Entity, &HarvestJob, Option<&AssignedWorker>), With<Job>>,
+    plant_query: Query<(), With<Plant>>,
+) {
+    for (job_entity, harvest_job, opt_worker) in &job_query {
+        if !plant_query.contains(harvest_job.0) {
+            info!(job = ?job_entity, plant = ?harvest_job.0, "Cancelling harvest job because plant does not exist");
+            job_manager_params.cancel_job_and_assignment(job_entity, opt_worker);
+        }
+    }
+}
+
+fn despawn_finished_harvest_jobs(
+    mut commands: Commands,
+    finished_job_query: Query<
+        Entity,
+        (
+            With<HarvestJob>,
+            // Canceled jobs stay around until their worker noticed
+            Or<(
+                With<CompletedJob>,
+                (With<CanceledJob>, Without<AssignedWorker>),
+            )>,
+        ),
+    >,
+) {
+    for job_entity in &finished_job_query {
+        commands.entity(job_entity).despawn_recursive();
+    }
+}
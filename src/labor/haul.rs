@@ -1,6 +1,16 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::system::{lifetimeless::SQuery, SystemParamItem},
+    math::Vec3Swizzles,
+    prelude::*,
+};
 
-use crate::labor::job::all_workers_eligible;
+use crate::{
+    actions::{
+        action_area::{ActionArea, HasActionArea, HasActionPosition},
+        pickup::PickupTarget,
+    },
+    labor::job::{AssignedWorker, CompletedJob, Job},
+};
 
 use super::job::JobAssignmentSet;
 
@@ -10,17 +20,18 @@ impl Plugin for HaulPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (all_workers_eligible::<HaulRequest>).before(JobAssignmentSet),
+            (cancel_lost_hauls, despawn_completed_hauls).before(JobAssignmentSet),
         );
     }
 }
 
+#[derive(Debug)]
 pub enum HaulItem {
     Entity(Entity),
     ObjectType(Name),
 }
 
-#[derive(Component)]
+#[derive(Component, Debug)]
 pub struct HaulRequest {
     pub load: HaulItem,
     pub to: Entity,
@@ -41,3 +52,51 @@ impl HaulRequest {
         }
     }
 }
+
+impl HasActionArea for HaulRequest {
+    fn action_area() -> ActionArea {
+        PickupTarget::action_area()
+    }
+}
+
+impl HasActionPosition for HaulRequest {
+    type PositionParam = SQuery<&'static GlobalTransform>;
+
+    fn action_pos(
+        &self,
+        global_transform_query: &SystemParamItem<Self::PositionParam>,
+    ) -> Option<Vec2> {
+        // Only hauls of a specific item have a place to start from
+        match self.load {
+            HaulItem::Entity(load) => global_transform_query
+                .get(load)
+                .map(|transform| transform.translation().xy())
+                .ok(),
+            HaulItem::ObjectType(_) => None,
+        }
+    }
+}
+
+/// Despawns hauls nobody picked up yet whose item is gone, so their target requests a new one.
+fn cancel_lost_hauls(
+    mut commands: Commands,
+    haul_query: Query<(Entity, &HaulRequest), (With<Job>, Without<AssignedWorker>)>,
+    entity_query: Query<Entity>,
+) {
+    for (job_entity, haul_request) in &haul_query {
+        let HaulItem::Entity(load) = haul_request.load else { continue; };
+        if !entity_query.contains(load) {
+            info!(job = ?job_entity, "Haul item no longer exists, canceling haul");
+            commands.entity(job_entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_completed_hauls(
+    mut commands: Commands,
+    completed_haul_query: Query<Entity, (With<HaulRequest>, With<CompletedJob>)>,
+) {
+    for job_entity in &completed_haul_query {
+        commands.entity(job_entity).despawn_recursive();
+    }
+}
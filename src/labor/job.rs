@@ -1,16 +1,20 @@
 use bevy::{
     ecs::system::SystemParam,
     prelude::{
-        Added, App, Commands, Component, Entity, Event, EventWriter, Plugin, Query, SystemSet,
-        Vec2, With, Without,
+        Added, App, Changed, Commands, Component, Entity, Event, EventReader, EventWriter,
+        IntoSystemConfigs, Or, Plugin, Query, Res, Resource, SystemSet, Update, With, Without,
     },
     reflect::Reflect,
-    time::Timer,
+    time::{Time, Timer},
     utils::{HashMap, HashSet},
 };
-use tracing::info;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
 
-use crate::actions::action_area::ActionArea;
+use crate::{
+    actions::action_area::ActionArea,
+    equipment::{has_tool_for, Equipment},
+};
 
 pub struct JobPlugin;
 
@@ -18,13 +22,33 @@ impl Plugin for JobPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<JobAssignedEvent>()
             .add_event::<JobCompletedEvent>()
+            .add_event::<JobUnassignableEvent>()
             .register_type::<AssignedJob>()
             .register_type::<AssignedWorker>()
             .register_type::<BlacklistedWorkers>()
             .register_type::<EligibleWorkers>()
+            .register_type::<Labor>()
+            .register_type::<EnabledLabors>()
+            .register_type::<JobPriority>()
             .register_type::<ActionArea>()
             .register_type::<JobAssignedEvent>()
-            .register_type::<JobCompletedEvent>();
+            .register_type::<JobCompletedEvent>()
+            .register_type::<JobUnassignableEvent>()
+            .register_type::<Prerequisites>()
+            .init_resource::<LaborPriorities>()
+            .init_resource::<DesignationPriority>()
+            .add_systems(
+                Update,
+                (
+                    resolve_prerequisites,
+                    cancel_dependents,
+                    update_eligible_workers,
+                    expire_blacklisted_workers,
+                    flag_unassignable_jobs,
+                )
+                    .chain()
+                    .before(JobAssignmentSet),
+            );
     }
 }
 
@@ -45,9 +69,17 @@ pub struct AssignedJob(pub Entity);
 #[derive(Component, Debug, Reflect)]
 pub struct AssignedWorker(pub Entity);
 
+/// Workers that can't take the job until their timer runs out, e.g. because they got stuck on
+/// the way to it.
 #[derive(Component, Debug, Reflect)]
 pub struct BlacklistedWorkers(pub HashMap<Entity, Timer>);
 
+impl BlacklistedWorkers {
+    pub fn contains(&self, worker: Entity) -> bool {
+        self.0.contains_key(&worker)
+    }
+}
+
 #[derive(Event, Debug, Reflect)]
 pub struct JobAssignedEvent {
     pub job: Entity,
@@ -56,10 +88,33 @@ pub struct JobAssignedEvent {
 
 #[derive(Event, Debug, Reflect)]
 pub struct JobCompletedEvent {
-    job_entity: Entity,
-    worker_entity: Entity,
+    pub job_entity: Entity,
+    pub worker_entity: Entity,
 }
 
+/// Sent when no worker is left that can take an unassigned job, because none is eligible or all
+/// eligible ones are blacklisted.
+#[derive(Event, Debug, Reflect)]
+pub struct JobUnassignableEvent {
+    pub job: Entity,
+}
+
+/// An unassigned job no worker can take, see [`JobUnassignableEvent`].
+#[derive(Component, Debug)]
+pub struct Unassignable;
+
+/// Jobs that have to be completed before this job can be taken, or before this entity, e.g. a
+/// construction site, requests its own jobs. Removed once all of them are completed.
+///
+/// When one of them is canceled, a dependent job is canceled as well and any other dependent
+/// gets [`PrerequisiteCanceled`].
+#[derive(Component, Debug, Default, Reflect)]
+pub struct Prerequisites(pub HashSet<Entity>);
+
+/// A dependent that isn't a job whose [`Prerequisites`] can't be completed anymore.
+#[derive(Component, Debug)]
+pub struct PrerequisiteCanceled;
+
 #[derive(Component)]
 pub struct CompletedJob;
 
@@ -69,6 +124,114 @@ pub struct CanceledJob;
 #[derive(Component, Debug, Reflect)]
 pub struct EligibleWorkers(pub HashSet<Entity>);
 
+/// Kind of work a job is. Workers only take jobs of the labors they have enabled, jobs without a
+/// labor can be taken by any worker.
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Labor {
+    Mining,
+    Woodcutting,
+    Hauling,
+    Construction,
+    Farming,
+    Crafting,
+}
+
+impl Labor {
+    pub const ALL: [Labor; 6] = [
+        Labor::Mining,
+        Labor::Woodcutting,
+        Labor::Hauling,
+        Labor::Construction,
+        Labor::Farming,
+        Labor::Crafting,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Labor::Mining => "Mining",
+            Labor::Woodcutting => "Woodcutting",
+            Labor::Hauling => "Hauling",
+            Labor::Construction => "Construction",
+            Labor::Farming => "Farming",
+            Labor::Crafting => "Crafting",
+        }
+    }
+}
+
+/// Labors a worker does, all of them by default.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct EnabledLabors(pub HashSet<Labor>);
+
+impl Default for EnabledLabors {
+    fn default() -> Self {
+        Self(HashSet::from_iter(Labor::ALL))
+    }
+}
+
+impl EnabledLabors {
+    pub fn is_enabled(&self, labor: Labor) -> bool {
+        self.0.contains(&labor)
+    }
+
+    pub fn set(&mut self, labor: Labor, enabled: bool) {
+        if enabled {
+            self.0.insert(labor);
+        } else {
+            self.0.remove(&labor);
+        }
+    }
+}
+
+pub const MIN_JOB_PRIORITY: u32 = 1;
+pub const MAX_JOB_PRIORITY: u32 = 5;
+pub const DEFAULT_JOB_PRIORITY: u32 = 3;
+
+/// Priority set on a job when it was designated, overrides the priority of its labor. Higher
+/// priority jobs are picked first.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct JobPriority(pub u32);
+
+/// Priority given to newly designated jobs, `None` leaves it to the priority of their labor.
+#[derive(Resource, Debug, Default)]
+pub struct DesignationPriority(pub Option<u32>);
+
+/// Priority of the jobs of each labor that don't have a [`JobPriority`] of their own.
+#[derive(Resource, Debug)]
+pub struct LaborPriorities(pub HashMap<Labor, u32>);
+
+impl Default for LaborPriorities {
+    fn default() -> Self {
+        Self(
+            Labor::ALL
+                .into_iter()
+                .map(|labor| (labor, DEFAULT_JOB_PRIORITY))
+                .collect(),
+        )
+    }
+}
+
+impl LaborPriorities {
+    pub fn get(&self, labor: Labor) -> u32 {
+        self.0.get(&labor).copied().unwrap_or(DEFAULT_JOB_PRIORITY)
+    }
+}
+
+#[derive(SystemParam)]
+pub struct JobPriorityParam<'w, 's> {
+    job_query: Query<'w, 's, (Option<&'static JobPriority>, Option<&'static Labor>)>,
+    labor_priorities: Res<'w, LaborPriorities>,
+}
+
+impl JobPriorityParam<'_, '_> {
+    pub fn priority(&self, job: Entity) -> u32 {
+        match self.job_query.get(job) {
+            Ok((Some(job_priority), _)) => job_priority.0,
+            Ok((None, Some(labor))) => self.labor_priorities.get(*labor),
+            _ => DEFAULT_JOB_PRIORITY,
+        }
+    }
+}
+
 #[derive(SystemParam)]
 pub struct JobManagerParams<'w, 's> {
     commands: Commands<'w, 's>,
@@ -117,18 +280,164 @@ impl JobManagerParams<'_, '_> {
             .remove::<Job>()
             .insert(CanceledJob);
     }
+
+    /// Cancels the job and takes it away from the worker doing it, if any.
+    pub fn cancel_job_and_assignment(
+        &mut self,
+        job_entity: Entity,
+        opt_worker: Option<&AssignedWorker>,
+    ) {
+        if let Some(worker) = opt_worker {
+            self.cancel_job_assignment(job_entity, worker.0);
+        }
+        self.cancel_job(job_entity);
+    }
+}
+
+/// Workers that have the job's labor enabled and the tool it needs, if any.
+fn eligible_workers(
+    opt_labor: Option<&Labor>,
+    worker_query: &Query<(Entity, Option<&EnabledLabors>, Option<&Equipment>), With<Worker>>,
+) -> EligibleWorkers {
+    EligibleWorkers(
+        worker_query
+            .iter()
+            .filter(|(_, opt_enabled_labors, opt_equipment)| {
+                let Some(labor) = opt_labor else { return true; };
+                opt_enabled_labors.map_or(true, |enabled_labors| enabled_labors.is_enabled(*labor))
+                    && has_tool_for(*opt_equipment, *labor)
+            })
+            .map(|(worker, _, _)| worker)
+            .collect(),
+    )
 }
 
-pub fn all_workers_eligible<JobType>(
+/// Sets the workers eligible for new jobs once they have no prerequisites left, and updates them for all jobs when workers are added
+/// or their labors or equipment change.
+fn update_eligible_workers(
     mut commands: Commands,
-    new_job_query: Query<Entity, (With<JobType>, Without<EligibleWorkers>, Added<Job>)>,
-    worker_query: Query<Entity, With<Worker>>,
-) where
-    JobType: Component,
-{
-    for job in &new_job_query {
+    new_job_query: Query<
+        (Entity, Option<&Labor>),
+        (With<Job>, Without<EligibleWorkers>, Without<Prerequisites>),
+    >,
+    mut job_query: Query<(Option<&Labor>, &mut EligibleWorkers), With<Job>>,
+    worker_query: Query<(Entity, Option<&EnabledLabors>, Option<&Equipment>), With<Worker>>,
+    changed_worker_query: Query<
+        (),
+        (
+            With<Worker>,
+            Or<(Added<Worker>, Changed<EnabledLabors>, Changed<Equipment>)>,
+        ),
+    >,
+) {
+    for (job, opt_labor) in &new_job_query {
         commands
             .entity(job)
-            .insert(EligibleWorkers(HashSet::from_iter(worker_query.iter())));
+            .insert(eligible_workers(opt_labor, &worker_query));
+    }
+
+    if changed_worker_query.is_empty() {
+        return;
+    }
+    for (opt_labor, mut eligible) in &mut job_query {
+        *eligible = eligible_workers(opt_labor, &worker_query);
+    }
+}
+
+fn expire_blacklisted_workers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut blacklisted_workers_query: Query<(Entity, &mut BlacklistedWorkers)>,
+) {
+    for (job, mut blacklisted_workers) in &mut blacklisted_workers_query {
+        blacklisted_workers
+            .0
+            .retain(|_, timer| !timer.tick(time.delta()).finished());
+        if blacklisted_workers.0.is_empty() {
+            commands.entity(job).remove::<BlacklistedWorkers>();
+        }
+    }
+}
+
+/// Flags unassigned jobs that none of their eligible workers can take, and unflags them once one
+/// can again.
+fn flag_unassignable_jobs(
+    mut commands: Commands,
+    job_query: Query<
+        (
+            Entity,
+            &EligibleWorkers,
+            Option<&BlacklistedWorkers>,
+            Option<&AssignedWorker>,
+            Option<&Unassignable>,
+        ),
+        With<Job>,
+    >,
+    mut job_unassignable_events: EventWriter<JobUnassignableEvent>,
+) {
+    for (job, eligible_workers, opt_blacklisted_workers, opt_assigned_worker, opt_unassignable) in
+        &job_query
+    {
+        let assignable = opt_assigned_worker.is_some()
+            || eligible_workers.0.iter().any(|worker| {
+                opt_blacklisted_workers.map_or(true, |blacklisted_workers| {
+                    !blacklisted_workers.contains(*worker)
+                })
+            });
+        match (assignable, opt_unassignable.is_some()) {
+            (false, false) => {
+                warn!(?job, "No worker can take job");
+                commands.entity(job).insert(Unassignable);
+                job_unassignable_events.send(JobUnassignableEvent { job });
+            }
+            (true, true) => {
+                info!(?job, "Job can be taken again");
+                commands.entity(job).remove::<Unassignable>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resolve_prerequisites(
+    mut commands: Commands,
+    mut job_completed_events: EventReader<JobCompletedEvent>,
+    mut dependent_query: Query<(Entity, &mut Prerequisites)>,
+) {
+    for event in job_completed_events.iter() {
+        for (dependent, mut prerequisites) in &mut dependent_query {
+            if prerequisites.0.remove(&event.job_entity) && prerequisites.0.is_empty() {
+                info!(?dependent, "All prerequisites completed");
+                commands.entity(dependent).remove::<Prerequisites>();
+            }
+        }
+    }
+}
+
+/// Cancels the dependents of canceled jobs, and of jobs that are gone without being completed.
+fn cancel_dependents(
+    mut commands: Commands,
+    dependent_query: Query<
+        (Entity, &Prerequisites, Option<&Job>),
+        (Without<CanceledJob>, Without<PrerequisiteCanceled>),
+    >,
+    prerequisite_query: Query<Option<&CanceledJob>>,
+    mut job_manager_params: JobManagerParams,
+) {
+    for (dependent, prerequisites, opt_job) in &dependent_query {
+        let canceled = prerequisites.0.iter().any(|prerequisite| {
+            prerequisite_query
+                .get(*prerequisite)
+                .map_or(true, |opt_canceled| opt_canceled.is_some())
+        });
+        if !canceled {
+            continue;
+        }
+        info!(?dependent, "Prerequisite canceled");
+        if opt_job.is_some() {
+            job_manager_params.cancel_job(dependent);
+        } else {
+            commands.entity(dependent).insert(PrerequisiteCanceled);
+        }
     }
 }
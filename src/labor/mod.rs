@@ -1,18 +1,26 @@
 use bevy::prelude::*;
 
 use build_structure::BuildStructurePlugin;
+use cancel::CancelPlugin;
 use chop_tree::ChopTreePlugin;
+use craft::CraftPlugin;
 use dig_tile::DigPlugin;
+use farm::FarmPlugin;
 use haul::HaulPlugin;
+use stockpile::StockpilePlugin;
 use stuck::StuckPlugin;
 
 use job::JobPlugin;
 
 pub mod build_structure;
+pub mod cancel;
 pub mod chop_tree;
+pub mod craft;
 pub mod dig_tile;
+pub mod farm;
 pub mod haul;
 pub mod job;
+pub mod stockpile;
 pub mod stuck;
 
 pub struct LaborPlugin;
@@ -26,6 +34,10 @@ impl Plugin for LaborPlugin {
             BuildStructurePlugin,
             ChopTreePlugin,
             HaulPlugin,
+            StockpilePlugin,
+            CancelPlugin,
+            FarmPlugin,
+            CraftPlugin,
         ));
     }
 }
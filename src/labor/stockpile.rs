@@ -0,0 +1,402 @@
+use bevy::{
+    math::Vec3Swizzles,
+    prelude::*,
+    sprite::MaterialMesh2dBundle,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    actions::action_area::ActionArea,
+    building_material::Reserved,
+    cursor_position::LastCursorPosition,
+    designation_layer::{clear_designation_area, DesignationArea, DesignationMode, TileArea},
+    item::Item,
+    labor::job::{AssignedWorker, Job, JobAssignmentSet, JobManagerParams, Labor},
+    terrain::{TerrainParam, TerrainSet},
+    terrain_settings::TerrainSettings,
+};
+
+use super::haul::{HaulItem, HaulRequest};
+
+pub struct StockpilePlugin;
+
+impl Plugin for StockpilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<StockpileToolState>()
+            .register_type::<Stockpile>()
+            .register_type::<Stockpiled>()
+            .register_type::<StockpileToolState>()
+            .init_resource::<DesignationArea>()
+            .init_resource::<NewStockpileSettings>()
+            .add_systems(
+                OnExit(StockpileToolState::Designating),
+                clear_designation_area,
+            )
+            .add_systems(
+                Update,
+                designate_stockpile
+                    .run_if(state_exists_and_equals(StockpileToolState::Designating))
+                    .run_if(resource_exists::<TerrainSettings>())
+                    .before(JobAssignmentSet),
+            )
+            .add_systems(
+                Update,
+                (
+                    mark_stockpiled_items,
+                    apply_deferred,
+                    request_stockpile_hauls,
+                )
+                    .chain()
+                    .run_if(stockpile_contents_changed)
+                    .after(TerrainSet)
+                    .before(JobAssignmentSet),
+            );
+    }
+}
+
+/// A zone of tiles, from `min` to `max` inclusive, that loose items are hauled to.
+///
+/// Every tile holds a single stack of items. Items are only stored when `allowed_items` is `None`
+/// or contains their name, loose items go to the stockpile with the highest `priority` first.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct Stockpile {
+    pub min: IVec2,
+    pub max: IVec2,
+    pub allowed_items: Option<Vec<Name>>,
+    pub priority: u32,
+}
+
+impl Stockpile {
+    pub fn new(corner: IVec2, opposite_corner: IVec2) -> Self {
+        Self {
+            min: corner.min(opposite_corner),
+            max: corner.max(opposite_corner),
+            allowed_items: None,
+            priority: DEFAULT_STOCKPILE_PRIORITY,
+        }
+    }
+
+    pub fn contains(&self, tile_pos: IVec2) -> bool {
+        tile_pos.cmpge(self.min).all() && tile_pos.cmple(self.max).all()
+    }
+
+    pub fn overlaps(&self, area: &TileArea) -> bool {
+        self.min.cmple(area.max).all() && self.max.cmpge(area.min).all()
+    }
+
+    pub fn accepts(&self, item: &Name) -> bool {
+        self.allowed_items
+            .as_ref()
+            .map_or(true, |allowed_items| allowed_items.contains(item))
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = IVec2> + '_ {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| IVec2::new(x, y)))
+    }
+
+    pub fn size(&self) -> IVec2 {
+        self.max - self.min + IVec2::ONE
+    }
+}
+
+pub const MIN_STOCKPILE_PRIORITY: u32 = 1;
+pub const MAX_STOCKPILE_PRIORITY: u32 = 5;
+pub const DEFAULT_STOCKPILE_PRIORITY: u32 = 1;
+pub const STOCKPILE_LAYER_Z: f32 = 0.5;
+const STOCKPILE_COLOR: Color = Color::rgba(0.2, 0.4, 1., 0.2);
+
+/// An item lying on a tile of the stockpile `0`.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct Stockpiled(pub Entity);
+
+#[derive(States, Default, Reflect, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum StockpileToolState {
+    #[default]
+    Inactive,
+    Designating,
+}
+
+/// Allowed items and priority of the stockpiles designated next, see [`Stockpile`].
+#[derive(Resource, Debug, Clone)]
+pub struct NewStockpileSettings {
+    pub allowed_items: Option<Vec<Name>>,
+    pub priority: u32,
+}
+
+impl Default for NewStockpileSettings {
+    fn default() -> Self {
+        Self {
+            allowed_items: None,
+            priority: DEFAULT_STOCKPILE_PRIORITY,
+        }
+    }
+}
+
+/// Turns the dragged out area into a stockpile with the [`NewStockpileSettings`], or removes the
+/// stockpiles overlapping it when dragged in [`DesignationMode::Remove`].
+///
+/// Hauls to removed stockpiles are given up and their items freed, items on their way there are
+/// dropped where the hauler stands.
+fn designate_stockpile(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    cursor_position: Res<LastCursorPosition>,
+    mut designation_area: ResMut<DesignationArea>,
+    new_stockpile_settings: Res<NewStockpileSettings>,
+    terrain_settings: Res<TerrainSettings>,
+    stockpile_query: Query<(Entity, &Stockpile, Option<&Children>)>,
+    haul_job_query: Query<(&HaulRequest, Option<&AssignedWorker>)>,
+    reserved_query: Query<(), With<Reserved>>,
+    mut job_manager_params: JobManagerParams,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let tile_pos = terrain_settings.world_to_tile(cursor_position.0);
+    let Some(area) = designation_area.drag(&mouse_button_input, &keyboard_input, tile_pos) else { return; };
+
+    match designation_area.mode {
+        DesignationMode::Add => {
+            let stockpile = Stockpile {
+                allowed_items: new_stockpile_settings.allowed_items.clone(),
+                priority: new_stockpile_settings.priority,
+                ..Stockpile::new(area.min, area.max)
+            };
+            info!(min = ?stockpile.min, max = ?stockpile.max, "Designated stockpile");
+            spawn_stockpile(
+                &mut commands,
+                stockpile,
+                &terrain_settings,
+                &mut materials,
+                &mut meshes,
+            );
+        }
+        DesignationMode::Remove => {
+            for (stockpile_entity, stockpile, opt_children) in &stockpile_query {
+                if !stockpile.overlaps(&area) {
+                    continue;
+                }
+                for &job_entity in opt_children.into_iter().flatten() {
+                    let Ok((haul_request, opt_worker)) = haul_job_query.get(job_entity) else { continue; };
+                    if let Some(worker) = opt_worker {
+                        job_manager_params.cancel_job_assignment(job_entity, worker.0);
+                    }
+                    if let HaulItem::Entity(load) = haul_request.load {
+                        if reserved_query.contains(load) {
+                            commands.entity(load).remove::<Reserved>();
+                        }
+                    }
+                }
+                info!(min = ?stockpile.min, max = ?stockpile.max, "Removed stockpile");
+                commands.entity(stockpile_entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+pub fn spawn_stockpile(
+    commands: &mut Commands,
+    stockpile: Stockpile,
+    terrain_settings: &TerrainSettings,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> Entity {
+    let center = (terrain_settings.tile_to_world(stockpile.min)
+        + terrain_settings.tile_to_world(stockpile.max))
+        / 2.;
+    let size = stockpile.size().as_vec2() * terrain_settings.cell_size;
+    commands
+        .spawn((
+            Name::new("Stockpile"),
+            stockpile,
+            MaterialMesh2dBundle {
+                transform: Transform::from_translation(center.extend(STOCKPILE_LAYER_Z)),
+                material: materials.add(STOCKPILE_COLOR.into()),
+                mesh: meshes.add(Mesh::from(shape::Quad::new(size))).into(),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+/// Whether items or stockpiles changed since the last frame, only then can items need to be
+/// marked or hauled.
+fn stockpile_contents_changed(
+    changed_stockpile_query: Query<(), Changed<Stockpile>>,
+    mut removed_stockpiles: RemovedComponents<Stockpile>,
+    changed_item_query: Query<(), (With<Item>, Changed<GlobalTransform>)>,
+    mut removed_stockpiled: RemovedComponents<Stockpiled>,
+    mut removed_reserved: RemovedComponents<Reserved>,
+) -> bool {
+    let removed = removed_stockpiles.iter().count()
+        + removed_stockpiled.iter().count()
+        + removed_reserved.iter().count();
+    removed > 0 || !changed_stockpile_query.is_empty() || !changed_item_query.is_empty()
+}
+
+/// Keeps the [`Stockpiled`] marker of items in sync with the stockpile they lie in.
+///
+/// Only items that moved are checked, unless stockpiles changed.
+fn mark_stockpiled_items(
+    mut commands: Commands,
+    terrain: TerrainParam,
+    stockpile_query: Query<(Entity, &Stockpile)>,
+    changed_stockpile_query: Query<(), Changed<Stockpile>>,
+    mut removed_stockpiles: RemovedComponents<Stockpile>,
+    item_query: Query<(Entity, &GlobalTransform, Option<&Stockpiled>), With<Item>>,
+    changed_item_query: Query<
+        (Entity, &GlobalTransform, Option<&Stockpiled>),
+        (With<Item>, Changed<GlobalTransform>),
+    >,
+) {
+    let stockpiles_changed =
+        !changed_stockpile_query.is_empty() || removed_stockpiles.iter().count() > 0;
+    let items = if stockpiles_changed {
+        item_query.iter().collect::<Vec<_>>()
+    } else {
+        changed_item_query.iter().collect()
+    };
+
+    for (item_entity, transform, opt_stockpiled) in items {
+        let stockpile_entity = terrain
+            .global_to_tile_pos(transform.translation().xy())
+            .and_then(|tile_pos| {
+                stockpile_query
+                    .iter()
+                    .find(|(_, stockpile)| stockpile.contains(tile_pos))
+            })
+            .map(|(stockpile_entity, _)| stockpile_entity);
+
+        match (stockpile_entity, opt_stockpiled) {
+            (Some(stockpile_entity), Some(stockpiled)) if stockpiled.0 == stockpile_entity => {}
+            (Some(stockpile_entity), _) => {
+                commands
+                    .entity(item_entity)
+                    .insert(Stockpiled(stockpile_entity));
+            }
+            (None, Some(_)) => {
+                commands.entity(item_entity).remove::<Stockpiled>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Tiles holding an item, by the stockpile the item lies in.
+fn occupied_stockpile_tiles(
+    terrain: &TerrainParam,
+    stockpiled_query: &Query<(&GlobalTransform, &Stockpiled)>,
+) -> HashMap<Entity, HashSet<IVec2>> {
+    let mut occupied_tiles: HashMap<Entity, HashSet<IVec2>> = HashMap::new();
+    for (transform, stockpiled) in stockpiled_query {
+        if let Some(tile_pos) = terrain.global_to_tile_pos(transform.translation().xy()) {
+            occupied_tiles
+                .entry(stockpiled.0)
+                .or_default()
+                .insert(tile_pos);
+        }
+    }
+    occupied_tiles
+}
+
+/// Tiles of `stockpile` that don't hold an item yet.
+pub fn free_stockpile_tiles(
+    stockpile_entity: Entity,
+    stockpile: &Stockpile,
+    terrain: &TerrainParam,
+    stockpiled_query: &Query<(&GlobalTransform, &Stockpiled)>,
+) -> Vec<IVec2> {
+    let occupied_tiles: HashSet<IVec2> = stockpiled_query
+        .iter()
+        .filter(|(_, stockpiled)| stockpiled.0 == stockpile_entity)
+        .filter_map(|(transform, _)| terrain.global_to_tile_pos(transform.translation().xy()))
+        .collect();
+    stockpile
+        .tiles()
+        .filter(|tile_pos| !occupied_tiles.contains(tile_pos))
+        .collect()
+}
+
+/// Requests loose items to be hauled to the stockpile with the highest priority that accepts them
+/// and has room left, preferring the closest one.
+fn request_stockpile_hauls(
+    mut commands: Commands,
+    terrain: TerrainParam,
+    stockpile_query: Query<(Entity, &Stockpile, &GlobalTransform)>,
+    stockpiled_query: Query<(&GlobalTransform, &Stockpiled)>,
+    loose_item_query: Query<
+        (Entity, &Name, &GlobalTransform),
+        (With<Item>, Without<Stockpiled>, Without<Reserved>),
+    >,
+    haul_request_query: Query<&HaulRequest, With<Job>>,
+) {
+    if loose_item_query.is_empty() || stockpile_query.is_empty() {
+        return;
+    }
+
+    // Room left in every stockpile, minus the items already on their way there
+    let mut pending_hauls: HashMap<Entity, usize> = HashMap::new();
+    for haul_request in &haul_request_query {
+        if let HaulItem::Entity(_) = haul_request.load {
+            *pending_hauls.entry(haul_request.to).or_default() += 1;
+        }
+    }
+    let occupied_tiles = occupied_stockpile_tiles(&terrain, &stockpiled_query);
+    let mut room_left: HashMap<Entity, usize> = stockpile_query
+        .iter()
+        .map(|(stockpile_entity, stockpile, _)| {
+            let free_tiles = stockpile
+                .tiles()
+                .filter(|tile_pos| {
+                    occupied_tiles
+                        .get(&stockpile_entity)
+                        .map_or(true, |occupied| !occupied.contains(tile_pos))
+                })
+                .count();
+            let pending = pending_hauls.get(&stockpile_entity).copied().unwrap_or(0);
+            (stockpile_entity, free_tiles.saturating_sub(pending))
+        })
+        .collect();
+
+    for (item_entity, item_name, item_transform) in &loose_item_query {
+        let item_position = item_transform.translation().xy();
+        let Some((stockpile_entity, _, _)) = stockpile_query
+            .iter()
+            .filter(|(stockpile_entity, stockpile, _)| {
+                stockpile.accepts(item_name) && room_left[stockpile_entity] > 0
+            })
+            .max_by(
+                |(_, stockpile_a, transform_a), (_, stockpile_b, transform_b)| {
+                    let distance_a = transform_a
+                        .translation()
+                        .xy()
+                        .distance_squared(item_position);
+                    let distance_b = transform_b
+                        .translation()
+                        .xy()
+                        .distance_squared(item_position);
+                    stockpile_a
+                        .priority
+                        .cmp(&stockpile_b.priority)
+                        .then(distance_b.total_cmp(&distance_a))
+                },
+            )
+        else {
+            continue;
+        };
+
+        *room_left.get_mut(&stockpile_entity).unwrap() -= 1;
+        let haul_job = commands
+            .spawn((
+                Job,
+                HaulRequest::request_entity(item_entity, stockpile_entity),
+                Labor::Hauling,
+                ActionArea(vec![item_position]),
+            ))
+            .id();
+        commands.entity(stockpile_entity).add_child(haul_job);
+        commands.entity(item_entity).insert(Reserved);
+        info!(item = ?item_entity, stockpile = ?stockpile_entity, "Requested haul to stockpile");
+    }
+}
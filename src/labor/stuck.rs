@@ -1,12 +1,15 @@
 use bevy::{
-    prelude::{App, Commands, Component, Entity, Plugin, Query, Res, Update, With},
+    prelude::{App, Commands, Component, Entity, EventReader, Plugin, Query, Res, Update, With},
     time::{Time, Timer, TimerMode},
-    utils::HashMap,
+    utils::{HashMap, HashSet},
 };
 use bevy_rapier2d::prelude::KinematicCharacterControllerOutput;
+use big_brain::{prelude::ActionState, thinker::Actor};
+use tracing::info;
 
 use crate::{
     labor::job::{AssignedJob, AssignedWorker, BlacklistedWorkers, Job, Worker},
+    path_request::{failed_path_entities, PathFailedEvent},
     pathfinding::Path,
 };
 
@@ -14,10 +17,13 @@ pub struct StuckPlugin;
 
 impl Plugin for StuckPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (stuck, stuck_timer));
+        app.add_systems(Update, (stuck, stuck_timer, job_unreachable));
     }
 }
 
+/// How long a worker that got stuck on the way to a job can't take it again.
+const BLACKLIST_SECONDS: f32 = 5.;
+
 #[derive(Component)]
 pub struct StuckTimer(Timer);
 
@@ -33,15 +39,21 @@ fn stuck(
         (
             Entity,
             Option<&StuckTimer>,
-            Option<&Path>,
             &KinematicCharacterControllerOutput,
         ),
         (With<Worker>, With<AssignedJob>),
     >,
+    following_path_query: Query<&Actor, (With<Path>, With<ActionState>)>,
 ) {
-    for (worker_entity, opt_stuck_timer, opt_path, controller_output) in &worker_query {
-        if opt_path.is_none() && opt_stuck_timer.is_some() {
-            commands.entity(worker_entity).remove::<StuckTimer>();
+    // Paths are followed by the worker's actions, not the worker itself
+    let following_path: HashSet<Entity> =
+        following_path_query.iter().map(|actor| actor.0).collect();
+
+    for (worker_entity, opt_stuck_timer, controller_output) in &worker_query {
+        if !following_path.contains(&worker_entity) {
+            if opt_stuck_timer.is_some() {
+                commands.entity(worker_entity).remove::<StuckTimer>();
+            }
             continue;
         }
 
@@ -50,9 +62,7 @@ fn stuck(
         if !standing_still && opt_stuck_timer.is_some() {
             commands.entity(worker_entity).remove::<StuckTimer>();
         } else if standing_still && opt_stuck_timer.is_none() {
-            commands
-                .entity(worker_entity)
-                .insert(StuckTimer(Timer::from_seconds(1., TimerMode::Once)));
+            commands.entity(worker_entity).insert(StuckTimer::default());
         }
     }
 }
@@ -65,21 +75,112 @@ fn stuck_timer(
 ) {
     for (worker_entity, mut stuck_timer, assigned_job) in &mut stuck_timer_query {
         if stuck_timer.0.tick(time.delta()).just_finished() {
+            info!(worker = ?worker_entity, job = ?assigned_job.0, "Worker got stuck");
             commands.entity(worker_entity).remove::<StuckTimer>();
-            commands.entity(worker_entity).remove::<AssignedJob>();
-
-            commands.entity(assigned_job.0).remove::<AssignedWorker>();
-            if let Ok(mut blacklisted_workers) = blacklisted_workers_query.get_mut(assigned_job.0) {
-                blacklisted_workers
-                    .0
-                    .insert(worker_entity, Timer::from_seconds(5., TimerMode::Once));
-            } else {
-                let map =
-                    HashMap::from([(worker_entity, Timer::from_seconds(5., TimerMode::Once))]);
-                commands
-                    .entity(assigned_job.0)
-                    .insert(BlacklistedWorkers(map));
-            }
+            blacklist_worker(
+                &mut commands,
+                &mut blacklisted_workers_query,
+                assigned_job.0,
+                worker_entity,
+            );
         }
     }
 }
+
+/// Blacklists workers whose actions can't find a path for their assigned job.
+fn job_unreachable(
+    mut commands: Commands,
+    mut path_failed_events: EventReader<PathFailedEvent>,
+    action_query: Query<&Actor, With<ActionState>>,
+    assigned_job_query: Query<&AssignedJob, With<Worker>>,
+    mut blacklisted_workers_query: Query<&mut BlacklistedWorkers, With<Job>>,
+) {
+    let mut unreachable = HashSet::new();
+    for action_entity in failed_path_entities(&mut path_failed_events) {
+        let Ok(actor) = action_query.get(action_entity) else { continue; };
+        let Ok(assigned_job) = assigned_job_query.get(actor.0) else { continue; };
+        unreachable.insert((assigned_job.0, actor.0));
+    }
+
+    for (job_entity, worker_entity) in unreachable {
+        info!(worker = ?worker_entity, job = ?job_entity, "Worker can't reach job");
+        commands.entity(worker_entity).remove::<StuckTimer>();
+        blacklist_worker(
+            &mut commands,
+            &mut blacklisted_workers_query,
+            job_entity,
+            worker_entity,
+        );
+    }
+}
+
+/// Takes the job away from the worker and keeps the worker from taking it again for a while.
+fn blacklist_worker(
+    commands: &mut Commands,
+    blacklisted_workers_query: &mut Query<&mut BlacklistedWorkers, With<Job>>,
+    job_entity: Entity,
+    worker_entity: Entity,
+) {
+    commands.entity(worker_entity).remove::<AssignedJob>();
+    commands.entity(job_entity).remove::<AssignedWorker>();
+
+    let timer = Timer::from_seconds(BLACKLIST_SECONDS, TimerMode::Once);
+    if let Ok(mut blacklisted_workers) = blacklisted_workers_query.get_mut(job_entity) {
+        blacklisted_workers.0.insert(worker_entity, timer);
+    } else {
+        commands
+            .entity(job_entity)
+            .insert(BlacklistedWorkers(HashMap::from([(worker_entity, timer)])));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy::prelude::{App, Events, Time};
+    use big_brain::{prelude::ActionState, thinker::Actor};
+
+    use super::StuckPlugin;
+    use crate::{
+        labor::job::{AssignedJob, AssignedWorker, BlacklistedWorkers, Job, JobPlugin, Worker},
+        path_request::PathFailedEvent,
+    };
+
+    #[test]
+    fn unreachable_job_blacklists_worker_until_expired() {
+        let mut app = App::new();
+        app.add_event::<PathFailedEvent>()
+            .insert_resource(Time::default())
+            .add_plugins((JobPlugin, StuckPlugin));
+
+        let start = Instant::now();
+        app.world.resource_mut::<Time>().update_with_instant(start);
+
+        let worker = app.world.spawn(Worker).id();
+        let job = app.world.spawn((Job, AssignedWorker(worker))).id();
+        app.world.entity_mut(worker).insert(AssignedJob(job));
+        let action = app
+            .world
+            .spawn((Actor(worker), ActionState::Executing))
+            .id();
+        app.world
+            .resource_mut::<Events<PathFailedEvent>>()
+            .send(PathFailedEvent { entity: action });
+        app.update();
+
+        assert!(app.world.get::<AssignedJob>(worker).is_none());
+        assert!(app.world.get::<AssignedWorker>(job).is_none());
+        assert!(app
+            .world
+            .get::<BlacklistedWorkers>(job)
+            .is_some_and(|blacklisted_workers| blacklisted_workers.contains(worker)));
+
+        app.world
+            .resource_mut::<Time>()
+            .update_with_instant(start + Duration::from_secs_f32(super::BLACKLIST_SECONDS + 1.));
+        app.update();
+
+        assert!(app.world.get::<BlacklistedWorkers>(job).is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    dwarf::Dwarf,
+    labor::job::{
+        DesignationPriority, EnabledLabors, Labor, LaborPriorities, Unassignable, MAX_JOB_PRIORITY,
+        MIN_JOB_PRIORITY,
+    },
+};
+
+pub struct LaborPanelPlugin;
+
+impl Plugin for LaborPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, labor_panel);
+    }
+}
+
+/// Priority of new designations and of every labor, which labors every dwarf does and how many
+/// jobs nobody can take.
+fn labor_panel(
+    mut contexts: EguiContexts,
+    mut designation_priority: ResMut<DesignationPriority>,
+    mut labor_priorities: ResMut<LaborPriorities>,
+    mut dwarf_query: Query<(&Name, &mut EnabledLabors), With<Dwarf>>,
+    unassignable_job_query: Query<(), With<Unassignable>>,
+) {
+    egui::Window::new("Labors").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Designation priority");
+            ui.selectable_value(&mut designation_priority.0, None, "Labor");
+            for priority in MIN_JOB_PRIORITY..=MAX_JOB_PRIORITY {
+                ui.selectable_value(
+                    &mut designation_priority.0,
+                    Some(priority),
+                    priority.to_string(),
+                );
+            }
+        });
+
+        egui::Grid::new("labors").show(ui, |ui| {
+            ui.label("");
+            for labor in Labor::ALL {
+                ui.label(labor.name());
+            }
+            ui.end_row();
+
+            ui.label("Priority");
+            for labor in Labor::ALL {
+                let mut priority = labor_priorities.get(labor);
+                let drag_value = egui::DragValue::new(&mut priority)
+                    .clamp_range(MIN_JOB_PRIORITY..=MAX_JOB_PRIORITY);
+                if ui.add(drag_value).changed() {
+                    labor_priorities.0.insert(labor, priority);
+                }
+            }
+            ui.end_row();
+
+            for (name, mut enabled_labors) in &mut dwarf_query {
+                ui.label(name.as_str());
+                for labor in Labor::ALL {
+                    // Only touch the labors when toggled, changing them updates every job
+                    let mut enabled = enabled_labors.is_enabled(labor);
+                    if ui.checkbox(&mut enabled, "").changed() {
+                        enabled_labors.set(labor, enabled);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+        let unassignable_jobs = unassignable_job_query.iter().count();
+        if unassignable_jobs > 0 {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("{unassignable_jobs} jobs nobody can take"),
+            );
+        }
+    });
+}
@@ -0,0 +1,80 @@
+//! A 2D colony simulation built on Bevy.
+//!
+//! The simulation is split in two plugin groups: [`SimulationPlugins`] runs the world (terrain,
+//! pathfinding, physics, worker AI and labor) and [`ClientPlugins`] adds the interactive
+//! presentation on top of it. External crates can add their own job types with
+//! [`JobTypePlugin`](actions::work::JobTypePlugin) and the types re-exported in [`prelude`].
+
+pub mod actions;
+pub mod building_material;
+mod client;
+pub mod climbable;
+mod crafting_panel;
+mod cursor_position;
+mod debug;
+mod designation_layer;
+pub mod dwarf;
+mod dwarf_panel;
+pub mod embark;
+pub mod equipment;
+pub mod gravity;
+pub mod health;
+pub mod hit;
+mod hovered_tile;
+pub mod inventory;
+pub mod item;
+pub mod labor;
+mod labor_panel;
+mod load;
+mod main_camera;
+pub mod main_state;
+pub mod material;
+pub mod movement;
+pub mod navigation;
+pub mod needs;
+mod pan_zoom_camera2d;
+pub mod path_request;
+pub mod pathfinding;
+pub mod plant;
+pub mod save;
+mod simulation;
+pub mod skills;
+mod stockpile_panel;
+pub mod structure;
+pub mod terrain;
+pub mod terrain_settings;
+mod toolbar;
+pub mod tree;
+mod util;
+mod world_generation;
+
+pub use client::ClientPlugins;
+pub use simulation::{headless_argument, HeadlessPlugin, SimulationPlugins};
+
+pub mod prelude {
+    pub use crate::{
+        actions::{
+            action_area::{ActionArea, GlobalActionArea, HasActionArea, HasActionPosition},
+            work::{do_job, job_scorer_builder, JobTypePlugin},
+        },
+        building_material::{BuildingMaterial, BuildingMaterialLocator, Reserved},
+        inventory::{Inventory, ItemStack},
+        item::{Item, ItemDefinitions},
+        labor::job::{
+            AssignedJob, AssignedWorker, EligibleWorkers, EnabledLabors, Job, JobAssignmentSet,
+            JobManagerParams, JobPriority, JobUnassignableEvent, Labor, LaborPriorities,
+            PrerequisiteCanceled, Prerequisites, Unassignable, Worker,
+        },
+        main_state::MainState,
+        material::{Material, MaterialProperties},
+        navigation::NavigationGraph,
+        path_request::{PathFailedEvent, PathRequest},
+        pathfinding::{Path, Pathfinding},
+        structure::{PlacedStructure, StructureDefinitions},
+        terrain::{
+            ChunkLoader, PlaceTileEvent, TerrainChunk, TerrainData, TerrainParam, TerrainSet,
+            TerrainTiles, TileDamageEvent, TileDestroyedEvent, TilePlacedEvent,
+        },
+        ClientPlugins, HeadlessPlugin, SimulationPlugins,
+    };
+}
@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 
 use crate::{
-    main_state::MainState, material::MaterialsState, terrain_settings::TerrainSettingsState,
+    embark::EmbarkState, item::ItemsState, main_state::MainState, material::MaterialsState,
+    save::LoadedSave, structure::StructuresState, terrain_settings::TerrainSettingsState,
 };
 
 pub struct LoadPlugin;
@@ -13,12 +14,22 @@ impl Plugin for LoadPlugin {
             start_map_generation
                 .run_if(in_state(MainState::Loading))
                 .run_if(in_state(MaterialsState::Loaded))
-                .run_if(in_state(TerrainSettingsState::Loaded)), // .run_if(in_state(ItemsState::Loaded)),
+                .run_if(in_state(TerrainSettingsState::Loaded))
+                .run_if(in_state(ItemsState::Loaded))
+                .run_if(in_state(StructuresState::Loaded))
+                .run_if(in_state(EmbarkState::Loaded)),
         );
     }
 }
 
-fn start_map_generation(mut state: ResMut<NextState<MainState>>) {
+fn start_map_generation(
+    mut state: ResMut<NextState<MainState>>,
+    loaded_save: Option<Res<LoadedSave>>,
+) {
     info!("Loading complete");
-    state.set(MainState::MapGeneration);
+    if loaded_save.is_some() {
+        state.set(MainState::LoadGame);
+    } else {
+        state.set(MainState::MapGeneration);
+    }
 }
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 
-use crate::{main_state::MainState, pan_zoom_camera2d::PanZoomCamera2dBundle};
+use crate::{
+    main_state::MainState, pan_zoom_camera2d::PanZoomCamera2dBundle, terrain::ChunkLoader,
+};
 
 pub struct MainCameraPlugin;
 
@@ -11,8 +13,11 @@ impl Plugin for MainCameraPlugin {
 }
 
 fn spawn_camera(mut commands: Commands) {
-    commands.spawn(PanZoomCamera2dBundle {
-        camera: Camera2dBundle { ..default() },
-        ..default()
-    });
+    commands.spawn((
+        PanZoomCamera2dBundle {
+            camera: Camera2dBundle { ..default() },
+            ..default()
+        },
+        ChunkLoader,
+    ));
 }
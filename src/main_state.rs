@@ -13,5 +13,6 @@ pub enum MainState {
     #[default]
     Loading,
     MapGeneration,
+    LoadGame,
     Game,
 }
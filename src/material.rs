@@ -4,6 +4,13 @@ use bevy::{
 };
 use bevy_common_assets::ron::RonAssetPlugin;
 
+use crate::{
+    item::{spawn_item, ItemDefinitions},
+    main_state::MainState,
+    terrain::{TerrainChunk, TileDestroyedEvent},
+    terrain_settings::TerrainSettings,
+};
+
 pub struct MaterialPlugin;
 
 impl Plugin for MaterialPlugin {
@@ -15,7 +22,8 @@ impl Plugin for MaterialPlugin {
             .add_systems(
                 Update,
                 setup_materials.run_if(in_state(MaterialsState::Loading)),
-            );
+            )
+            .add_systems(Update, spawn_tile_drops.run_if(in_state(MainState::Game)));
     }
 }
 
@@ -23,6 +31,29 @@ impl Plugin for MaterialPlugin {
 pub struct Material {
     pub name: String,
     pub color: Color,
+    /// Hardness of the material: the health of its tiles.
+    #[serde(default = "default_health")]
+    pub health: u32,
+    /// Multiplies the time between two dig ticks on tiles of this material.
+    #[serde(default = "default_dig_time_multiplier")]
+    pub dig_time_multiplier: f32,
+    #[serde(default = "default_diggable")]
+    pub diggable: bool,
+    /// Name of the building material dropped when a tile of this material is destroyed.
+    #[serde(default)]
+    pub drop: Option<String>,
+}
+
+fn default_health() -> u32 {
+    100
+}
+
+fn default_dig_time_multiplier() -> f32 {
+    1.
+}
+
+fn default_diggable() -> bool {
+    true
 }
 
 #[derive(serde::Deserialize, TypeUuid, TypePath)]
@@ -40,6 +71,25 @@ fn load_materials(mut commands: Commands, asset_server: Res<AssetServer>) {
 #[derive(Resource)]
 pub struct MaterialProperties(pub Vec<Material>);
 
+impl MaterialProperties {
+    pub fn get(&self, material: u16) -> Option<&Material> {
+        self.0.get(material as usize)
+    }
+
+    pub fn diggable(&self, material: u16) -> bool {
+        self.get(material)
+            .map_or(false, |material| material.diggable)
+    }
+
+    /// Id of the material called `name`.
+    pub fn find(&self, name: &str) -> Option<u16> {
+        self.0
+            .iter()
+            .position(|material| material.name == name)
+            .map(|index| index as u16)
+    }
+}
+
 #[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum MaterialsState {
     #[default]
@@ -59,3 +109,52 @@ fn setup_materials(
         state.set(MaterialsState::Loaded);
     }
 }
+
+/// Spawns the item dropped by a destroyed tile of `material` at `position`, `None` when the
+/// material doesn't drop anything.
+pub fn spawn_material_drop(
+    commands: &mut Commands,
+    material_properties: &MaterialProperties,
+    item_definitions: &ItemDefinitions,
+    material: u16,
+    position: Vec2,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> Option<Entity> {
+    let drop_name = material_properties.get(material)?.drop.as_ref()?;
+    let Some(definition) = item_definitions.get(drop_name) else {
+        error!("Material drop {} is not a known item", drop_name);
+        return None;
+    };
+    Some(spawn_item(
+        commands, definition, 1, position, materials, meshes,
+    ))
+}
+
+fn spawn_tile_drops(
+    mut commands: Commands,
+    mut destroyed_tiles: EventReader<TileDestroyedEvent>,
+    terrain_settings: Res<TerrainSettings>,
+    material_properties: Res<MaterialProperties>,
+    item_definitions: Res<ItemDefinitions>,
+    chunk_query: Query<&TerrainChunk>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for destroyed_tile in destroyed_tiles.iter() {
+        let Ok(chunk) = chunk_query.get(destroyed_tile.chunk) else { continue; };
+        let tile_pos = terrain_settings.chunk_to_tile(chunk.0, destroyed_tile.tile_pos);
+        let position = terrain_settings.tile_to_world(tile_pos);
+        if let Some(drop_entity) = spawn_material_drop(
+            &mut commands,
+            &material_properties,
+            &item_definitions,
+            destroyed_tile.material,
+            position,
+            &mut materials,
+            &mut meshes,
+        ) {
+            info!(drop=?drop_entity, tile_pos=?tile_pos, "Spawned tile drop");
+        }
+    }
+}
@@ -29,6 +29,10 @@ pub struct Walker {
     pub move_direction: Option<Vec2>,
 }
 
+/// Multiplies how far a walker moves every frame.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WalkSpeed(pub f32);
+
 #[derive(Component, Default, Reflect)]
 pub struct Jumper {
     pub jump_timer: Option<Timer>,
@@ -45,11 +49,12 @@ fn walk(
     mut dude_query: Query<(
         &mut KinematicCharacterController,
         &mut Walker,
+        Option<&WalkSpeed>,
         Option<(&mut Jumper, &KinematicCharacterControllerOutput)>,
     )>,
     time: Res<Time>,
 ) {
-    for (mut controller, mut walker, mut maybe_jumper) in &mut dude_query {
+    for (mut controller, mut walker, opt_walk_speed, mut maybe_jumper) in &mut dude_query {
         if let Some((jumper, controller_output)) = maybe_jumper.as_mut() {
             if jumper.jump && controller_output.grounded {
                 jumper.jump_timer = Some(Timer::from_seconds(0.5, TimerMode::Once));
@@ -64,7 +69,10 @@ fn walk(
             }
         }
 
-        let move_direction = walker.move_direction.map(|dir| Vec2::new(dir.x, dir.y));
+        let speed = opt_walk_speed.map_or(1., |walk_speed| walk_speed.0);
+        let move_direction = walker
+            .move_direction
+            .map(|dir| Vec2::new(dir.x, dir.y) * speed);
         walker.move_direction = None;
 
         controller.translation = controller
@@ -85,36 +93,30 @@ fn fall(
         (Entity, &mut KinematicCharacterController, &GlobalTransform),
         With<Climber>,
     >,
-    climbable_map_query: Query<&ClimbableMap>,
+    climbable_map: Res<ClimbableMap>,
     terrain: TerrainParam,
 ) {
     for (climber_entity, mut controller, climber_transform) in &mut climber_query {
-        for climbable_map in &climbable_map_query {
-            let climber_pos = climber_transform.translation().xy();
-            let climber_tile_pos = terrain.global_to_tile_pos(climber_pos).unwrap_or_else(|| {
-                panic!(
-                    "climber outside of terrain. Climber position: {:?}",
-                    climber_pos
-                )
-            });
-
-            let shape_pos = climber_pos - Vec2::new(0., 6.);
-            let shape_rot = 0.;
-            let shape = Collider::cuboid(6., 0.2);
-            let filter = QueryFilter::default().groups(CollisionGroups::new(
-                DWARF_COLLISION_GROUP,
-                TERRAIN_COLLISION_GROUP,
-            ));
-            let is_grounded = rapier_context
-                .intersection_with_shape(shape_pos, shape_rot, &shape, filter)
-                .is_some();
-
-            if !climbable_map.is_climbable(climber_tile_pos) && !is_grounded {
-                commands.entity(climber_entity).insert(Falling);
-                controller.translation = Some(Vec2::new(0., -1.));
-            } else {
-                commands.entity(climber_entity).remove::<Falling>();
-            }
+        let climber_pos = climber_transform.translation().xy();
+        // Skip climbers in a chunk that is still loading
+        let Some(climber_tile_pos) = terrain.global_to_tile_pos(climber_pos) else { continue; };
+
+        let shape_pos = climber_pos - Vec2::new(0., 6.);
+        let shape_rot = 0.;
+        let shape = Collider::cuboid(6., 0.2);
+        let filter = QueryFilter::default().groups(CollisionGroups::new(
+            DWARF_COLLISION_GROUP,
+            TERRAIN_COLLISION_GROUP,
+        ));
+        let is_grounded = rapier_context
+            .intersection_with_shape(shape_pos, shape_rot, &shape, filter)
+            .is_some();
+
+        if !climbable_map.is_climbable(climber_tile_pos) && !is_grounded {
+            commands.entity(climber_entity).insert(Falling);
+            controller.translation = Some(Vec2::new(0., -1.));
+        } else {
+            commands.entity(climber_entity).remove::<Falling>();
         }
     }
 }
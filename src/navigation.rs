@@ -0,0 +1,694 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_ecs_tilemap::tiles::TileStorage;
+use pathfinding::directed::astar::astar;
+
+use crate::{
+    climbable::{ClimbableChangedEvent, ClimbableMap},
+    pathfinding::{can_stand_or_climb, find_path_within, successors, Path},
+    terrain::{
+        Terrain, TerrainChunk, TerrainParam, TerrainSet, TerrainTiles, TileDestroyedEvent,
+        TilePlacedEvent,
+    },
+    terrain_settings::TerrainSettings,
+};
+
+/// Width and height of a navigation cluster in tiles.
+pub const CLUSTER_SIZE: i32 = 8;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavigationGraph>().add_systems(
+            Update,
+            (
+                invalidate_chunks,
+                invalidate_destroyed_tiles,
+                invalidate_placed_tiles,
+                invalidate_climbable_tiles,
+                rebuild_navigation_graph,
+            )
+                .chain()
+                .in_set(NavigationSet)
+                .after(TerrainSet)
+                .run_if(resource_exists::<TerrainSettings>()),
+        );
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NavigationSet;
+
+/// A connected group of walkable tiles inside a single cluster.
+type Node = (IVec2, u16);
+
+#[derive(Default)]
+struct Cluster {
+    /// Component of every walkable tile in the cluster.
+    components: HashMap<IVec2, u16>,
+    /// Steps from a tile in this cluster onto a tile of another cluster.
+    exits: Vec<(IVec2, IVec2)>,
+    /// Components of neighbouring clusters each component has an exit onto.
+    links: HashMap<u16, HashSet<Node>>,
+}
+
+/// The cluster and the eight clusters around it, the only ones a single step can reach.
+fn cluster_neighbourhood(cluster: IVec2) -> impl Iterator<Item = IVec2> {
+    (-1..=1).flat_map(move |x| (-1..=1).map(move |y| cluster + IVec2::new(x, y)))
+}
+
+/// Search for a path to `target_tile_pos`, planned on the [`NavigationGraph`].
+///
+/// The search itself only needs the terrain, so it can run on a terrain snapshot off the main
+/// thread.
+#[derive(Debug, Clone)]
+pub struct PathPlan {
+    pub target_tile_pos: IVec2,
+    /// Clusters the tile search stays inside: the clusters along the component route to the
+    /// target, or every cluster between start and target when there is no route.
+    route: HashSet<IVec2>,
+}
+
+impl PathPlan {
+    pub fn search(
+        &self,
+        terrain: &impl TerrainTiles,
+        climbable_map: &ClimbableMap,
+        start_tile_pos: IVec2,
+    ) -> Option<Path> {
+        let path = find_path_within(
+            terrain,
+            Some(climbable_map),
+            start_tile_pos,
+            self.target_tile_pos,
+            |tile_pos| self.route.contains(&cluster_of(tile_pos)),
+        );
+        if path.is_some() {
+            return path;
+        }
+
+        // Components only tell which tiles are connected, not in which direction steps are
+        // possible, so the route can miss a path that leaves it for a neighbouring cluster
+        let widened_route = self
+            .route
+            .iter()
+            .flat_map(|cluster| cluster_neighbourhood(*cluster))
+            .collect::<HashSet<_>>();
+        find_path_within(
+            terrain,
+            Some(climbable_map),
+            start_tile_pos,
+            self.target_tile_pos,
+            |tile_pos| widened_route.contains(&cluster_of(tile_pos)),
+        )
+    }
+}
+
+/// Cached navigation graph on top of the terrain.
+///
+/// The world is divided into clusters of [`CLUSTER_SIZE`] tiles. Inside a cluster walkable tiles
+/// are grouped into components, components of neighbouring clusters are linked wherever a step
+/// crosses the cluster border. Components that are linked, directly or through others, share a
+/// region label: tiles with different labels can never reach each other.
+///
+/// Paths are searched on the component graph first, the tile search then only visits the
+/// clusters along that route. Clusters are rebuilt when their tiles or climbables change, only
+/// the regions of the rebuilt clusters and their neighbours are labelled again.
+#[derive(Resource, Default)]
+pub struct NavigationGraph {
+    clusters: HashMap<IVec2, Cluster>,
+    regions: HashMap<Node, u32>,
+    /// Components labelled with each region.
+    region_nodes: HashMap<u32, Vec<Node>>,
+    next_region: u32,
+    dirty: HashSet<IVec2>,
+    /// Clusters rebuilt by the latest rebuild.
+    changed: HashSet<IVec2>,
+    chunks: HashMap<Entity, IVec2>,
+    version: u64,
+}
+
+pub fn cluster_of(tile_pos: IVec2) -> IVec2 {
+    tile_pos.div_euclid(IVec2::splat(CLUSTER_SIZE))
+}
+
+impl NavigationGraph {
+    /// Incremented every time the graph is rebuilt after the terrain or climbables changed.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Clusters whose tiles or climbables changed between the previous version and this one.
+    pub fn changed_clusters(&self) -> &HashSet<IVec2> {
+        &self.changed
+    }
+
+    fn node(&self, tile_pos: IVec2) -> Option<Node> {
+        let cluster = cluster_of(tile_pos);
+        self.clusters
+            .get(&cluster)
+            .and_then(|cluster_data| cluster_data.components.get(&tile_pos))
+            .map(|component| (cluster, *component))
+    }
+
+    /// Region label of a walkable tile.
+    pub fn region(&self, tile_pos: IVec2) -> Option<u32> {
+        self.node(tile_pos)
+            .and_then(|node| self.regions.get(&node))
+            .copied()
+    }
+
+    /// Returns false when `target_tile_pos` can't be reached from `start_tile_pos`.
+    ///
+    /// Start tiles that aren't walkable, e.g. while falling, are assumed to reach everything.
+    pub fn may_reach(&self, start_tile_pos: IVec2, target_tile_pos: IVec2) -> bool {
+        let Some(target_region) = self.region(target_tile_pos) else {
+            return false;
+        };
+        self.region(start_tile_pos)
+            .map_or(true, |start_region| start_region == target_region)
+    }
+
+    /// Marks the clusters whose navigation depends on `tile_pos`.
+    ///
+    /// Whether a step is possible depends on the tiles above, below and beside it, so the
+    /// clusters of all tiles within two tiles are rebuilt.
+    pub fn invalidate_tile(&mut self, tile_pos: IVec2) {
+        for x in -2..=2 {
+            for y in -2..=2 {
+                self.dirty.insert(cluster_of(tile_pos + IVec2::new(x, y)));
+            }
+        }
+    }
+
+    fn invalidate_area(&mut self, min: IVec2, max: IVec2) {
+        let min_cluster = cluster_of(min - 2);
+        let max_cluster = cluster_of(max + 2);
+        for x in min_cluster.x..=max_cluster.x {
+            for y in min_cluster.y..=max_cluster.y {
+                self.dirty.insert(IVec2::new(x, y));
+            }
+        }
+    }
+
+    pub fn find_path(
+        &self,
+        terrain: &impl TerrainTiles,
+        climbable_map: &ClimbableMap,
+        start_tile_pos: IVec2,
+        target_tile_pos: IVec2,
+    ) -> Option<Path> {
+        self.plan_path(start_tile_pos, target_tile_pos)?.search(
+            terrain,
+            climbable_map,
+            start_tile_pos,
+        )
+    }
+
+    /// Plans the search for a path between two tiles, `None` when they can't reach each other.
+    pub fn plan_path(&self, start_tile_pos: IVec2, target_tile_pos: IVec2) -> Option<PathPlan> {
+        if !self.may_reach(start_tile_pos, target_tile_pos) {
+            return None;
+        }
+
+        let route = self
+            .cluster_route(start_tile_pos, target_tile_pos)
+            .unwrap_or_else(|| {
+                // Starts that aren't walkable, e.g. while falling, have no route
+                let start_cluster = cluster_of(start_tile_pos);
+                let target_cluster = cluster_of(target_tile_pos);
+                let min = start_cluster.min(target_cluster);
+                let max = start_cluster.max(target_cluster);
+                (min.x..=max.x)
+                    .flat_map(|x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+                    .collect()
+            });
+        Some(PathPlan {
+            target_tile_pos,
+            route,
+        })
+    }
+
+    /// Plans the search for a path to the closest of `target_tile_positions` in every region
+    /// `start_tile_pos` may reach, closest target first.
+    pub fn plan_paths_to_any(
+        &self,
+        start_tile_pos: IVec2,
+        target_tile_positions: &[IVec2],
+    ) -> Vec<PathPlan> {
+        let distance = |tile_pos: IVec2| {
+            (tile_pos.x - start_tile_pos.x).abs() + (tile_pos.y - start_tile_pos.y).abs()
+        };
+
+        let mut closest_targets: HashMap<u32, IVec2> = HashMap::new();
+        for &target_tile_pos in target_tile_positions {
+            if !self.may_reach(start_tile_pos, target_tile_pos) {
+                continue;
+            }
+            let Some(region) = self.region(target_tile_pos) else { continue; };
+            let closest = closest_targets.entry(region).or_insert(target_tile_pos);
+            if distance(target_tile_pos) < distance(*closest) {
+                *closest = target_tile_pos;
+            }
+        }
+
+        let mut targets = closest_targets.into_values().collect::<Vec<_>>();
+        targets.sort_by_key(|target_tile_pos| distance(*target_tile_pos));
+        targets
+            .into_iter()
+            .filter_map(|target_tile_pos| self.plan_path(start_tile_pos, target_tile_pos))
+            .collect()
+    }
+
+    /// Clusters along the shortest route between the components of two tiles.
+    fn cluster_route(
+        &self,
+        start_tile_pos: IVec2,
+        target_tile_pos: IVec2,
+    ) -> Option<HashSet<IVec2>> {
+        let start = self.node(start_tile_pos)?;
+        let target = self.node(target_tile_pos)?;
+        let cluster_distance =
+            |a: IVec2, b: IVec2| ((a.x - b.x).abs() + (a.y - b.y).abs()) * CLUSTER_SIZE;
+
+        let (route, _) = astar(
+            &start,
+            |node| {
+                self.links(*node)
+                    .map(|next| (*next, cluster_distance(node.0, next.0)))
+                    .collect::<Vec<_>>()
+            },
+            |node| cluster_distance(node.0, target.0),
+            |node| *node == target,
+        )?;
+        Some(route.into_iter().map(|(cluster, _)| cluster).collect())
+    }
+
+    fn rebuild_cluster(
+        &mut self,
+        cluster: IVec2,
+        terrain: &impl TerrainTiles,
+        climbable_map: &ClimbableMap,
+    ) {
+        let min = cluster * CLUSTER_SIZE;
+        let walkable_tiles = (0..CLUSTER_SIZE)
+            .flat_map(|x| (0..CLUSTER_SIZE).map(move |y| min + IVec2::new(x, y)))
+            .filter(|tile_pos| can_stand_or_climb(terrain, Some(climbable_map), *tile_pos))
+            .collect::<Vec<_>>();
+
+        if walkable_tiles.is_empty() {
+            self.clusters.remove(&cluster);
+            return;
+        }
+
+        // Steps inside the cluster are followed in both directions, components only need to
+        // be connected, not strongly connected
+        let mut neighbours: HashMap<IVec2, Vec<IVec2>> = HashMap::new();
+        let mut exits = vec![];
+        for &tile_pos in &walkable_tiles {
+            for (next_tile_pos, _) in successors(terrain, Some(climbable_map), tile_pos) {
+                if cluster_of(next_tile_pos) == cluster {
+                    neighbours.entry(tile_pos).or_default().push(next_tile_pos);
+                    neighbours.entry(next_tile_pos).or_default().push(tile_pos);
+                } else {
+                    exits.push((tile_pos, next_tile_pos));
+                }
+            }
+        }
+
+        let mut components = HashMap::new();
+        let mut component_count = 0u16;
+        for &tile_pos in &walkable_tiles {
+            if components.contains_key(&tile_pos) {
+                continue;
+            }
+            let component = component_count;
+            component_count += 1;
+
+            let mut queue = VecDeque::from([tile_pos]);
+            components.insert(tile_pos, component);
+            while let Some(current) = queue.pop_front() {
+                for next in neighbours.get(&current).into_iter().flatten() {
+                    if !components.contains_key(next) {
+                        components.insert(*next, component);
+                        queue.push_back(*next);
+                    }
+                }
+            }
+        }
+
+        self.clusters.insert(
+            cluster,
+            Cluster {
+                components,
+                exits,
+                links: HashMap::new(),
+            },
+        );
+    }
+
+    /// Components `node` has an exit onto.
+    fn links(&self, node: Node) -> impl Iterator<Item = &Node> {
+        self.clusters
+            .get(&node.0)
+            .and_then(|cluster_data| cluster_data.links.get(&node.1))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Components linked to `node` in either direction.
+    fn linked_nodes(&self, node: Node) -> Vec<Node> {
+        let mut linked = self.links(node).copied().collect::<Vec<_>>();
+        for cluster in cluster_neighbourhood(node.0) {
+            let Some(cluster_data) = self.clusters.get(&cluster) else { continue; };
+            for (component, targets) in &cluster_data.links {
+                if targets.contains(&node) {
+                    linked.push((cluster, *component));
+                }
+            }
+        }
+        linked
+    }
+
+    fn nodes(&self, cluster: IVec2) -> impl Iterator<Item = Node> + '_ {
+        self.clusters
+            .get(&cluster)
+            .into_iter()
+            .flat_map(move |cluster_data| {
+                cluster_data
+                    .components
+                    .values()
+                    .map(move |component| (cluster, *component))
+            })
+    }
+
+    /// Relinks the components of `cluster` to the components of the clusters around it.
+    fn relink_cluster(&mut self, cluster: IVec2) {
+        let Some(cluster_data) = self.clusters.get(&cluster) else { return; };
+        let mut links: HashMap<u16, HashSet<Node>> = HashMap::new();
+        for (from, to) in &cluster_data.exits {
+            let Some(from_component) = cluster_data.components.get(from) else { continue; };
+            let Some(to_node) = self.node(*to) else { continue; };
+            links.entry(*from_component).or_default().insert(to_node);
+        }
+        if let Some(cluster_data) = self.clusters.get_mut(&cluster) {
+            cluster_data.links = links;
+        }
+    }
+
+    /// Rebuilds the `dirty` clusters, relinks them and their neighbours and labels the regions
+    /// they were or are part of again.
+    ///
+    /// Links only change around the rebuilt clusters, so every other region keeps its label.
+    fn rebuild(
+        &mut self,
+        dirty: &HashSet<IVec2>,
+        terrain: &impl TerrainTiles,
+        climbable_map: &ClimbableMap,
+    ) {
+        let relinked = dirty
+            .iter()
+            .flat_map(|cluster| cluster_neighbourhood(*cluster))
+            .collect::<HashSet<_>>();
+        let stale_regions = relinked
+            .iter()
+            .flat_map(|cluster| self.nodes(*cluster))
+            .filter_map(|node| self.regions.get(&node).copied())
+            .collect::<HashSet<_>>();
+
+        for &cluster in dirty {
+            self.rebuild_cluster(cluster, terrain, climbable_map);
+        }
+        for &cluster in &relinked {
+            self.relink_cluster(cluster);
+        }
+
+        // Components of the stale regions that still exist, and the components of the rebuilt
+        // clusters, are labelled again
+        let mut unlabelled = Vec::new();
+        for region in stale_regions {
+            for node in self.region_nodes.remove(&region).unwrap_or_default() {
+                self.regions.remove(&node);
+                let exists = self.clusters.get(&node.0).map_or(false, |cluster_data| {
+                    cluster_data
+                        .components
+                        .values()
+                        .any(|component| *component == node.1)
+                });
+                if exists {
+                    unlabelled.push(node);
+                }
+            }
+        }
+        unlabelled.extend(dirty.iter().flat_map(|cluster| self.nodes(*cluster)));
+
+        for node in unlabelled {
+            if self.regions.contains_key(&node) {
+                continue;
+            }
+            let region = self.next_region;
+            self.next_region += 1;
+
+            let mut queue = VecDeque::from([node]);
+            let mut nodes = vec![node];
+            self.regions.insert(node, region);
+            while let Some(current) = queue.pop_front() {
+                for next in self.linked_nodes(current) {
+                    if !self.regions.contains_key(&next) {
+                        self.regions.insert(next, region);
+                        nodes.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            self.region_nodes.insert(region, nodes);
+        }
+    }
+}
+
+fn invalidate_chunks(
+    mut navigation_graph: ResMut<NavigationGraph>,
+    terrain_settings: Res<TerrainSettings>,
+    new_chunk_query: Query<(Entity, &TerrainChunk), (With<Terrain>, Added<TileStorage>)>,
+    mut removed_chunks: RemovedComponents<TerrainChunk>,
+) {
+    let chunk_size = terrain_settings.chunk_size();
+    for (chunk_entity, chunk) in &new_chunk_query {
+        navigation_graph.chunks.insert(chunk_entity, chunk.0);
+        let min = chunk.0 * chunk_size;
+        navigation_graph.invalidate_area(min, min + chunk_size - 1);
+    }
+    for chunk_entity in removed_chunks.iter() {
+        if let Some(region) = navigation_graph.chunks.remove(&chunk_entity) {
+            let min = region * chunk_size;
+            navigation_graph.invalidate_area(min, min + chunk_size - 1);
+        }
+    }
+}
+
+fn invalidate_destroyed_tiles(
+    mut navigation_graph: ResMut<NavigationGraph>,
+    terrain_settings: Res<TerrainSettings>,
+    mut destroyed_tiles: EventReader<TileDestroyedEvent>,
+) {
+    for destroyed_tile in destroyed_tiles.iter() {
+        let Some(&region) = navigation_graph.chunks.get(&destroyed_tile.chunk) else {
+            continue;
+        };
+        let tile_pos = terrain_settings.chunk_to_tile(region, destroyed_tile.tile_pos);
+        navigation_graph.invalidate_tile(tile_pos);
+    }
+}
+
+fn invalidate_placed_tiles(
+    mut navigation_graph: ResMut<NavigationGraph>,
+    terrain_settings: Res<TerrainSettings>,
+    mut placed_tiles: EventReader<TilePlacedEvent>,
+) {
+    for placed_tile in placed_tiles.iter() {
+        let Some(&region) = navigation_graph.chunks.get(&placed_tile.chunk) else {
+            continue;
+        };
+        let tile_pos = terrain_settings.chunk_to_tile(region, placed_tile.tile_pos);
+        navigation_graph.invalidate_tile(tile_pos);
+    }
+}
+
+fn invalidate_climbable_tiles(
+    mut navigation_graph: ResMut<NavigationGraph>,
+    mut climbable_changed_events: EventReader<ClimbableChangedEvent>,
+) {
+    for climbable_changed in climbable_changed_events.iter() {
+        navigation_graph.invalidate_tile(climbable_changed.tile_pos);
+    }
+}
+
+fn rebuild_navigation_graph(
+    mut navigation_graph: ResMut<NavigationGraph>,
+    terrain: TerrainParam,
+    climbable_map: Res<ClimbableMap>,
+) {
+    if navigation_graph.dirty.is_empty() {
+        return;
+    }
+
+    let dirty = std::mem::take(&mut navigation_graph.dirty);
+    debug!(clusters = dirty.len(), "Rebuilding navigation graph");
+    navigation_graph.rebuild(&dirty, &terrain, &climbable_map);
+    navigation_graph.changed = dirty;
+    navigation_graph.version += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{prelude::IVec2, utils::HashSet};
+    use ndarray::Array2;
+
+    use super::NavigationGraph;
+    use crate::{climbable::ClimbableMap, terrain::TerrainTiles};
+
+    const WIDTH: usize = 48;
+    const HEIGHT: usize = 8;
+
+    struct TestTerrain(Array2<u16>);
+
+    impl TerrainTiles for TestTerrain {
+        fn get_tile(&self, tile_pos: IVec2) -> Option<u16> {
+            if tile_pos.x < 0 || tile_pos.y < 0 {
+                return None;
+            }
+            self.0
+                .get([tile_pos.x as usize, tile_pos.y as usize])
+                .copied()
+        }
+    }
+
+    /// Solid floor at y = 0 with a wall two tiles high, too high to climb, at every x in `walls`.
+    fn terrain_with_walls(walls: &[i32]) -> TestTerrain {
+        let mut tiles = Array2::zeros((WIDTH, HEIGHT));
+        tiles.column_mut(0).fill(1);
+        for &x in walls {
+            tiles[[x as usize, 1]] = 1;
+            tiles[[x as usize, 2]] = 1;
+        }
+        TestTerrain(tiles)
+    }
+
+    fn rebuild_dirty(navigation_graph: &mut NavigationGraph, terrain: &TestTerrain) {
+        let dirty = std::mem::take(&mut navigation_graph.dirty);
+        navigation_graph.rebuild(&dirty, terrain, &ClimbableMap::default());
+    }
+
+    fn build_graph(terrain: &TestTerrain) -> NavigationGraph {
+        let mut navigation_graph = NavigationGraph::default();
+        navigation_graph
+            .invalidate_area(IVec2::ZERO, IVec2::new(WIDTH as i32 - 1, HEIGHT as i32 - 1));
+        rebuild_dirty(&mut navigation_graph, terrain);
+        navigation_graph
+    }
+
+    fn set_tile(
+        navigation_graph: &mut NavigationGraph,
+        terrain: &mut TestTerrain,
+        tile_pos: IVec2,
+        tile: u16,
+    ) {
+        terrain.0[[tile_pos.x as usize, tile_pos.y as usize]] = tile;
+        navigation_graph.invalidate_tile(tile_pos);
+    }
+
+    #[test]
+    fn walls_separate_regions() {
+        let terrain = terrain_with_walls(&[12, 36]);
+        let navigation_graph = build_graph(&terrain);
+
+        // Linked across the border of the first two clusters
+        assert!(navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(10, 1)));
+        assert!(navigation_graph.may_reach(IVec2::new(13, 1), IVec2::new(35, 1)));
+        assert!(!navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(20, 1)));
+        assert!(!navigation_graph.may_reach(IVec2::new(20, 1), IVec2::new(40, 1)));
+        // Solid tiles can't be reached, starts in the air may reach everything
+        assert!(!navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(12, 1)));
+        assert!(navigation_graph.may_reach(IVec2::new(20, 5), IVec2::new(1, 1)));
+
+        let path = navigation_graph
+            .find_path(
+                &terrain,
+                &ClimbableMap::default(),
+                IVec2::new(1, 1),
+                IVec2::new(10, 1),
+            )
+            .expect("Tiles on the same floor should be connected");
+        assert_eq!(path.0.last(), Some(&IVec2::new(10, 1)));
+    }
+
+    #[test]
+    fn dig_joins_regions() {
+        let mut terrain = terrain_with_walls(&[12, 36]);
+        let mut navigation_graph = build_graph(&terrain);
+        let far_region = navigation_graph.region(IVec2::new(40, 1));
+
+        set_tile(&mut navigation_graph, &mut terrain, IVec2::new(12, 1), 0);
+        set_tile(&mut navigation_graph, &mut terrain, IVec2::new(12, 2), 0);
+        rebuild_dirty(&mut navigation_graph, &terrain);
+
+        assert!(navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(20, 1)));
+        assert!(navigation_graph.may_reach(IVec2::new(20, 1), IVec2::new(1, 1)));
+        assert!(!navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(40, 1)));
+        // Regions away from the rebuilt clusters keep their label
+        assert_eq!(navigation_graph.region(IVec2::new(40, 1)), far_region);
+    }
+
+    #[test]
+    fn placed_tiles_split_regions() {
+        let mut terrain = terrain_with_walls(&[36]);
+        let mut navigation_graph = build_graph(&terrain);
+        let far_region = navigation_graph.region(IVec2::new(40, 1));
+        assert!(navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(20, 1)));
+
+        set_tile(&mut navigation_graph, &mut terrain, IVec2::new(12, 1), 1);
+        set_tile(&mut navigation_graph, &mut terrain, IVec2::new(12, 2), 1);
+        rebuild_dirty(&mut navigation_graph, &terrain);
+
+        assert!(!navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(20, 1)));
+        assert!(!navigation_graph.may_reach(IVec2::new(20, 1), IVec2::new(1, 1)));
+        assert!(navigation_graph.may_reach(IVec2::new(1, 1), IVec2::new(10, 1)));
+        assert_eq!(navigation_graph.region(IVec2::new(40, 1)), far_region);
+    }
+
+    #[test]
+    fn plans_paths_to_closest_target_per_region() {
+        let terrain = terrain_with_walls(&[12, 36]);
+        let navigation_graph = build_graph(&terrain);
+        let targets = [
+            IVec2::new(8, 1),
+            IVec2::new(20, 1),
+            IVec2::new(5, 1),
+            IVec2::new(30, 1),
+        ];
+
+        let plans = navigation_graph.plan_paths_to_any(IVec2::new(1, 1), &targets);
+        let planned_targets = plans
+            .iter()
+            .map(|plan| plan.target_tile_pos)
+            .collect::<Vec<_>>();
+        assert_eq!(planned_targets, vec![IVec2::new(5, 1)]);
+
+        // Starts in the air may reach every region, closest target first
+        let plans = navigation_graph.plan_paths_to_any(IVec2::new(16, 5), &targets);
+        let planned_targets = plans
+            .iter()
+            .map(|plan| plan.target_tile_pos)
+            .collect::<HashSet<_>>();
+        assert_eq!(plans[0].target_tile_pos, IVec2::new(20, 1));
+        assert_eq!(
+            planned_targets,
+            HashSet::from([IVec2::new(20, 1), IVec2::new(8, 1)])
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dwarf::Attributes,
+    health::{HealthDamageEvent, HealthSet},
+    item::ItemDefinition,
+    main_state::MainState,
+};
+
+pub struct NeedsPlugin;
+
+impl Plugin for NeedsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Needs>()
+            .register_type::<Sleeping>()
+            .insert_resource(UnmetNeedsTimer(Timer::from_seconds(
+                UNMET_NEED_DAMAGE_INTERVAL,
+                TimerMode::Repeating,
+            )))
+            .add_systems(
+                Update,
+                (update_needs, unmet_needs_damage.before(HealthSet))
+                    .chain()
+                    .run_if(in_state(MainState::Game)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NeedKind {
+    Hunger,
+    Thirst,
+    Fatigue,
+}
+
+impl NeedKind {
+    pub const ALL: [NeedKind; 3] = [NeedKind::Hunger, NeedKind::Thirst, NeedKind::Fatigue];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NeedKind::Hunger => "Hunger",
+            NeedKind::Thirst => "Thirst",
+            NeedKind::Fatigue => "Fatigue",
+        }
+    }
+
+    /// How much consuming a single item of `definition` satisfies the need.
+    pub fn satisfaction(&self, definition: &ItemDefinition) -> f32 {
+        match self {
+            NeedKind::Hunger => definition.nutrition,
+            NeedKind::Thirst => definition.hydration,
+            NeedKind::Fatigue => 0.,
+        }
+    }
+}
+
+/// How badly a dwarf needs to eat, drink and sleep, from 0 (satisfied) to 1 (unmet).
+#[derive(Component, Debug, Default, Clone, Copy, Reflect, Serialize, Deserialize)]
+pub struct Needs {
+    pub hunger: f32,
+    pub thirst: f32,
+    pub fatigue: f32,
+}
+
+impl Needs {
+    pub fn get(&self, kind: NeedKind) -> f32 {
+        match kind {
+            NeedKind::Hunger => self.hunger,
+            NeedKind::Thirst => self.thirst,
+            NeedKind::Fatigue => self.fatigue,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: NeedKind) -> &mut f32 {
+        match kind {
+            NeedKind::Hunger => &mut self.hunger,
+            NeedKind::Thirst => &mut self.thirst,
+            NeedKind::Fatigue => &mut self.fatigue,
+        }
+    }
+
+    /// Lowers the need by `amount`, down to fully satisfied.
+    pub fn satisfy(&mut self, kind: NeedKind, amount: f32) {
+        let need = self.get_mut(kind);
+        *need = (*need - amount).max(0.);
+    }
+}
+
+/// Marks a dwarf that is asleep, recovering from fatigue instead of growing tired.
+#[derive(Component, Debug, Default, Reflect)]
+pub struct Sleeping;
+
+/// Need at which dwarves drop their work to take care of it.
+pub const URGENT_NEED: f32 = 0.8;
+pub const HUNGER_PER_SECOND: f32 = 1. / 480.;
+pub const THIRST_PER_SECOND: f32 = 1. / 360.;
+pub const FATIGUE_PER_SECOND: f32 = 1. / 720.;
+pub const FATIGUE_RECOVERY_PER_SECOND: f32 = 1. / 60.;
+/// Seconds between two damage ticks of needs that are fully unmet.
+pub const UNMET_NEED_DAMAGE_INTERVAL: f32 = 5.;
+pub const UNMET_NEED_DAMAGE: u32 = 1;
+
+#[derive(Resource)]
+struct UnmetNeedsTimer(Timer);
+
+fn update_needs(
+    mut needs_query: Query<(&mut Needs, Option<&Sleeping>, Option<&Attributes>)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    for (mut needs, opt_sleeping, opt_attributes) in &mut needs_query {
+        needs.hunger = (needs.hunger + HUNGER_PER_SECOND * delta).min(1.);
+        needs.thirst = (needs.thirst + THIRST_PER_SECOND * delta).min(1.);
+        if opt_sleeping.is_some() {
+            needs.satisfy(NeedKind::Fatigue, FATIGUE_RECOVERY_PER_SECOND * delta);
+        } else {
+            let fatigue_multiplier = opt_attributes.map_or(1., Attributes::fatigue_multiplier);
+            needs.fatigue =
+                (needs.fatigue + FATIGUE_PER_SECOND * fatigue_multiplier * delta).min(1.);
+        }
+    }
+}
+
+/// Hurts every dwarf for each of its needs that went fully unmet.
+fn unmet_needs_damage(
+    mut timer: ResMut<UnmetNeedsTimer>,
+    time: Res<Time>,
+    needs_query: Query<(Entity, &Needs)>,
+    mut health_damage_events: EventWriter<HealthDamageEvent>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    for (entity, needs) in &needs_query {
+        for kind in NeedKind::ALL {
+            if needs.get(kind) >= 1. {
+                debug!(?entity, need = kind.name(), "Need unmet");
+                health_damage_events.send(HealthDamageEvent {
+                    entity,
+                    damage: UNMET_NEED_DAMAGE,
+                });
+            }
+        }
+    }
+}
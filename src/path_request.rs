@@ -0,0 +1,345 @@
+use std::sync::Arc;
+
+use bevy::{
+    prelude::*,
+    tasks::{futures_lite::future, AsyncComputeTaskPool, Task},
+    utils::{HashMap, HashSet},
+};
+use bevy_ecs_tilemap::tiles::TileStorage;
+
+use crate::{
+    climbable::ClimbableMap,
+    navigation::{NavigationGraph, NavigationSet, CLUSTER_SIZE},
+    pathfinding::{can_stand_or_climb, successors, Path},
+    terrain::{Terrain, TerrainChunk, TerrainData, TerrainTiles},
+    terrain_settings::TerrainSettings,
+};
+
+pub struct PathRequestPlugin;
+
+impl Plugin for PathRequestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PathfindingSnapshot>()
+            .add_event::<PathFailedEvent>()
+            .add_systems(
+                Update,
+                (
+                    update_pathfinding_snapshot,
+                    discard_stale_paths,
+                    receive_paths,
+                    start_path_requests,
+                )
+                    .chain()
+                    .in_set(PathRequestSet)
+                    .after(NavigationSet)
+                    .run_if(resource_exists::<TerrainSettings>()),
+            );
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathRequestSet;
+
+/// Requests a path from `start` to the closest reachable of `targets`, in world positions.
+///
+/// The search runs in the background. Once it is done the request is removed and either a
+/// [`Path`] is inserted on the same entity or a [`PathFailedEvent`] is sent for it. Inserting a
+/// new request replaces the one in progress.
+#[derive(Component, Debug, Clone)]
+pub struct PathRequest {
+    pub start: Vec2,
+    pub targets: Vec<Vec2>,
+}
+
+impl PathRequest {
+    pub fn new(start: Vec2, target: Vec2) -> Self {
+        Self {
+            start,
+            targets: vec![target],
+        }
+    }
+
+    pub fn to_any(start: Vec2, targets: impl IntoIterator<Item = Vec2>) -> Self {
+        Self {
+            start,
+            targets: targets.into_iter().collect(),
+        }
+    }
+}
+
+/// Sent when no path could be found for the [`PathRequest`] of `entity`.
+#[derive(Event, Debug)]
+pub struct PathFailedEvent {
+    pub entity: Entity,
+}
+
+/// Version of the terrain snapshot a requested [`Path`] was last checked against.
+///
+/// Paths that can't be followed anymore once the terrain changes are removed, so their owners
+/// request a new one.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PathVersion(pub u64);
+
+/// Entities whose path request failed since the last call.
+pub fn failed_path_entities(
+    path_failed_events: &mut EventReader<PathFailedEvent>,
+) -> HashSet<Entity> {
+    path_failed_events
+        .iter()
+        .map(|event| event.entity)
+        .collect()
+}
+
+/// Removes the path of `entity` and any request for a new one.
+pub fn clear_path(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<(Path, PathVersion, PathRequest)>();
+}
+
+/// Copy of the loaded terrain chunks and climbables that path searches run against.
+///
+/// Chunks are shared between snapshots, a new snapshot only copies the chunks that changed.
+pub struct TerrainSnapshot {
+    settings: TerrainSettings,
+    chunks: HashMap<IVec2, Arc<TerrainData>>,
+    climbable_map: ClimbableMap,
+}
+
+impl TerrainSnapshot {
+    /// Whether every step of `path` can still be taken.
+    fn can_follow(&self, path: &[IVec2]) -> bool {
+        let climbable_map = Some(&self.climbable_map);
+        let can_reach_first = path.first().map_or(true, |first_tile_pos| {
+            can_stand_or_climb(self, climbable_map, *first_tile_pos)
+        });
+        can_reach_first
+            && path.windows(2).all(|step| {
+                successors(self, climbable_map, step[0])
+                    .iter()
+                    .any(|(next_tile_pos, _)| *next_tile_pos == step[1])
+            })
+    }
+}
+
+impl TerrainTiles for TerrainSnapshot {
+    fn get_tile(&self, tile_pos: IVec2) -> Option<u16> {
+        let (region, chunk_tile_pos) = self.settings.tile_to_chunk(tile_pos);
+        self.chunks.get(&region)?.get_tile(chunk_tile_pos.into())
+    }
+}
+
+/// The latest [`TerrainSnapshot`], taken again whenever the [`NavigationGraph`] is rebuilt.
+#[derive(Resource, Default)]
+pub struct PathfindingSnapshot {
+    version: u64,
+    terrain: Option<Arc<TerrainSnapshot>>,
+}
+
+impl PathfindingSnapshot {
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct PathTask {
+    task: Task<Option<Path>>,
+    version: u64,
+}
+
+fn update_pathfinding_snapshot(
+    mut snapshot: ResMut<PathfindingSnapshot>,
+    navigation_graph: Res<NavigationGraph>,
+    terrain_settings: Res<TerrainSettings>,
+    climbable_map: Res<ClimbableMap>,
+    chunk_query: Query<(&TerrainChunk, &TerrainData), (With<Terrain>, With<TileStorage>)>,
+) {
+    if snapshot.terrain.is_some() && snapshot.version == navigation_graph.version() {
+        return;
+    }
+
+    let loaded_chunks: HashMap<IVec2, &TerrainData> = chunk_query
+        .iter()
+        .map(|(chunk, terrain_data)| (chunk.0, terrain_data))
+        .collect();
+    let chunks = match snapshot.terrain.as_deref() {
+        // Only the chunks under the rebuilt clusters changed since the previous version
+        Some(previous) if snapshot.version + 1 == navigation_graph.version() => {
+            let mut chunks = previous.chunks.clone();
+            let changed_regions =
+                cluster_regions(navigation_graph.changed_clusters(), &terrain_settings);
+            debug!(
+                version = navigation_graph.version(),
+                chunks = changed_regions.len(),
+                "Updating pathfinding snapshot"
+            );
+            for region in changed_regions {
+                match loaded_chunks.get(&region) {
+                    Some(terrain_data) => {
+                        chunks.insert(region, Arc::new((*terrain_data).clone()));
+                    }
+                    None => {
+                        chunks.remove(&region);
+                    }
+                }
+            }
+            chunks
+        }
+        _ => {
+            debug!(
+                version = navigation_graph.version(),
+                "Taking pathfinding snapshot"
+            );
+            loaded_chunks
+                .into_iter()
+                .map(|(region, terrain_data)| (region, Arc::new(terrain_data.clone())))
+                .collect()
+        }
+    };
+
+    snapshot.version = navigation_graph.version();
+    snapshot.terrain = Some(Arc::new(TerrainSnapshot {
+        settings: terrain_settings.clone(),
+        chunks,
+        climbable_map: climbable_map.clone(),
+    }));
+}
+
+/// Chunks that any of `clusters` overlap.
+fn cluster_regions(
+    clusters: &HashSet<IVec2>,
+    terrain_settings: &TerrainSettings,
+) -> HashSet<IVec2> {
+    let mut regions = HashSet::new();
+    for cluster in clusters {
+        let min = *cluster * CLUSTER_SIZE;
+        let (min_region, _) = terrain_settings.tile_to_chunk(min);
+        let (max_region, _) = terrain_settings.tile_to_chunk(min + CLUSTER_SIZE - 1);
+        for x in min_region.x..=max_region.x {
+            for y in min_region.y..=max_region.y {
+                regions.insert(IVec2::new(x, y));
+            }
+        }
+    }
+    regions
+}
+
+/// Keeps the paths that can still be followed on the latest snapshot and removes the others, so
+/// their owners request a new one.
+fn discard_stale_paths(
+    mut commands: Commands,
+    snapshot: Res<PathfindingSnapshot>,
+    mut path_query: Query<(Entity, &Path, &mut PathVersion)>,
+) {
+    let Some(terrain) = snapshot.terrain.as_deref() else { return; };
+
+    for (entity, path, mut path_version) in &mut path_query {
+        if path_version.0 == snapshot.version {
+            continue;
+        }
+        if terrain.can_follow(&path.0) {
+            path_version.0 = snapshot.version;
+        } else {
+            debug!(?entity, "Discarding path blocked by terrain changes");
+            commands.entity(entity).remove::<(Path, PathVersion)>();
+        }
+    }
+}
+
+fn start_path_requests(
+    mut commands: Commands,
+    snapshot: Res<PathfindingSnapshot>,
+    navigation_graph: Res<NavigationGraph>,
+    request_query: Query<(Entity, Ref<PathRequest>, Option<&PathTask>)>,
+    mut path_failed_events: EventWriter<PathFailedEvent>,
+) {
+    let Some(terrain) = snapshot.terrain.as_ref() else { return; };
+
+    let requests = request_query
+        .iter()
+        .filter(|(_, request, opt_task)| request.is_changed() || opt_task.is_none());
+    for (entity, request, _) in requests {
+        let start_tile_pos = terrain.settings.world_to_tile(request.start);
+        let target_tile_positions = request
+            .targets
+            .iter()
+            .map(|target| terrain.settings.world_to_tile(*target))
+            .collect::<Vec<_>>();
+        // Targets in another region are never reachable, only the closest target of each
+        // reachable region is searched for
+        let plans = navigation_graph.plan_paths_to_any(start_tile_pos, &target_tile_positions);
+
+        if plans.is_empty() {
+            finish_path_request(
+                &mut commands,
+                &mut path_failed_events,
+                entity,
+                None,
+                snapshot.version,
+            );
+            continue;
+        }
+
+        let terrain = Arc::clone(terrain);
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            plans
+                .iter()
+                .find_map(|plan| plan.search(&*terrain, &terrain.climbable_map, start_tile_pos))
+        });
+        commands.entity(entity).insert(PathTask {
+            task,
+            version: snapshot.version,
+        });
+    }
+}
+
+fn receive_paths(
+    mut commands: Commands,
+    snapshot: Res<PathfindingSnapshot>,
+    mut task_query: Query<(Entity, &mut PathTask, Option<Ref<PathRequest>>)>,
+    mut path_failed_events: EventWriter<PathFailedEvent>,
+) {
+    for (entity, mut path_task, opt_request) in &mut task_query {
+        let Some(path) = future::block_on(future::poll_once(&mut path_task.task)) else { continue; };
+        commands.entity(entity).remove::<PathTask>();
+
+        // The request was withdrawn or replaced while searching
+        let Some(request) = opt_request else { continue; };
+        if request.is_changed() {
+            continue;
+        }
+
+        // The terrain changed while searching, the request is started again on the new snapshot
+        if path_task.version != snapshot.version {
+            debug!(?entity, "Discarding path found on outdated terrain");
+            continue;
+        }
+
+        finish_path_request(
+            &mut commands,
+            &mut path_failed_events,
+            entity,
+            path,
+            path_task.version,
+        );
+    }
+}
+
+fn finish_path_request(
+    commands: &mut Commands,
+    path_failed_events: &mut EventWriter<PathFailedEvent>,
+    entity: Entity,
+    path: Option<Path>,
+    version: u64,
+) {
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.remove::<PathRequest>();
+    if let Some(path) = path {
+        entity_commands.insert((path, PathVersion(version)));
+    } else {
+        entity_commands.remove::<(Path, PathVersion)>();
+        path_failed_events.send(PathFailedEvent { entity });
+    }
+}
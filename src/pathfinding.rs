@@ -1,24 +1,21 @@
 use std::matches;
 
-use bevy::{
-    ecs::system::{lifetimeless::SQuery, SystemParam},
-    prelude::*,
-};
+use bevy::{ecs::system::SystemParam, prelude::*};
 
-use bevy_ecs_tilemap::{
-    helpers::square_grid::neighbors::SquareDirection, prelude::TilemapSize, tiles::TilePos,
-};
+use bevy_ecs_tilemap::helpers::square_grid::neighbors::SquareDirection;
 use pathfinding::directed::astar::astar;
 
 use crate::{
     climbable::ClimbableMap,
-    terrain::{TerrainData, TerrainParam},
+    navigation::NavigationGraph,
+    terrain::{TerrainParam, TerrainTiles},
 };
 
 #[derive(SystemParam)]
 pub struct Pathfinding<'w, 's> {
     pub terrain: TerrainParam<'w, 's>,
-    climbable_map_query: Query<'w, 's, &'static ClimbableMap>,
+    climbable_map: Res<'w, ClimbableMap>,
+    navigation_graph: Res<'w, NavigationGraph>,
 }
 
 impl<'w, 's> Pathfinding<'w, 's> {
@@ -29,69 +26,105 @@ impl<'w, 's> Pathfinding<'w, 's> {
         let Some(target_tile_pos) = self.terrain.global_to_tile_pos(target_pos) else {
             return None;
         };
-        let terrain_data = self.terrain.terrain_data_query.single();
-        let climbable_map = self.climbable_map_query.single();
-
-        find_path(
-            terrain_data,
-            Some(climbable_map),
-            start_tile_pos.into(),
-            target_tile_pos.into(),
+
+        self.navigation_graph.find_path(
+            &self.terrain,
+            &self.climbable_map,
+            start_tile_pos,
+            target_tile_pos,
         )
     }
 }
 
+/// A path of world tile positions.
 #[derive(Component, Debug, Reflect, Clone)]
-pub struct Path(pub Vec<UVec2>);
+pub struct Path(pub Vec<IVec2>);
+
+/// Offset of the neighbouring tile in `direction`.
+pub fn direction_offset(direction: SquareDirection) -> IVec2 {
+    match direction {
+        SquareDirection::North => IVec2::new(0, 1),
+        SquareDirection::NorthEast => IVec2::new(1, 1),
+        SquareDirection::East => IVec2::new(1, 0),
+        SquareDirection::SouthEast => IVec2::new(1, -1),
+        SquareDirection::South => IVec2::new(0, -1),
+        SquareDirection::SouthWest => IVec2::new(-1, -1),
+        SquareDirection::West => IVec2::new(-1, 0),
+        SquareDirection::NorthWest => IVec2::new(-1, 1),
+    }
+}
+
+/// Tiles reachable in a single step from `tile_pos`, with the cost of the step.
+pub fn successors(
+    terrain: &impl TerrainTiles,
+    climbable_map: Option<&ClimbableMap>,
+    tile_pos: IVec2,
+) -> Vec<(IVec2, i32)> {
+    let mut successors = Vec::new();
+    for direction in [
+        SquareDirection::North,
+        SquareDirection::NorthEast,
+        SquareDirection::East,
+        SquareDirection::SouthEast,
+        SquareDirection::South,
+        SquareDirection::SouthWest,
+        SquareDirection::West,
+        SquareDirection::NorthWest,
+    ]
+    .iter()
+    {
+        let target_tile_pos = tile_pos + direction_offset(*direction);
+        if matches!(
+            direction,
+            SquareDirection::SouthWest | SquareDirection::SouthEast
+        ) {
+            if can_stand(terrain, target_tile_pos)
+                && can_move_to(terrain, climbable_map, tile_pos, *direction)
+            {
+                successors.push((target_tile_pos, 1));
+            }
+        } else if can_stand_or_climb(terrain, climbable_map, target_tile_pos)
+            && can_move_to(terrain, climbable_map, tile_pos, *direction)
+        {
+            successors.push((target_tile_pos, 1));
+        }
+    }
+    successors
+}
 
+/// Finds a path between two tiles. The search never leaves the loaded chunks, tiles of
+/// unloaded chunks are treated as impassable.
 pub fn find_path(
-    terrain_data: &TerrainData,
+    terrain: &impl TerrainTiles,
     climbable_map: Option<&ClimbableMap>,
-    start_tile_pos: UVec2,
-    target_tile_pos: UVec2,
+    start_tile_pos: IVec2,
+    target_tile_pos: IVec2,
+) -> Option<Path> {
+    find_path_within(
+        terrain,
+        climbable_map,
+        start_tile_pos,
+        target_tile_pos,
+        |_| true,
+    )
+}
+
+/// Like [`find_path`], but only steps onto tiles for which `allowed` returns true.
+pub fn find_path_within(
+    terrain: &impl TerrainTiles,
+    climbable_map: Option<&ClimbableMap>,
+    start_tile_pos: IVec2,
+    target_tile_pos: IVec2,
+    allowed: impl Fn(IVec2) -> bool,
 ) -> Option<Path> {
     let path = astar(
         &start_tile_pos,
         |p| {
-            let mut successors = Vec::new();
-            let tile_pos: TilePos = (*p).into();
-            let map_size: TilemapSize = terrain_data.map_size().into();
-            for direction in [
-                SquareDirection::North,
-                SquareDirection::NorthEast,
-                SquareDirection::East,
-                SquareDirection::SouthEast,
-                SquareDirection::South,
-                SquareDirection::SouthWest,
-                SquareDirection::West,
-                SquareDirection::NorthWest,
-            ]
-            .iter()
-            {
-                let Some(target_tile_pos) = tile_pos.square_offset(direction, &map_size) else {
-                    continue;
-                };
-                if matches!(
-                    direction,
-                    SquareDirection::SouthWest | SquareDirection::SouthEast
-                ) {
-                    if can_stand(terrain_data, target_tile_pos)
-                        && can_move_to(terrain_data, climbable_map, tile_pos, *direction)
-                    {
-                        successors.push((target_tile_pos.into(), 1));
-                    }
-                } else if can_stand_or_climb(terrain_data, climbable_map, target_tile_pos)
-                    && can_move_to(terrain_data, climbable_map, tile_pos, *direction)
-                {
-                    successors.push((target_tile_pos.into(), 1));
-                }
-            }
+            let mut successors = successors(terrain, climbable_map, *p);
+            successors.retain(|(tile_pos, _)| allowed(*tile_pos));
             successors
         },
-        |p| {
-            (p.x as i32 - target_tile_pos.x as i32).abs()
-                + (p.y as i32 - target_tile_pos.y as i32).abs()
-        },
+        |p| (p.x - target_tile_pos.x).abs() + (p.y - target_tile_pos.y).abs(),
         |p| *p == target_tile_pos,
     )
     .unwrap_or_default()
@@ -105,18 +138,18 @@ pub fn find_path(
 }
 
 pub fn can_stand_or_climb(
-    terrain_data: &TerrainData,
+    terrain: &impl TerrainTiles,
     climbable_map: Option<&ClimbableMap>,
-    tile_pos: TilePos,
+    tile_pos: IVec2,
 ) -> bool {
     let can_climb_in_tile = can_climb(climbable_map, tile_pos);
 
-    let can_stand_in_tile = can_stand(terrain_data, tile_pos);
+    let can_stand_in_tile = can_stand(terrain, tile_pos);
 
     can_climb_in_tile || can_stand_in_tile
 }
 
-pub fn can_climb(climbable_map: Option<&ClimbableMap>, tile_pos: TilePos) -> bool {
+pub fn can_climb(climbable_map: Option<&ClimbableMap>, tile_pos: IVec2) -> bool {
     if let Some(climbable_map) = climbable_map {
         let tile_is_climbable = climbable_map.is_climbable(tile_pos);
         if tile_is_climbable {
@@ -126,38 +159,27 @@ pub fn can_climb(climbable_map: Option<&ClimbableMap>, tile_pos: TilePos) -> boo
     false
 }
 
-pub fn can_stand(terrain_data: &TerrainData, tile_pos: TilePos) -> bool {
-    let map_size: TilemapSize = terrain_data.map_size().into();
-    if terrain_data
-        .get_tile(tile_pos.into())
-        .map_or(false, |tile| tile != 0)
-    {
-        // Tile is solid
+pub fn can_stand(terrain: &impl TerrainTiles, tile_pos: IVec2) -> bool {
+    if terrain.get_tile(tile_pos).map_or(true, |tile| tile != 0) {
+        // Tile is solid or outside of the loaded chunks
         return false;
     }
-    let Some(south_tile_pos) = tile_pos.square_offset(&SquareDirection::South, &map_size) else {
-        // Tileposition is outside of the map
-        return false;
-    };
+    let south_tile_pos = tile_pos + direction_offset(SquareDirection::South);
 
-    terrain_data
-        .get_tile(south_tile_pos.into())
+    terrain
+        .get_tile(south_tile_pos)
         .map_or(false, |tile| tile != 0)
 }
 
 pub fn can_move_to(
-    terrain_data: &TerrainData,
+    terrain: &impl TerrainTiles,
     climbable_map: Option<&ClimbableMap>,
-    tile_pos: TilePos,
+    tile_pos: IVec2,
     direction: SquareDirection,
 ) -> bool {
-    let map_size: TilemapSize = terrain_data.map_size().into();
-    let Some(new_tile_pos) = tile_pos.square_offset(&direction, &map_size) else {
-        // Tileposition is outside of the map
-        return false;
-    };
-    let new_tile_is_empty = terrain_data
-        .get_tile(new_tile_pos.into())
+    let new_tile_pos = tile_pos + direction_offset(direction);
+    let new_tile_is_empty = terrain
+        .get_tile(new_tile_pos)
         .map_or(false, |tile| tile == 0);
 
     if !new_tile_is_empty {
@@ -179,43 +201,27 @@ pub fn can_move_to(
 
     match direction {
         SquareDirection::NorthEast | SquareDirection::NorthWest => {
-            let Some(north_tile_pos) = tile_pos.square_offset(&SquareDirection::North, &map_size) else {
-                // Tileposition is outside of the map
-                return false;
-            };
-            terrain_data
-                .get_tile(north_tile_pos.into())
+            let north_tile_pos = tile_pos + direction_offset(SquareDirection::North);
+            terrain
+                .get_tile(north_tile_pos)
                 .map_or(false, |tile| tile == 0)
         }
 
         SquareDirection::SouthEast => {
-            let Some(east_tile_pos) = tile_pos.square_offset(&SquareDirection::East, &map_size) else {
-                // Tileposition is outside of the map
-                return false;
-            };
+            let east_tile_pos = tile_pos + direction_offset(SquareDirection::East);
 
-            terrain_data
-                .get_tile(east_tile_pos.into())
+            terrain
+                .get_tile(east_tile_pos)
                 .map_or(false, |tile| tile == 0)
         }
         SquareDirection::SouthWest => {
-            let Some(west_tile_pos) = tile_pos.square_offset(&SquareDirection::West, &map_size) else {
-                // Tileposition is outside of the map
-                return false;
-            };
+            let west_tile_pos = tile_pos + direction_offset(SquareDirection::West);
 
-            terrain_data
-                .get_tile(west_tile_pos.into())
+            terrain
+                .get_tile(west_tile_pos)
                 .map_or(false, |tile| tile == 0)
         }
-        SquareDirection::West | SquareDirection::East => {
-            let Some(_target_tile_pos) = tile_pos.square_offset(&direction, &map_size) else {
-                // Tileposition is outside of the map
-                return false;
-            };
-
-            true
-        }
+        SquareDirection::West | SquareDirection::East => true,
         _ => false,
     }
 }
@@ -0,0 +1,240 @@
+use bevy::{math::Vec3Swizzles, prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_ecs_tilemap::tiles::TilePos;
+use rand::{seq::IteratorRandom, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    item::{spawn_item, ItemDefinitions},
+    main_state::MainState,
+    terrain::{GeneratedTerrain, TerrainChunk, TerrainData},
+    terrain_settings::TerrainSettings,
+};
+
+pub struct PlantPlugin;
+
+impl Plugin for PlantPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Plant>()
+            .register_type::<Ripe>()
+            .add_event::<PlantHarvestedEvent>()
+            .add_systems(
+                Update,
+                spawn_wild_plants.run_if(resource_exists::<TerrainSettings>()),
+            )
+            .add_systems(
+                Update,
+                (
+                    grow_plants,
+                    harvest_plants.run_if(resource_exists::<ItemDefinitions>()),
+                    color_ripe_plants,
+                )
+                    .chain()
+                    .run_if(in_state(MainState::Game)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum PlantKind {
+    /// Grows wild on the surface.
+    BerryBush,
+    /// Grows on farm plots.
+    Potato,
+}
+
+impl PlantKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlantKind::BerryBush => "Berry bush",
+            PlantKind::Potato => "Potato plant",
+        }
+    }
+
+    /// Name of the item the plant yields when harvested.
+    pub fn produce(&self) -> &'static str {
+        match self {
+            PlantKind::BerryBush => "Berries",
+            PlantKind::Potato => "Potato",
+        }
+    }
+
+    pub fn yield_count(&self) -> u32 {
+        match self {
+            PlantKind::BerryBush => 2,
+            PlantKind::Potato => 3,
+        }
+    }
+
+    /// Seconds the plant takes to grow from freshly planted or harvested to ripe.
+    pub fn grow_seconds(&self) -> f32 {
+        match self {
+            PlantKind::BerryBush => 240.,
+            PlantKind::Potato => 180.,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        match self {
+            PlantKind::BerryBush => Vec2::new(12., 10.),
+            PlantKind::Potato => Vec2::new(8., 8.),
+        }
+    }
+
+    fn color(&self, ripe: bool) -> Color {
+        match (self, ripe) {
+            (PlantKind::BerryBush, false) => Color::rgb(0.15, 0.4, 0.1),
+            (PlantKind::BerryBush, true) => Color::rgb(0.6, 0.1, 0.3),
+            (PlantKind::Potato, false) => Color::rgb(0.3, 0.5, 0.15),
+            (PlantKind::Potato, true) => Color::rgb(0.7, 0.6, 0.3),
+        }
+    }
+}
+
+/// A plant growing towards its next harvest, `growth` goes from 0 to 1.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct Plant {
+    pub kind: PlantKind,
+    pub growth: f32,
+}
+
+/// Marks plants that are fully grown and can be harvested.
+#[derive(Component, Debug, Default, Reflect)]
+pub struct Ripe;
+
+#[derive(Event)]
+pub struct PlantHarvestedEvent {
+    pub plant: Entity,
+    pub worker: Entity,
+}
+
+pub const PLANT_LAYER_Z: f32 = 1.5;
+/// Wild plants spawned on every newly generated chunk the surface runs through.
+pub const WILD_PLANT_COUNT: usize = 12;
+
+/// Spawns wild plants on the surface of newly generated chunks.
+fn spawn_wild_plants(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    terrain_settings: Res<TerrainSettings>,
+    new_chunk_query: Query<(&TerrainChunk, &TerrainData), Added<GeneratedTerrain>>,
+) {
+    for (chunk, terrain_data) in &new_chunk_query {
+        // Offset from the seed of the trees, so plants don't all grow in the same spots
+        let mut rng = Xoshiro256StarStar::seed_from_u64(terrain_settings.chunk_seed(chunk.0) + 1);
+        let kind = PlantKind::BerryBush;
+        // Trees grow on every third column, plants in between them
+        let plant_positions = (0..terrain_settings.width)
+            .filter_map(|x| {
+                let surface_tile = terrain_data.surface_tile(x)?;
+                let tile_pos = terrain_settings
+                    .chunk_to_tile(chunk.0, TilePos::new(surface_tile.x, surface_tile.y));
+                let position = terrain_settings.tile_to_world(tile_pos)
+                    + Vec2::new(0., (terrain_settings.cell_size + kind.size().y) / 2.);
+                (tile_pos.x.rem_euclid(3) == 1).then_some(position)
+            })
+            .choose_multiple(&mut rng, WILD_PLANT_COUNT);
+        for position in plant_positions {
+            spawn_plant(
+                &mut commands,
+                kind,
+                rng.gen_range(0.0..=1.0),
+                position.extend(PLANT_LAYER_Z),
+                &mut materials,
+                &mut meshes,
+            );
+        }
+    }
+}
+
+/// Spawns a plant centered on `translation`, relative to its parent if it gets one.
+pub fn spawn_plant(
+    commands: &mut Commands,
+    kind: PlantKind,
+    growth: f32,
+    translation: Vec3,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+) -> Entity {
+    let ripe = growth >= 1.;
+    let mut plant_commands = commands.spawn((
+        Plant {
+            kind,
+            growth: growth.min(1.),
+        },
+        Name::new(kind.name()),
+        MaterialMesh2dBundle {
+            transform: Transform::from_translation(translation),
+            material: materials.add(kind.color(ripe).into()),
+            mesh: meshes.add(Mesh::from(shape::Quad::new(kind.size()))).into(),
+            ..default()
+        },
+    ));
+    if ripe {
+        plant_commands.insert(Ripe);
+    }
+    plant_commands.id()
+}
+
+fn grow_plants(
+    mut commands: Commands,
+    mut plant_query: Query<(Entity, &mut Plant), Without<Ripe>>,
+    time: Res<Time>,
+) {
+    for (plant_entity, mut plant) in &mut plant_query {
+        plant.growth = (plant.growth + time.delta_seconds() / plant.kind.grow_seconds()).min(1.);
+        if plant.growth >= 1. {
+            debug!(plant = ?plant_entity, kind = plant.kind.name(), "Plant ripe");
+            commands.entity(plant_entity).insert(Ripe);
+        }
+    }
+}
+
+/// Drops the produce of harvested plants at their foot and lets them grow again.
+fn harvest_plants(
+    mut commands: Commands,
+    mut plant_harvested_events: EventReader<PlantHarvestedEvent>,
+    mut plant_query: Query<(&mut Plant, &GlobalTransform), With<Ripe>>,
+    item_definitions: Res<ItemDefinitions>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for event in plant_harvested_events.iter() {
+        let Ok((mut plant, transform)) = plant_query.get_mut(event.plant) else { continue; };
+        let Some(definition) = item_definitions.get(plant.kind.produce()) else {
+            error!("Item {} is not defined", plant.kind.produce());
+            continue;
+        };
+        let ground = transform.translation().xy() - Vec2::new(0., plant.kind.size().y / 2.);
+        let produce_entity = spawn_item(
+            &mut commands,
+            definition,
+            plant.kind.yield_count(),
+            ground + Vec2::new(0., definition.size.y / 2.),
+            &mut materials,
+            &mut meshes,
+        );
+        info!(plant = ?event.plant, worker = ?event.worker, produce = ?produce_entity, "Plant harvested");
+        plant.growth = 0.;
+        commands.entity(event.plant).remove::<Ripe>();
+    }
+}
+
+fn color_ripe_plants(
+    plant_query: Query<(&Plant, &Handle<ColorMaterial>, Option<&Ripe>)>,
+    ripened_query: Query<Entity, Added<Ripe>>,
+    mut harvested: RemovedComponents<Ripe>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let changed = harvested
+        .iter()
+        .chain(ripened_query.iter())
+        .collect::<Vec<_>>();
+    for plant_entity in changed {
+        let Ok((plant, material, opt_ripe)) = plant_query.get(plant_entity) else { continue; };
+        if let Some(material) = materials.get_mut(material) {
+            material.color = plant.kind.color(opt_ripe.is_some());
+        }
+    }
+}
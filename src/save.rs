@@ -0,0 +1,940 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    ecs::system::SystemParam,
+    math::Vec3Swizzles,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_ecs_tilemap::tiles::TileStorage;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::work::JobTypes,
+    climbable::ClimbableMap,
+    dwarf::{spawn_dwarf, Attributes, Dwarf, DWARF_INVENTORY_SLOTS, DWARF_MAX_HEALTH, DWARF_SIZE},
+    equipment::Equipment,
+    health::Health,
+    inventory::Inventory,
+    item::{spawn_item, Item, ItemDefinitions},
+    labor::{
+        build_structure::{
+            BuildingMaterialsNeeded, ConstructionCompletedEvent, Ghost, Structure,
+            UnderConstruction,
+        },
+        chop_tree::{spawn_felling_job, FellingJob},
+        craft::{spawn_craft_order, CraftOrder, Crafted},
+        dig_tile::{spawn_dig_job, DigJob},
+        farm::{spawn_farm_plot, FarmPlot},
+        job::{EnabledLabors, Job, JobPriority, Labor, LaborPriorities, Prerequisites},
+        stockpile::{spawn_stockpile, Stockpile},
+    },
+    main_state::MainState,
+    needs::Needs,
+    plant::{spawn_plant, Plant, PlantKind, PLANT_LAYER_Z},
+    skills::Skills,
+    structure::{spawn_structure, PlacedStructure, StructureDefinitions},
+    terrain::{
+        HeldTilePlacements, PlaceTileEvent, Terrain, TerrainBundle, TerrainChunk,
+        TerrainChunkCache, TerrainData, TerrainParam, TerrainSet, TileHealth,
+    },
+    terrain_settings::TerrainSettings,
+    tree::{spawn_tree, Tree},
+};
+
+/// Version of the save file format. Bump this whenever `SaveGame` changes shape.
+pub const SAVE_VERSION: u32 = 14;
+
+pub const QUICKSAVE_PATH: &str = "savegame.ron";
+pub const AUTOSAVE_PATH: &str = "autosave.ron";
+const AUTOSAVE_INTERVAL_SECONDS: f32 = 300.;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveGameEvent>()
+            .insert_resource(AutosaveTimer(Timer::from_seconds(
+                AUTOSAVE_INTERVAL_SECONDS,
+                TimerMode::Repeating,
+            )))
+            .add_systems(
+                Startup,
+                read_save_file.run_if(resource_exists::<LoadGameRequest>()),
+            )
+            .add_systems(OnEnter(MainState::LoadGame), spawn_saved_terrain)
+            .add_systems(
+                Update,
+                saved_terrain_spawned.run_if(in_state(MainState::LoadGame)),
+            )
+            .add_systems(
+                OnEnter(MainState::Game),
+                restore_saved_entities
+                    .after(TerrainSet)
+                    .run_if(resource_exists::<LoadedSave>()),
+            )
+            .add_systems(
+                Update,
+                (quicksave, autosave, save_game)
+                    .chain()
+                    .run_if(in_state(MainState::Game)),
+            );
+    }
+}
+
+/// Path of a save file to restore instead of generating a new world.
+#[derive(Resource)]
+pub struct LoadGameRequest(pub PathBuf);
+
+/// The save file the current game was restored from.
+#[derive(Resource)]
+pub struct LoadedSave(SaveGame);
+
+#[derive(Event)]
+pub struct SaveGameEvent {
+    pub path: PathBuf,
+}
+
+#[derive(Resource)]
+struct AutosaveTimer(Timer);
+
+/// Serialized colony state.
+///
+/// Job assignments are not stored: designations are restored as unassigned jobs and the workers'
+/// thinkers pick them up again. Haul requests are recreated by construction sites that still need
+/// building materials and by stockpiles with room for loose items, construction jobs by sites that
+/// have all of their materials, harvests by ripe plants. Tiles waiting to be placed are stored
+/// and placed again once the game is restored.
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub terrain: SavedTerrain,
+    pub climbable: Vec<IVec2>,
+    pub dwarves: Vec<SavedDwarf>,
+    pub trees: Vec<SavedTree>,
+    /// Plants growing wild, crops are stored with their farm plot.
+    pub plants: Vec<SavedPlant>,
+    pub farm_plots: Vec<SavedFarmPlot>,
+    pub items: Vec<SavedItem>,
+    pub structures: Vec<SavedStructure>,
+    pub stockpiles: Vec<SavedStockpile>,
+    /// Designated tiles and the priority they were designated with.
+    pub dig_designations: Vec<(IVec2, Option<u32>)>,
+    pub labor_priorities: Vec<(Labor, u32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTerrain {
+    /// Width of a chunk in tiles.
+    pub width: u32,
+    /// Height of a chunk in tiles.
+    pub height: u32,
+    pub cell_size: f32,
+    pub seed: u32,
+    /// Loaded chunks as well as unloaded chunks kept in the [`TerrainChunkCache`]. Chunks that
+    /// were never generated are generated again from the seed.
+    pub chunks: Vec<SavedChunk>,
+    pub tile_health: Vec<(IVec2, u32)>,
+    /// Tiles waiting for whatever overlaps them to move out of the way, with their material.
+    pub held_placements: Vec<(IVec2, u16)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedChunk {
+    pub region: IVec2,
+    pub tiles: Vec<u16>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedDwarf {
+    pub name: String,
+    pub position: Vec2,
+    pub inventory: Vec<(String, u32)>,
+    pub labors: Vec<Labor>,
+    pub health: u32,
+    pub needs: Needs,
+    pub attributes: Attributes,
+    pub skills: Skills,
+    pub equipment: Equipment,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTree {
+    pub position: Vec2,
+    pub health: u32,
+    pub designated: bool,
+    pub priority: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedPlant {
+    pub kind: PlantKind,
+    pub position: Vec2,
+    pub growth: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedFarmPlot {
+    pub tile: IVec2,
+    pub crop_growth: f32,
+}
+
+/// A stack of items lying in the world.
+#[derive(Serialize, Deserialize)]
+pub struct SavedItem {
+    pub name: String,
+    pub count: u32,
+    pub position: Vec2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedStructure {
+    pub name: String,
+    pub origin: IVec2,
+    pub construction: SavedConstruction,
+    pub priority: Option<u32>,
+    pub craft_orders: Vec<SavedCraftOrder>,
+}
+
+/// An item ordered at a workshop that isn't crafted yet.
+#[derive(Serialize, Deserialize)]
+pub struct SavedCraftOrder {
+    pub item: String,
+    pub materials_needed: Vec<(String, u32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedStockpile {
+    pub min: IVec2,
+    pub max: IVec2,
+    pub allowed_items: Option<Vec<String>>,
+    pub priority: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum SavedConstruction {
+    UnderConstruction {
+        progress: u32,
+        materials_needed: Vec<(String, u32)>,
+    },
+    Completed,
+}
+
+/// Only the version is read first, so files of an older format are rejected with a clear error
+/// instead of a parse failure somewhere in the body.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    UnsupportedVersion(u32),
+    TerrainSize,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "io error: {}", err),
+            SaveError::Parse(err) => write!(f, "parse error: {}", err),
+            SaveError::Serialize(err) => write!(f, "serialize error: {}", err),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported save version {} (expected {})",
+                version, SAVE_VERSION
+            ),
+            SaveError::TerrainSize => write!(f, "terrain tiles do not match terrain size"),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        SaveError::Parse(err)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(err: ron::Error) -> Self {
+        SaveError::Serialize(err)
+    }
+}
+
+pub fn read_save_game(path: &Path) -> Result<SaveGame, SaveError> {
+    let contents = fs::read_to_string(path)?;
+    let header: SaveHeader = ron::from_str(&contents)?;
+    if header.version != SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion(header.version));
+    }
+    let save_game: SaveGame = ron::from_str(&contents)?;
+    let terrain = &save_game.terrain;
+    let chunk_tile_count = terrain.width as usize * terrain.height as usize;
+    if terrain
+        .chunks
+        .iter()
+        .any(|chunk| chunk.tiles.len() != chunk_tile_count)
+    {
+        return Err(SaveError::TerrainSize);
+    }
+    Ok(save_game)
+}
+
+pub fn write_save_game(path: &Path, save_game: &SaveGame) -> Result<(), SaveError> {
+    let contents = ron::ser::to_string_pretty(save_game, ron::ser::PrettyConfig::default())?;
+    // Write to a temporary file first so a crash while saving never truncates the previous save
+    let tmp_path = path.with_extension("ron.tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Returns the save file passed with `--load <path>` on the command line.
+pub fn load_game_argument() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--load" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+fn read_save_file(mut commands: Commands, load_game_request: Res<LoadGameRequest>) {
+    match read_save_game(&load_game_request.0) {
+        Ok(save_game) => {
+            info!(path = ?load_game_request.0, "Save file read");
+            commands.insert_resource(LoadedSave(save_game));
+        }
+        Err(err) => {
+            error!(path = ?load_game_request.0, "Failed to read save file, generating a new world instead: {}", err);
+        }
+    }
+}
+
+fn spawn_saved_terrain(
+    mut commands: Commands,
+    loaded_save: Res<LoadedSave>,
+    mut terrain_settings: ResMut<TerrainSettings>,
+    mut climbable_map: ResMut<ClimbableMap>,
+    mut chunk_cache: ResMut<TerrainChunkCache>,
+) {
+    info!("Spawning saved terrain");
+    let saved_terrain = &loaded_save.0.terrain;
+    terrain_settings.width = saved_terrain.width;
+    terrain_settings.height = saved_terrain.height;
+    terrain_settings.cell_size = saved_terrain.cell_size;
+    terrain_settings.seed = saved_terrain.seed;
+
+    *climbable_map = ClimbableMap::default();
+    for tile_pos in &loaded_save.0.climbable {
+        climbable_map.mark_climbable(*tile_pos);
+    }
+
+    // Every saved chunk is spawned so the saved entities find their tiles, chunks far from the
+    // colonists are streamed out again once the game runs
+    chunk_cache.clear();
+    for saved_chunk in &saved_terrain.chunks {
+        let tiles = Array2::from_shape_vec(
+            (saved_terrain.width as usize, saved_terrain.height as usize),
+            saved_chunk.tiles.clone(),
+        )
+        .expect("Saved terrain size should be validated when reading the save file");
+
+        commands.spawn((
+            TerrainBundle {
+                chunk: TerrainChunk(saved_chunk.region),
+                ..default()
+            },
+            TerrainData::new(tiles),
+        ));
+    }
+}
+
+fn saved_terrain_spawned(
+    mut next_state: ResMut<NextState<MainState>>,
+    terrain_query: Query<Entity, (Added<TileStorage>, With<Terrain>)>,
+) {
+    if !terrain_query.is_empty() {
+        info!("Saved terrain spawned");
+        next_state.set(MainState::Game);
+    }
+}
+
+fn restore_saved_entities(
+    mut commands: Commands,
+    loaded_save: Res<LoadedSave>,
+    terrain: TerrainParam,
+    terrain_settings: Res<TerrainSettings>,
+    asset_server: Res<AssetServer>,
+    item_definitions: Res<ItemDefinitions>,
+    structure_definitions: Res<StructureDefinitions>,
+    job_types: Res<JobTypes>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut construction_completed_events: EventWriter<ConstructionCompletedEvent>,
+    mut labor_priorities: ResMut<LaborPriorities>,
+    mut place_tile_events: EventWriter<PlaceTileEvent>,
+) {
+    let save_game = &loaded_save.0;
+
+    labor_priorities.0 = save_game.labor_priorities.iter().copied().collect();
+
+    for (tile_pos, health) in &save_game.terrain.tile_health {
+        if let Some(tile_entity) = terrain.get_tile_entity(*tile_pos) {
+            commands.entity(tile_entity).insert(TileHealth(*health));
+        }
+    }
+    for &(tile_pos, material) in &save_game.terrain.held_placements {
+        place_tile_events.send(PlaceTileEvent { tile_pos, material });
+    }
+
+    let mut dig_jobs = HashMap::new();
+    for &(tile_pos, priority) in &save_game.dig_designations {
+        if let Some((tile_entity, tile_translation)) = terrain
+            .get_tile_entity(tile_pos)
+            .zip(terrain.tile_to_global_pos(tile_pos))
+        {
+            let dig_job = spawn_dig_job(
+                &mut commands,
+                tile_entity,
+                tile_translation,
+                priority.map(JobPriority),
+            );
+            dig_jobs.insert(tile_pos, dig_job);
+        }
+    }
+
+    for dwarf in &save_game.dwarves {
+        let dwarf_entity = spawn_dwarf(
+            &mut commands,
+            &dwarf.name,
+            dwarf.position.x,
+            dwarf.position.y,
+            dwarf.attributes,
+            &job_types,
+            &mut materials,
+            &mut meshes,
+        );
+        commands.entity(dwarf_entity).insert((
+            EnabledLabors(dwarf.labors.iter().copied().collect()),
+            Health(dwarf.health),
+            dwarf.needs,
+            dwarf.skills.clone(),
+            dwarf.equipment.clone(),
+        ));
+        if !dwarf.inventory.is_empty() {
+            let mut inventory =
+                Inventory::new(DWARF_INVENTORY_SLOTS, dwarf.attributes.max_carry_weight());
+            for (name, count) in &dwarf.inventory {
+                inventory.add(&item_definitions, &Name::new(name.clone()), *count);
+            }
+            commands.entity(dwarf_entity).insert(inventory);
+        }
+    }
+
+    for tree in &save_game.trees {
+        let tree_entity = spawn_tree(
+            &mut commands,
+            tree.position.x,
+            tree.position.y,
+            &mut materials,
+            &mut meshes,
+        );
+        commands.entity(tree_entity).insert(Health(tree.health));
+        if tree.designated {
+            spawn_felling_job(
+                &mut commands,
+                tree_entity,
+                tree.position,
+                tree.priority.map(JobPriority),
+            );
+        }
+    }
+
+    for item in &save_game.items {
+        let Some(definition) = item_definitions.get(&item.name) else {
+            error!(item = item.name.as_str(), "Unknown saved item");
+            continue;
+        };
+        spawn_item(
+            &mut commands,
+            definition,
+            item.count,
+            item.position,
+            &mut materials,
+            &mut meshes,
+        );
+    }
+
+    for structure in &save_game.structures {
+        let Some(definition) = structure_definitions.get(&structure.name) else {
+            error!(
+                structure = structure.name.as_str(),
+                "Unknown saved structure"
+            );
+            continue;
+        };
+        let structure_entity = spawn_structure(
+            &mut commands,
+            &asset_server,
+            &terrain_settings,
+            definition,
+            structure.origin,
+        );
+        if let Some(priority) = structure.priority {
+            commands
+                .entity(structure_entity)
+                .insert(JobPriority(priority));
+        }
+        for order in &structure.craft_orders {
+            spawn_craft_order(
+                &mut commands,
+                structure_entity,
+                order.item.clone(),
+                saved_materials_needed(&order.materials_needed),
+            );
+        }
+        match &structure.construction {
+            SavedConstruction::UnderConstruction {
+                progress,
+                materials_needed,
+            } => {
+                let mut under_construction = UnderConstruction::default();
+                under_construction.add_progress(*progress);
+                commands
+                    .entity(structure_entity)
+                    .insert((under_construction, saved_materials_needed(materials_needed)));
+                // Sites placed over solid tiles wait for them to be dug out again
+                let prerequisites: HashSet<Entity> = definition
+                    .tiles_to_dig(structure.origin, &terrain)
+                    .iter()
+                    .filter_map(|tile_pos| dig_jobs.get(tile_pos).copied())
+                    .collect();
+                if !prerequisites.is_empty() {
+                    commands
+                        .entity(structure_entity)
+                        .insert(Prerequisites(prerequisites));
+                }
+            }
+            SavedConstruction::Completed => {
+                commands.entity(structure_entity).insert(Structure);
+                construction_completed_events.send(ConstructionCompletedEvent {
+                    construction_site: structure_entity,
+                });
+            }
+        }
+    }
+
+    for plant in &save_game.plants {
+        spawn_plant(
+            &mut commands,
+            plant.kind,
+            plant.growth,
+            plant.position.extend(PLANT_LAYER_Z),
+            &mut materials,
+            &mut meshes,
+        );
+    }
+
+    for farm_plot in &save_game.farm_plots {
+        spawn_farm_plot(
+            &mut commands,
+            farm_plot.tile,
+            farm_plot.crop_growth,
+            &terrain_settings,
+            &mut materials,
+            &mut meshes,
+        );
+    }
+
+    for saved_stockpile in &save_game.stockpiles {
+        let stockpile = Stockpile {
+            min: saved_stockpile.min,
+            max: saved_stockpile.max,
+            allowed_items: saved_stockpile
+                .allowed_items
+                .as_ref()
+                .map(|allowed_items| allowed_items.iter().cloned().map(Name::new).collect()),
+            priority: saved_stockpile.priority,
+        };
+        spawn_stockpile(
+            &mut commands,
+            stockpile,
+            &terrain_settings,
+            &mut materials,
+            &mut meshes,
+        );
+    }
+
+    info!(
+        dwarves = save_game.dwarves.len(),
+        trees = save_game.trees.len(),
+        "Restored saved game"
+    );
+}
+
+fn quicksave(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut save_game_events: EventWriter<SaveGameEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        save_game_events.send(SaveGameEvent {
+            path: QUICKSAVE_PATH.into(),
+        });
+    }
+}
+
+fn autosave(
+    time: Res<Time>,
+    mut autosave_timer: ResMut<AutosaveTimer>,
+    mut save_game_events: EventWriter<SaveGameEvent>,
+) {
+    if autosave_timer.0.tick(time.delta()).just_finished() {
+        save_game_events.send(SaveGameEvent {
+            path: AUTOSAVE_PATH.into(),
+        });
+    }
+}
+
+#[derive(SystemParam)]
+struct SaveGameParam<'w, 's> {
+    terrain_settings: Res<'w, TerrainSettings>,
+    terrain: TerrainParam<'w, 's>,
+    chunk_cache: Res<'w, TerrainChunkCache>,
+    held_placements: Res<'w, HeldTilePlacements>,
+    climbable_map: Res<'w, ClimbableMap>,
+    tile_health_query: Query<'w, 's, (Entity, &'static TileHealth)>,
+    dwarf_query: Query<
+        'w,
+        's,
+        (
+            &'static Name,
+            &'static GlobalTransform,
+            Option<&'static Inventory>,
+            Option<&'static EnabledLabors>,
+            Option<&'static Health>,
+            Option<&'static Needs>,
+            Option<&'static Attributes>,
+            Option<&'static Skills>,
+            Option<&'static Equipment>,
+        ),
+        With<Dwarf>,
+    >,
+    tree_query: Query<'w, 's, (Entity, &'static GlobalTransform, &'static Health), With<Tree>>,
+    plant_query: Query<
+        'w,
+        's,
+        (
+            &'static Plant,
+            &'static GlobalTransform,
+            Option<&'static Parent>,
+        ),
+    >,
+    farm_plot_query: Query<'w, 's, (&'static FarmPlot, Option<&'static Children>)>,
+    item_query: Query<'w, 's, (&'static Name, &'static Item, &'static GlobalTransform)>,
+    structure_query: Query<
+        'w,
+        's,
+        (
+            &'static PlacedStructure,
+            Option<&'static UnderConstruction>,
+            Option<&'static BuildingMaterialsNeeded>,
+            Option<&'static JobPriority>,
+            Option<&'static Children>,
+        ),
+        Without<Ghost>,
+    >,
+    craft_order_query: Query<
+        'w,
+        's,
+        (
+            &'static CraftOrder,
+            Option<&'static BuildingMaterialsNeeded>,
+        ),
+        Without<Crafted>,
+    >,
+    stockpile_query: Query<'w, 's, &'static Stockpile>,
+    dig_job_query: Query<'w, 's, (&'static DigJob, Option<&'static JobPriority>), With<Job>>,
+    felling_job_query:
+        Query<'w, 's, (&'static FellingJob, Option<&'static JobPriority>), With<Job>>,
+    labor_priorities: Res<'w, LaborPriorities>,
+}
+
+impl SaveGameParam<'_, '_> {
+    fn save_game(&self) -> Option<SaveGame> {
+        if self.terrain.chunk_query.is_empty() {
+            return None;
+        }
+
+        let loaded_chunks = self
+            .terrain
+            .chunk_query
+            .iter()
+            .map(|(_, chunk, terrain_data, _)| (&chunk.0, terrain_data));
+        let chunks = loaded_chunks
+            .chain(self.chunk_cache.iter())
+            .map(|(region, terrain_data)| SavedChunk {
+                region: *region,
+                tiles: terrain_data.tiles().iter().copied().collect(),
+            })
+            .collect();
+
+        let terrain = SavedTerrain {
+            width: self.terrain_settings.width,
+            height: self.terrain_settings.height,
+            cell_size: self.terrain_settings.cell_size,
+            seed: self.terrain_settings.seed,
+            chunks,
+            tile_health: self
+                .tile_health_query
+                .iter()
+                .filter_map(|(tile_entity, tile_health)| {
+                    let tile_pos = self.terrain.get_entity_tile_pos(tile_entity)?;
+                    Some((tile_pos, tile_health.0))
+                })
+                .chain(
+                    self.chunk_cache
+                        .tile_health()
+                        .map(|(region, tile_pos, health)| {
+                            (
+                                self.terrain_settings.chunk_to_tile(region, tile_pos),
+                                health,
+                            )
+                        }),
+                )
+                .collect(),
+            held_placements: self
+                .held_placements
+                .iter()
+                .map(|placement| (placement.tile_pos, placement.material))
+                .collect(),
+        };
+
+        let dwarves = self
+            .dwarf_query
+            .iter()
+            .map(
+                |(
+                    name,
+                    transform,
+                    opt_inventory,
+                    opt_enabled_labors,
+                    opt_health,
+                    opt_needs,
+                    opt_attributes,
+                    opt_skills,
+                    opt_equipment,
+                )| {
+                    SavedDwarf {
+                        name: name.as_str().to_string(),
+                        position: transform.translation().xy() - Vec2::new(0., DWARF_SIZE.y / 2.),
+                        inventory: opt_inventory
+                            .map(|inventory| {
+                                inventory
+                                    .stacks()
+                                    .iter()
+                                    .map(|stack| (stack.item.as_str().to_string(), stack.count))
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                        labors: opt_enabled_labors.map_or(Labor::ALL.to_vec(), |enabled_labors| {
+                            enabled_labors.0.iter().copied().collect()
+                        }),
+                        health: opt_health.map_or(DWARF_MAX_HEALTH, |health| health.0),
+                        needs: opt_needs.copied().unwrap_or_default(),
+                        attributes: opt_attributes.copied().unwrap_or_default(),
+                        skills: opt_skills.cloned().unwrap_or_default(),
+                        equipment: opt_equipment.cloned().unwrap_or_default(),
+                    }
+                },
+            )
+            .collect();
+
+        let trees = self
+            .tree_query
+            .iter()
+            .map(|(tree_entity, transform, health)| {
+                let felling_job = self
+                    .felling_job_query
+                    .iter()
+                    .find(|(felling_job, _)| felling_job.0 == tree_entity);
+                SavedTree {
+                    position: transform.translation().xy(),
+                    health: health.0,
+                    designated: felling_job.is_some(),
+                    priority: felling_job
+                        .and_then(|(_, opt_priority)| opt_priority)
+                        .map(|priority| priority.0),
+                }
+            })
+            .collect();
+
+        let plants = self
+            .plant_query
+            .iter()
+            .filter(|(_, _, opt_parent)| opt_parent.is_none())
+            .map(|(plant, transform, _)| SavedPlant {
+                kind: plant.kind,
+                position: transform.translation().xy(),
+                growth: plant.growth,
+            })
+            .collect();
+
+        let farm_plots = self
+            .farm_plot_query
+            .iter()
+            .map(|(farm_plot, opt_children)| SavedFarmPlot {
+                tile: farm_plot.0,
+                crop_growth: opt_children
+                    .into_iter()
+                    .flatten()
+                    .find_map(|child| self.plant_query.get(*child).ok())
+                    .map_or(0., |(crop, _, _)| crop.growth),
+            })
+            .collect();
+
+        let structures = self
+            .structure_query
+            .iter()
+            .map(
+                |(
+                    structure,
+                    opt_under_construction,
+                    opt_materials_needed,
+                    opt_priority,
+                    opt_children,
+                )| {
+                    SavedStructure {
+                        name: structure.name.clone(),
+                        origin: structure.origin,
+                        construction: saved_construction(
+                            opt_under_construction,
+                            opt_materials_needed,
+                        ),
+                        priority: opt_priority.map(|priority| priority.0),
+                        craft_orders: opt_children
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|child| self.craft_order_query.get(*child).ok())
+                            .map(|(order, opt_materials_needed)| SavedCraftOrder {
+                                item: order.item.clone(),
+                                materials_needed: saved_materials(opt_materials_needed),
+                            })
+                            .collect(),
+                    }
+                },
+            )
+            .collect();
+
+        Some(SaveGame {
+            version: SAVE_VERSION,
+            terrain,
+            climbable: self.climbable_map.iter().copied().collect(),
+            dwarves,
+            trees,
+            plants,
+            farm_plots,
+            items: self
+                .item_query
+                .iter()
+                .map(|(name, item, transform)| SavedItem {
+                    name: name.as_str().to_string(),
+                    count: item.count,
+                    position: transform.translation().xy(),
+                })
+                .collect(),
+            structures,
+            stockpiles: self
+                .stockpile_query
+                .iter()
+                .map(|stockpile| SavedStockpile {
+                    min: stockpile.min,
+                    max: stockpile.max,
+                    allowed_items: stockpile.allowed_items.as_ref().map(|allowed_items| {
+                        allowed_items
+                            .iter()
+                            .map(|name| name.as_str().to_string())
+                            .collect()
+                    }),
+                    priority: stockpile.priority,
+                })
+                .collect(),
+            dig_designations: self
+                .dig_job_query
+                .iter()
+                .filter_map(|(dig_job, opt_priority)| {
+                    let tile_pos = self.terrain.get_entity_tile_pos(dig_job.0)?;
+                    Some((tile_pos, opt_priority.map(|priority| priority.0)))
+                })
+                .collect(),
+            labor_priorities: self
+                .labor_priorities
+                .0
+                .iter()
+                .map(|(labor, priority)| (*labor, *priority))
+                .collect(),
+        })
+    }
+}
+
+fn saved_construction(
+    opt_under_construction: Option<&UnderConstruction>,
+    opt_materials_needed: Option<&BuildingMaterialsNeeded>,
+) -> SavedConstruction {
+    let Some(under_construction) = opt_under_construction else {
+        return SavedConstruction::Completed;
+    };
+    SavedConstruction::UnderConstruction {
+        progress: under_construction.progress(),
+        materials_needed: saved_materials(opt_materials_needed),
+    }
+}
+
+/// Materials still to be delivered, none once all of them were.
+fn saved_materials(opt_materials_needed: Option<&BuildingMaterialsNeeded>) -> Vec<(String, u32)> {
+    opt_materials_needed
+        .map(|materials_needed| {
+            materials_needed
+                .resources_needed()
+                .iter()
+                .map(|(name, amount)| (name.as_str().to_string(), *amount))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn saved_materials_needed(materials_needed: &[(String, u32)]) -> BuildingMaterialsNeeded {
+    BuildingMaterialsNeeded::new(
+        materials_needed
+            .iter()
+            .map(|(name, amount)| (Name::new(name.clone()), *amount))
+            .collect(),
+    )
+}
+
+fn save_game(mut save_game_events: EventReader<SaveGameEvent>, save_game_param: SaveGameParam) {
+    for event in save_game_events.iter() {
+        let Some(save_game) = save_game_param.save_game() else {
+            error!("No terrain to save");
+            continue;
+        };
+        match write_save_game(&event.path, &save_game) {
+            Ok(()) => info!(path = ?event.path, "Game saved"),
+            Err(err) => error!(path = ?event.path, "Failed to save game: {}", err),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{PluginGroupBuilder, ScheduleRunnerPlugin},
+    input::InputPlugin,
+    log::LogPlugin,
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_rapier2d::prelude::{NoUserData, RapierConfiguration, RapierPhysicsPlugin, TimestepMode};
+use big_brain::BigBrainPlugin;
+
+use crate::{
+    actions::ActionsPlugin, building_material::BuildingMaterialPlugin, climbable::ClimbablePlugin,
+    dwarf::DwarfPlugin, embark::EmbarkPlugin, equipment::EquipmentPlugin, gravity::GravityPlugin,
+    health::HealthPlugin, hit::HitPlugin, inventory::InventoryPlugin, item::ItemPlugin,
+    labor::LaborPlugin, load::LoadPlugin, main_state::MainStatePlugin, material::MaterialPlugin,
+    movement::MovementPlugin, navigation::NavigationPlugin, needs::NeedsPlugin,
+    path_request::PathRequestPlugin, plant::PlantPlugin, save::SavePlugin, skills::SkillsPlugin,
+    structure::StructurePlugin, terrain::TerrainPlugin, terrain_settings::TerrainSettingsPlugin,
+    tree::TreePlugin, world_generation::WorldGenerationPlugin,
+};
+
+/// Duration of a single simulation step when running headless.
+pub const SIMULATION_TIMESTEP: Duration = Duration::from_micros(16_667);
+
+/// The colony simulation: terrain, pathfinding, physics, big-brain thinkers and labor.
+///
+/// Contains no windowing, rendering or UI plugins. The graphical client adds those on top, a
+/// headless app adds [`HeadlessPlugin`] instead.
+pub struct SimulationPlugins;
+
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.))
+            .add(BigBrainPlugin::new(PreUpdate))
+            .add(MainStatePlugin)
+            .add(LoadPlugin)
+            .add(MaterialPlugin)
+            .add(ItemPlugin)
+            .add(StructurePlugin)
+            .add(InventoryPlugin)
+            .add(EquipmentPlugin)
+            .add(TerrainSettingsPlugin)
+            .add(TerrainPlugin)
+            .add(WorldGenerationPlugin)
+            .add(SavePlugin)
+            .add(GravityPlugin)
+            .add(HealthPlugin)
+            .add(NeedsPlugin)
+            .add(MovementPlugin)
+            .add(ClimbablePlugin)
+            .add(NavigationPlugin)
+            .add(PathRequestPlugin)
+            .add(HitPlugin)
+            .add(DwarfPlugin)
+            .add(EmbarkPlugin)
+            .add(LaborPlugin)
+            .add(SkillsPlugin)
+            .add(ActionsPlugin)
+            .add(TreePlugin)
+            .add(PlantPlugin)
+            .add(BuildingMaterialPlugin)
+    }
+}
+
+/// Runs the app without a window, stepping the simulation as fast as possible with a fixed
+/// timestep of [`SIMULATION_TIMESTEP`].
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+        ))
+        // Simulation systems spawn meshes and sprites, their asset storage has to exist even
+        // though nothing is ever rendered
+        .add_asset::<Mesh>()
+        .add_asset::<ColorMaterial>()
+        .add_asset::<Image>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(SIMULATION_TIMESTEP))
+        .add_systems(Startup, fixed_physics_timestep);
+    }
+}
+
+fn fixed_physics_timestep(mut rapier_configuration: ResMut<RapierConfiguration>) {
+    rapier_configuration.timestep_mode = TimestepMode::Fixed {
+        dt: SIMULATION_TIMESTEP.as_secs_f32(),
+        substeps: 1,
+    };
+}
+
+/// Returns true when `--headless` was passed on the command line.
+pub fn headless_argument() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--headless")
+}
@@ -0,0 +1,93 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::labor::job::{JobCompletedEvent, Labor};
+
+pub struct SkillsPlugin;
+
+impl Plugin for SkillsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Skills>()
+            .add_systems(Update, gain_experience);
+    }
+}
+
+/// Labors whose work gets faster with skill, workers only gain experience in these.
+///
+/// Skilled haulers walk faster while hauling.
+pub const SKILLED_LABORS: [Labor; 6] = [
+    Labor::Mining,
+    Labor::Woodcutting,
+    Labor::Hauling,
+    Labor::Construction,
+    Labor::Farming,
+    Labor::Crafting,
+];
+
+pub const MAX_SKILL_LEVEL: u32 = 10;
+/// Experience needed to reach level 1, every next level needs that much more than the one
+/// before it.
+pub const FIRST_LEVEL_EXPERIENCE: u32 = 20;
+/// Experience a worker gains in the labor of every job it completes.
+pub const EXPERIENCE_PER_JOB: u32 = 5;
+/// How much faster than an unskilled worker every skill level makes a worker.
+pub const SPEED_PER_SKILL_LEVEL: f32 = 0.15;
+
+/// Total experience needed to reach `level`.
+pub fn experience_for_level(level: u32) -> u32 {
+    FIRST_LEVEL_EXPERIENCE * level * (level + 1) / 2
+}
+
+/// Experience a worker has gathered in each labor, its skill levels follow from it.
+#[derive(Component, Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+pub struct Skills(pub HashMap<Labor, u32>);
+
+impl Skills {
+    pub fn experience(&self, labor: Labor) -> u32 {
+        self.0.get(&labor).copied().unwrap_or_default()
+    }
+
+    pub fn level(&self, labor: Labor) -> u32 {
+        let experience = self.experience(labor);
+        (1..=MAX_SKILL_LEVEL)
+            .take_while(|level| experience >= experience_for_level(*level))
+            .last()
+            .unwrap_or(0)
+    }
+
+    pub fn add_experience(&mut self, labor: Labor, experience: u32) {
+        *self.0.entry(labor).or_default() += experience;
+    }
+
+    /// Multiplies how fast the worker does the work of `labor`, 1 when unskilled.
+    pub fn speed_multiplier(&self, labor: Labor) -> f32 {
+        1. + self.level(labor) as f32 * SPEED_PER_SKILL_LEVEL
+    }
+}
+
+/// Speed multiplier of `worker` for `labor`, workers without [`Skills`] work at unskilled speed.
+pub fn worker_speed(skills_query: &Query<&Skills>, worker: Entity, labor: Labor) -> f32 {
+    skills_query
+        .get(worker)
+        .map_or(1., |skills| skills.speed_multiplier(labor))
+}
+
+fn gain_experience(
+    mut job_completed_events: EventReader<JobCompletedEvent>,
+    labor_query: Query<&Labor>,
+    mut skills_query: Query<&mut Skills>,
+) {
+    for event in job_completed_events.iter() {
+        let Ok(labor) = labor_query.get(event.job_entity) else { continue; };
+        if !SKILLED_LABORS.contains(labor) {
+            continue;
+        }
+        let Ok(mut skills) = skills_query.get_mut(event.worker_entity) else { continue; };
+        let previous_level = skills.level(*labor);
+        skills.add_experience(*labor, EXPERIENCE_PER_JOB);
+        let level = skills.level(*labor);
+        if level > previous_level {
+            info!(worker = ?event.worker_entity, labor = labor.name(), level, "Skill level up");
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::hash::Hash;
+
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    item::ItemDefinitions,
+    labor::stockpile::{
+        NewStockpileSettings, Stockpile, MAX_STOCKPILE_PRIORITY, MIN_STOCKPILE_PRIORITY,
+    },
+};
+
+pub struct StockpilePanelPlugin;
+
+impl Plugin for StockpilePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            stockpile_panel.run_if(resource_exists::<ItemDefinitions>()),
+        );
+    }
+}
+
+/// Priority and allowed items of new stockpiles and of every stockpile.
+fn stockpile_panel(
+    mut contexts: EguiContexts,
+    mut new_stockpile_settings: ResMut<NewStockpileSettings>,
+    mut stockpile_query: Query<(Entity, &mut Stockpile)>,
+    item_definitions: Res<ItemDefinitions>,
+) {
+    egui::Window::new("Stockpiles").show(contexts.ctx_mut(), |ui| {
+        ui.label("New stockpiles");
+        let new_stockpile_settings = &mut *new_stockpile_settings;
+        stockpile_settings(
+            ui,
+            "new stockpile",
+            &mut new_stockpile_settings.allowed_items,
+            &mut new_stockpile_settings.priority,
+            &item_definitions,
+        );
+
+        for (stockpile_entity, mut stockpile) in &mut stockpile_query {
+            ui.separator();
+            ui.label(format!("Stockpile at {}", stockpile.min));
+            // Only touch the stockpile when changed, changing it marks and hauls items again
+            let mut allowed_items = stockpile.allowed_items.clone();
+            let mut priority = stockpile.priority;
+            if stockpile_settings(
+                ui,
+                stockpile_entity,
+                &mut allowed_items,
+                &mut priority,
+                &item_definitions,
+            ) {
+                stockpile.allowed_items = allowed_items;
+                stockpile.priority = priority;
+            }
+        }
+    });
+}
+
+/// Priority and allowed items controls, returns whether any of them changed.
+fn stockpile_settings(
+    ui: &mut egui::Ui,
+    id_source: impl Hash,
+    allowed_items: &mut Option<Vec<Name>>,
+    priority: &mut u32,
+    item_definitions: &ItemDefinitions,
+) -> bool {
+    ui.push_id(id_source, |ui| {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Priority");
+            let drag_value = egui::DragValue::new(priority)
+                .clamp_range(MIN_STOCKPILE_PRIORITY..=MAX_STOCKPILE_PRIORITY);
+            changed |= ui.add(drag_value).changed();
+        });
+
+        let mut all_items = allowed_items.is_none();
+        if ui.checkbox(&mut all_items, "All items").changed() {
+            *allowed_items = if all_items { None } else { Some(Vec::new()) };
+            changed = true;
+        }
+        if let Some(allowed_items) = allowed_items {
+            ui.horizontal_wrapped(|ui| {
+                for definition in &item_definitions.0 {
+                    let item = Name::new(definition.name.clone());
+                    let mut allowed = allowed_items.contains(&item);
+                    if ui
+                        .checkbox(&mut allowed, definition.name.as_str())
+                        .changed()
+                    {
+                        if allowed {
+                            allowed_items.push(item);
+                        } else {
+                            allowed_items.retain(|allowed_item| *allowed_item != item);
+                        }
+                        changed = true;
+                    }
+                }
+            });
+        }
+        changed
+    })
+    .inner
+}
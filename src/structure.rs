@@ -0,0 +1,325 @@
+use bevy::{
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::HashSet,
+};
+use bevy_common_assets::ron::RonAssetPlugin;
+use bevy_rapier2d::prelude::{Collider, CollisionGroups, Group, RigidBody};
+
+use crate::{
+    climbable::{Climbable, CLIMBABLE_COLLISION_GROUP},
+    labor::{
+        build_structure::{
+            BuildingMaterialsNeeded, ConstructionCompletedEvent, ConstructionJob, BUILDING_LAYER_Z,
+            CONSTRUCTION_COLLISION_GROUP,
+        },
+        craft::Workshop,
+    },
+    material::MaterialProperties,
+    terrain::{PlaceTileEvent, TerrainTiles},
+    terrain_settings::TerrainSettings,
+};
+
+pub struct StructurePlugin;
+
+impl Plugin for StructurePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<Structures>::new(&["structures.ron"]))
+            .add_asset::<Structures>()
+            .add_state::<StructuresState>()
+            .register_type::<PlacedStructure>()
+            .register_type::<ReplacedByTiles>()
+            .add_systems(OnEnter(StructuresState::Loading), load_structures)
+            .add_systems(
+                Update,
+                setup_structures.run_if(in_state(StructuresState::Loading)),
+            )
+            .add_systems(
+                Update,
+                (finish_structure_construction, despawn_replaced_structures)
+                    .run_if(resource_exists::<StructureDefinitions>()),
+            );
+    }
+}
+
+/// What a structure does once it's built.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum StructureEffect {
+    /// Workers can climb the structure's tiles.
+    Climbable,
+    /// The structure turns into solid terrain tiles of the material with the given name.
+    SolidTile(String),
+    /// Workers craft the items ordered at the structure.
+    Workshop,
+}
+
+/// What has to be next to a structure for it to be placed.
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StructureSupport {
+    /// Solid ground or another structure below every tile of its bottom row.
+    #[default]
+    Below,
+    /// Solid ground or another structure next to any of its tiles.
+    Adjacent,
+    /// Can be placed in mid-air.
+    None,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct StructureDefinition {
+    pub name: String,
+    /// Texture stretched over the structure's footprint.
+    pub sprite: String,
+    /// Size in tiles, up and to the right of the tile the structure is placed on.
+    #[serde(default = "default_footprint")]
+    pub footprint: UVec2,
+    /// Items, by name, that have to be delivered before the structure can be built.
+    pub materials: Vec<(String, u32)>,
+    /// Seconds of work it takes to build the structure.
+    pub build_time: f32,
+    #[serde(default)]
+    pub support: StructureSupport,
+    #[serde(default)]
+    pub effects: Vec<StructureEffect>,
+}
+
+fn default_footprint() -> UVec2 {
+    UVec2::ONE
+}
+
+impl StructureDefinition {
+    /// Construction progress made by a second of work.
+    pub fn progress_per_second(&self) -> u32 {
+        (100. / self.build_time).ceil() as u32
+    }
+
+    /// Size of the structure in world units.
+    pub fn size(&self, cell_size: f32) -> Vec2 {
+        self.footprint.as_vec2() * cell_size
+    }
+
+    /// Whether the structure can be placed at `origin`: all of its tiles have to be loaded, free of
+    /// other structures and empty or diggable, and it has to be supported as its definition
+    /// requires.
+    pub fn can_place(
+        &self,
+        origin: IVec2,
+        terrain: &impl TerrainTiles,
+        occupied_tiles: &HashSet<IVec2>,
+        material_properties: &MaterialProperties,
+    ) -> bool {
+        let all_free = footprint_tiles(origin, self.footprint).all(|tile_pos| {
+            !occupied_tiles.contains(&tile_pos)
+                && terrain.get_tile(tile_pos).map_or(false, |tile| {
+                    tile == 0 || material_properties.diggable(tile)
+                })
+        });
+        if !all_free {
+            return false;
+        }
+
+        let supports = |tile_pos: IVec2| {
+            occupied_tiles.contains(&tile_pos)
+                || terrain.get_tile(tile_pos).map_or(false, |tile| tile != 0)
+        };
+        let size = self.footprint.as_ivec2();
+        match self.support {
+            StructureSupport::Below => (0..size.x).all(|x| supports(origin + IVec2::new(x, -1))),
+            StructureSupport::Adjacent => {
+                let below_and_above =
+                    (0..size.x).flat_map(|x| [IVec2::new(x, -1), IVec2::new(x, size.y)]);
+                let left_and_right =
+                    (0..size.y).flat_map(|y| [IVec2::new(-1, y), IVec2::new(size.x, y)]);
+                below_and_above
+                    .chain(left_and_right)
+                    .any(|offset| supports(origin + offset))
+            }
+            StructureSupport::None => true,
+        }
+    }
+
+    /// Tiles of the structure placed at `origin` that have to be dug out before it can be built.
+    pub fn tiles_to_dig(&self, origin: IVec2, terrain: &impl TerrainTiles) -> Vec<IVec2> {
+        footprint_tiles(origin, self.footprint)
+            .filter(|&tile_pos| terrain.get_tile(tile_pos).map_or(false, |tile| tile != 0))
+            .collect()
+    }
+
+    pub fn materials_needed(&self) -> BuildingMaterialsNeeded {
+        BuildingMaterialsNeeded::new(
+            self.materials
+                .iter()
+                .map(|(name, amount)| (Name::new(name.clone()), *amount))
+                .collect(),
+        )
+    }
+}
+
+#[derive(serde::Deserialize, TypeUuid, TypePath)]
+#[uuid = "0f3d6a52-8a1e-4f4e-9c55-2d7e3b1c9a41"]
+struct Structures(Vec<StructureDefinition>);
+
+#[derive(Resource)]
+struct StructuresHandle(Handle<Structures>);
+
+fn load_structures(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let structures = asset_server.load("base.structures.ron");
+    commands.insert_resource(StructuresHandle(structures));
+}
+
+#[derive(Resource)]
+pub struct StructureDefinitions(pub Vec<StructureDefinition>);
+
+impl StructureDefinitions {
+    pub fn get(&self, name: &str) -> Option<&StructureDefinition> {
+        self.0.iter().find(|definition| definition.name == name)
+    }
+}
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StructuresState {
+    #[default]
+    Loading,
+    Loaded,
+}
+
+fn setup_structures(
+    mut commands: Commands,
+    structures: Res<StructuresHandle>,
+    structures_assets: Res<Assets<Structures>>,
+    mut state: ResMut<NextState<StructuresState>>,
+) {
+    if let Some(structures) = structures_assets.get(&structures.0) {
+        commands.insert_resource(StructureDefinitions(structures.0.clone()));
+        info!("Structures loaded");
+        state.set(StructuresState::Loaded);
+    }
+}
+
+/// A structure of the definition called `name`, covering `footprint` tiles up and to the right of
+/// `origin`.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct PlacedStructure {
+    pub name: String,
+    pub origin: IVec2,
+    pub footprint: UVec2,
+}
+
+impl PlacedStructure {
+    pub fn tiles(&self) -> impl Iterator<Item = IVec2> {
+        footprint_tiles(self.origin, self.footprint)
+    }
+}
+
+/// Tiles covered by a footprint of `footprint` tiles with its bottom left tile at `origin`.
+pub fn footprint_tiles(origin: IVec2, footprint: UVec2) -> impl Iterator<Item = IVec2> {
+    let size = footprint.as_ivec2();
+    (0..size.y).flat_map(move |y| (0..size.x).map(move |x| origin + IVec2::new(x, y)))
+}
+
+const UNFINISHED_STRUCTURE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.5);
+
+/// Spawns an unfinished structure of `definition` with its bottom left tile at `origin`.
+pub fn spawn_structure(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    terrain_settings: &TerrainSettings,
+    definition: &StructureDefinition,
+    origin: IVec2,
+) -> Entity {
+    let size = definition.size(terrain_settings.cell_size);
+    let center = terrain_settings.tile_to_world(origin) + (size - terrain_settings.cell_size) / 2.;
+    commands
+        .spawn((
+            Name::new(definition.name.clone()),
+            PlacedStructure {
+                name: definition.name.clone(),
+                origin,
+                footprint: definition.footprint,
+            },
+            SpriteBundle {
+                texture: asset_server.load(definition.sprite.as_str()),
+                sprite: Sprite {
+                    color: UNFINISHED_STRUCTURE_COLOR,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(center.extend(BUILDING_LAYER_Z)),
+                ..default()
+            },
+            RigidBody::Fixed,
+            Collider::cuboid(size.x / 2., size.y / 2.),
+            CollisionGroups::new(CONSTRUCTION_COLLISION_GROUP, Group::empty()),
+        ))
+        .id()
+}
+
+/// A finished structure that was turned into terrain tiles, despawned once nobody works on it.
+#[derive(Component, Debug, Reflect)]
+pub struct ReplacedByTiles;
+
+fn finish_structure_construction(
+    mut commands: Commands,
+    mut construction_complete_events: EventReader<ConstructionCompletedEvent>,
+    mut structure_query: Query<(&PlacedStructure, &mut Sprite, &mut Visibility)>,
+    structure_definitions: Res<StructureDefinitions>,
+    material_properties: Option<Res<MaterialProperties>>,
+    mut place_tile_events: EventWriter<PlaceTileEvent>,
+) {
+    for event in construction_complete_events.iter() {
+        let Ok((structure, mut sprite, mut visibility)) = structure_query.get_mut(event.construction_site) else { continue; };
+        let Some(definition) = structure_definitions.get(&structure.name) else {
+            error!("Structure {} is not defined", structure.name);
+            continue;
+        };
+
+        sprite.color = Color::WHITE;
+        for effect in &definition.effects {
+            match effect {
+                StructureEffect::Climbable => {
+                    commands.entity(event.construction_site).insert((
+                        Climbable,
+                        CollisionGroups::new(CLIMBABLE_COLLISION_GROUP, Group::all()),
+                    ));
+                }
+                StructureEffect::SolidTile(material_name) => {
+                    let Some(material) = material_properties
+                        .as_deref()
+                        .and_then(|material_properties| material_properties.find(material_name))
+                    else {
+                        error!("Structure material {} is not defined", material_name);
+                        continue;
+                    };
+                    for tile_pos in structure.tiles() {
+                        place_tile_events.send(PlaceTileEvent { tile_pos, material });
+                    }
+                    *visibility = Visibility::Hidden;
+                    commands
+                        .entity(event.construction_site)
+                        .remove::<(Collider, RigidBody)>()
+                        .insert(ReplacedByTiles);
+                }
+                StructureEffect::Workshop => {
+                    commands.entity(event.construction_site).insert(Workshop);
+                }
+            }
+        }
+    }
+}
+
+fn despawn_replaced_structures(
+    mut commands: Commands,
+    replaced_query: Query<(Entity, Option<&Children>), With<ReplacedByTiles>>,
+    construction_job_query: Query<(), With<ConstructionJob>>,
+) {
+    for (structure_entity, opt_children) in &replaced_query {
+        let working = opt_children.map_or(false, |children| {
+            children
+                .iter()
+                .any(|child| construction_job_query.contains(*child))
+        });
+        if !working {
+            commands.entity(structure_entity).despawn_recursive();
+        }
+    }
+}
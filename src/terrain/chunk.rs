@@ -0,0 +1,170 @@
+use bevy::{
+    math::Vec3Swizzles,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_ecs_tilemap::{prelude::TilemapId, tiles::TilePos};
+
+use crate::{designation_layer::Designated, terrain_settings::TerrainSettings};
+
+use super::{Terrain, TerrainBundle, TerrainData, TileHealth};
+
+/// Chunks within this many chunks of a [`ChunkLoader`] are kept loaded.
+pub const CHUNK_LOAD_DISTANCE: i32 = 1;
+/// Loaded chunks are only unloaded once they are further away than this, so a loader moving
+/// back and forth over a chunk border doesn't load and unload the same chunk every frame.
+pub const CHUNK_UNLOAD_DISTANCE: i32 = CHUNK_LOAD_DISTANCE + 1;
+
+/// Location of a terrain chunk, in chunks. Chunk (0, 0) is centered on the world origin.
+#[derive(Component, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TerrainChunk(pub IVec2);
+
+/// Terrain chunk entities by their location, kept up to date as chunks are spawned and
+/// despawned.
+#[derive(Resource, Default)]
+pub struct TerrainChunkIndex(HashMap<IVec2, Entity>);
+
+impl TerrainChunkIndex {
+    pub fn get(&self, region: IVec2) -> Option<Entity> {
+        self.0.get(&region).copied()
+    }
+}
+
+pub(super) fn index_terrain_chunks(
+    mut chunk_index: ResMut<TerrainChunkIndex>,
+    new_chunk_query: Query<(Entity, &TerrainChunk), Added<Terrain>>,
+    mut removed_chunks: RemovedComponents<Terrain>,
+) {
+    for removed_entity in removed_chunks.iter() {
+        chunk_index
+            .0
+            .retain(|_, chunk_entity| *chunk_entity != removed_entity);
+    }
+    for (chunk_entity, chunk) in &new_chunk_query {
+        chunk_index.0.insert(chunk.0, chunk_entity);
+    }
+}
+
+/// Keeps the terrain chunks around this entity loaded.
+#[derive(Component, Default)]
+pub struct ChunkLoader;
+
+/// Health of the damaged tiles of a chunk, by their position inside the chunk.
+#[derive(Component, Default, Clone)]
+pub struct ChunkTileHealth(pub Vec<(TilePos, u32)>);
+
+/// Terrain data and tile health of chunks that have been unloaded, so changes to them survive
+/// streaming.
+#[derive(Resource, Default)]
+pub struct TerrainChunkCache(HashMap<IVec2, (TerrainData, ChunkTileHealth)>);
+
+impl TerrainChunkCache {
+    pub fn insert(
+        &mut self,
+        region: IVec2,
+        terrain_data: TerrainData,
+        tile_health: ChunkTileHealth,
+    ) {
+        self.0.insert(region, (terrain_data, tile_health));
+    }
+
+    pub fn take(&mut self, region: IVec2) -> Option<(TerrainData, ChunkTileHealth)> {
+        self.0.remove(&region)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&IVec2, &TerrainData)> {
+        self.0
+            .iter()
+            .map(|(region, (terrain_data, _))| (region, terrain_data))
+    }
+
+    /// Health of the damaged tiles of every cached chunk, along with the chunk they are in.
+    pub fn tile_health(&self) -> impl Iterator<Item = (IVec2, TilePos, u32)> + '_ {
+        self.0.iter().flat_map(|(region, (_, tile_health))| {
+            tile_health
+                .0
+                .iter()
+                .map(|(tile_pos, health)| (*region, *tile_pos, *health))
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Spawns the chunks around every [`ChunkLoader`] and unloads chunks that no loader is near.
+///
+/// Chunks are streamed in both directions, the world is infinitely wide and deep.
+pub fn stream_terrain_chunks(
+    mut commands: Commands,
+    terrain_settings: Res<TerrainSettings>,
+    mut chunk_cache: ResMut<TerrainChunkCache>,
+    loader_query: Query<&GlobalTransform, With<ChunkLoader>>,
+    chunk_query: Query<(Entity, &TerrainChunk, Option<&TerrainData>)>,
+    designated_tiles_query: Query<&TilemapId, With<Designated>>,
+    tile_health_query: Query<(&TileHealth, &TilePos, &TilemapId)>,
+) {
+    let loader_regions = loader_query
+        .iter()
+        .map(|loader_transform| {
+            let tile_pos = terrain_settings.world_to_tile(loader_transform.translation().xy());
+            terrain_settings.tile_to_chunk(tile_pos).0
+        })
+        .collect::<Vec<_>>();
+
+    if loader_regions.is_empty() {
+        return;
+    }
+
+    let loaded_regions = chunk_query
+        .iter()
+        .map(|(_, chunk, _)| chunk.0)
+        .collect::<HashSet<_>>();
+
+    let mut spawned_regions = HashSet::new();
+    for &loader_region in &loader_regions {
+        for x in -CHUNK_LOAD_DISTANCE..=CHUNK_LOAD_DISTANCE {
+            for y in -CHUNK_LOAD_DISTANCE..=CHUNK_LOAD_DISTANCE {
+                let region = loader_region + IVec2::new(x, y);
+                if loaded_regions.contains(&region) || !spawned_regions.insert(region) {
+                    continue;
+                }
+                info!("Loading terrain chunk {}", region);
+                let mut chunk_commands = commands.spawn(TerrainBundle {
+                    chunk: TerrainChunk(region),
+                    ..default()
+                });
+                if let Some((terrain_data, tile_health)) = chunk_cache.take(region) {
+                    chunk_commands.insert((terrain_data, tile_health));
+                }
+            }
+        }
+    }
+
+    // Chunks with designated tiles stay loaded, their jobs reference the tile entities
+    let designated_chunks = designated_tiles_query
+        .iter()
+        .map(|tilemap_id| tilemap_id.0)
+        .collect::<HashSet<_>>();
+
+    for (chunk_entity, chunk, opt_terrain_data) in &chunk_query {
+        // Chunks that are still generating have nothing to cache yet
+        let Some(terrain_data) = opt_terrain_data else { continue; };
+        let in_range = loader_regions.iter().any(|loader_region| {
+            let distance = (chunk.0 - *loader_region).abs();
+            distance.x.max(distance.y) <= CHUNK_UNLOAD_DISTANCE
+        });
+        if in_range || designated_chunks.contains(&chunk_entity) {
+            continue;
+        }
+        info!("Unloading terrain chunk {}", chunk.0);
+        let tile_health = tile_health_query
+            .iter()
+            .filter(|(_, _, tilemap_id)| tilemap_id.0 == chunk_entity)
+            .map(|(tile_health, tile_pos, _)| (*tile_pos, tile_health.0))
+            .collect();
+        chunk_cache.insert(chunk.0, terrain_data.clone(), ChunkTileHealth(tile_health));
+        commands.entity(chunk_entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,265 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_tilemap::tiles::TilePos;
+use bevy_rapier2d::prelude::{Collider, CollisionGroups, Group, Vect};
+
+use crate::terrain_settings::TerrainSettings;
+
+use super::{Terrain, TerrainData, TileDestroyedEvent, TilePlacedEvent, TERRAIN_COLLISION_GROUP};
+
+/// Width and height, in tiles, of the terrain sections that each get their own collider.
+pub const COLLIDER_SECTION_SIZE: u32 = 16;
+
+/// Collider for one section of a terrain chunk, spawned as a child of the chunk.
+///
+/// Solid tiles in the section are merged into as few rectangles as possible. When tiles change
+/// only the colliders of the sections containing them are rebuilt.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TerrainColliderSection(pub UVec2);
+
+impl TerrainColliderSection {
+    pub fn containing(tile_pos: TilePos) -> Self {
+        Self(UVec2::new(tile_pos.x, tile_pos.y) / COLLIDER_SECTION_SIZE)
+    }
+
+    /// Position of the section's bottom left tile inside the chunk.
+    pub fn min_tile(&self) -> UVec2 {
+        self.0 * COLLIDER_SECTION_SIZE
+    }
+}
+
+/// A rectangle of solid tiles, in tiles relative to the section's bottom left tile.
+struct TileRect {
+    min: UVec2,
+    size: UVec2,
+}
+
+/// Merges the solid tiles of a section into rectangles: every rectangle is grown as far right
+/// as possible first, then upwards while the whole row above it is solid and not yet covered.
+fn greedy_rects(terrain_data: &TerrainData, section: TerrainColliderSection) -> Vec<TileRect> {
+    let map_size = terrain_data.map_size();
+    let min = section.min_tile();
+    let size = (map_size - min).min(UVec2::splat(COLLIDER_SECTION_SIZE));
+
+    let is_solid = |x: u32, y: u32| {
+        terrain_data
+            .get_tile(min + UVec2::new(x, y))
+            .map_or(false, |tile| tile != 0)
+    };
+
+    let mut covered = vec![false; (size.x * size.y) as usize];
+    let index = |x: u32, y: u32| (y * size.x + x) as usize;
+
+    let mut rects = vec![];
+    for y in 0..size.y {
+        for x in 0..size.x {
+            if covered[index(x, y)] || !is_solid(x, y) {
+                continue;
+            }
+
+            let mut width = 1;
+            while x + width < size.x && !covered[index(x + width, y)] && is_solid(x + width, y) {
+                width += 1;
+            }
+
+            let mut height = 1;
+            while y + height < size.y
+                && (x..x + width)
+                    .all(|rx| !covered[index(rx, y + height)] && is_solid(rx, y + height))
+            {
+                height += 1;
+            }
+
+            for ry in y..y + height {
+                for rx in x..x + width {
+                    covered[index(rx, ry)] = true;
+                }
+            }
+
+            rects.push(TileRect {
+                min: UVec2::new(x, y),
+                size: UVec2::new(width, height),
+            });
+        }
+    }
+    rects
+}
+
+/// Builds the collider of a section, `None` when the section has no solid tiles.
+fn build_section_collider(
+    terrain_data: &TerrainData,
+    section: TerrainColliderSection,
+    cell_size: f32,
+) -> Option<Collider> {
+    let shapes = greedy_rects(terrain_data, section)
+        .into_iter()
+        .map(|rect| {
+            // Positions are relative to the center of the section's bottom left tile
+            let center = (rect.min.as_vec2() + (rect.size.as_vec2() - 1.) / 2.) * cell_size;
+            let half_extents = rect.size.as_vec2() * cell_size / 2.;
+            (
+                Vect::new(center.x, center.y),
+                0.,
+                Collider::cuboid(half_extents.x, half_extents.y),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if shapes.is_empty() {
+        None
+    } else {
+        Some(Collider::compound(shapes))
+    }
+}
+
+/// Spawns the collider sections of a newly spawned chunk.
+pub fn spawn_terrain_colliders(
+    commands: &mut Commands,
+    terrain_entity: Entity,
+    terrain_data: &TerrainData,
+    terrain_settings: &TerrainSettings,
+) {
+    let map_size = terrain_data.map_size();
+    let section_count = (map_size + COLLIDER_SECTION_SIZE - 1) / COLLIDER_SECTION_SIZE;
+
+    commands.entity(terrain_entity).with_children(|parent| {
+        for x in 0..section_count.x {
+            for y in 0..section_count.y {
+                let section = TerrainColliderSection(UVec2::new(x, y));
+                let translation = section.min_tile().as_vec2() * terrain_settings.cell_size;
+                let mut section_commands = parent.spawn((
+                    Name::new("Terrain collider"),
+                    section,
+                    TransformBundle::from_transform(Transform::from_translation(
+                        translation.extend(0.),
+                    )),
+                    CollisionGroups::new(TERRAIN_COLLISION_GROUP, Group::ALL),
+                ));
+                if let Some(collider) =
+                    build_section_collider(terrain_data, section, terrain_settings.cell_size)
+                {
+                    section_commands.insert(collider);
+                }
+            }
+        }
+    });
+}
+
+/// Rebuilds the colliders of the sections whose tiles changed this frame.
+pub fn update_terrain_colliders(
+    mut commands: Commands,
+    terrain_settings: Res<TerrainSettings>,
+    mut destroyed_tiles: EventReader<TileDestroyedEvent>,
+    mut placed_tiles: EventReader<TilePlacedEvent>,
+    chunk_query: Query<(&TerrainData, &Children), With<Terrain>>,
+    section_query: Query<&TerrainColliderSection>,
+) {
+    let changed_tiles = destroyed_tiles
+        .iter()
+        .map(|event| (event.chunk, event.tile_pos))
+        .chain(
+            placed_tiles
+                .iter()
+                .map(|event| (event.chunk, event.tile_pos)),
+        );
+    let changed_sections = changed_tiles
+        .map(|(chunk, tile_pos)| (chunk, TerrainColliderSection::containing(tile_pos)))
+        .collect::<HashSet<_>>();
+
+    for (chunk_entity, changed_section) in changed_sections {
+        let Ok((terrain_data, children)) = chunk_query.get(chunk_entity) else { continue; };
+        let Some(section_entity) = children.iter().copied().find(|child| {
+            section_query
+                .get(*child)
+                .map_or(false, |section| *section == changed_section)
+        }) else {
+            continue;
+        };
+
+        // Inserting replaces the old collider, only remove it when the section is now empty
+        match build_section_collider(terrain_data, changed_section, terrain_settings.cell_size) {
+            Some(collider) => {
+                commands.entity(section_entity).insert(collider);
+            }
+            None => {
+                commands.entity(section_entity).remove::<Collider>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::UVec2;
+    use ndarray::prelude::*;
+
+    use super::{build_section_collider, greedy_rects, TerrainColliderSection};
+    use crate::terrain::TerrainData;
+
+    fn rects(terrain_data: &TerrainData, section: TerrainColliderSection) -> Vec<(UVec2, UVec2)> {
+        greedy_rects(terrain_data, section)
+            .into_iter()
+            .map(|rect| (rect.min, rect.size))
+            .collect()
+    }
+
+    #[test]
+    fn merges_solid_block_into_one_rect() {
+        let terrain_data = TerrainData::new(Array2::from_elem((16, 16), 1));
+
+        assert_eq!(
+            rects(&terrain_data, TerrainColliderSection(UVec2::ZERO)),
+            vec![(UVec2::ZERO, UVec2::splat(16))]
+        );
+    }
+
+    #[test]
+    fn grows_rects_right_then_up() {
+        // y = 1: x x . .
+        // y = 0: x x x .
+        let mut tiles = Array2::zeros((4, 2));
+        for [x, y] in [[0, 0], [1, 0], [2, 0], [0, 1], [1, 1]] {
+            tiles[[x, y]] = 1;
+        }
+        let terrain_data = TerrainData::new(tiles);
+
+        assert_eq!(
+            rects(&terrain_data, TerrainColliderSection(UVec2::ZERO)),
+            vec![
+                (UVec2::new(0, 0), UVec2::new(3, 1)),
+                (UVec2::new(0, 1), UVec2::new(2, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn clips_sections_to_the_chunk() {
+        let terrain_data = TerrainData::new(Array2::from_elem((20, 4), 1));
+
+        assert_eq!(
+            rects(&terrain_data, TerrainColliderSection(UVec2::new(1, 0))),
+            vec![(UVec2::ZERO, UVec2::new(4, 4))]
+        );
+    }
+
+    #[test]
+    fn builds_no_collider_for_empty_sections() {
+        let mut tiles = Array2::zeros((32, 16));
+        tiles[[20, 3]] = 1;
+        let terrain_data = TerrainData::new(tiles);
+
+        assert!(
+            build_section_collider(&terrain_data, TerrainColliderSection(UVec2::ZERO), 16.)
+                .is_none()
+        );
+        let collider =
+            build_section_collider(&terrain_data, TerrainColliderSection(UVec2::new(1, 0)), 16.)
+                .expect("section has a solid tile");
+        assert_eq!(
+            collider
+                .raw
+                .as_compound()
+                .map(|compound| compound.shapes().len()),
+            Some(1)
+        );
+    }
+}
@@ -1,11 +1,14 @@
+mod chunk;
+mod collider;
 mod terrain_params;
 
+#[cfg(not(feature = "async"))]
 use std::sync::Arc;
 
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle, utils::HashMap};
 
 use bevy_ecs_tilemap::prelude::*;
-use bevy_rapier2d::prelude::{Collider, CollisionGroups, Group, RigidBody, Vect};
+use bevy_rapier2d::prelude::{Collider, CollisionGroups, Group, RapierContext, RigidBody};
 
 #[cfg(feature = "async")]
 use {
@@ -16,18 +19,39 @@ use {
 use ndarray::prelude::*;
 
 use crate::{
-    main_state::MainState, material::MaterialProperties, terrain_settings::TerrainSettings,
+    dwarf::DWARF_COLLISION_GROUP,
+    item::Item,
+    main_state::MainState,
+    material::MaterialProperties,
+    terrain_settings::TerrainSettings,
+    tree::{OBJECT_COLLISION_GROUP, TREE_COLLISION_GROUP},
 };
 
 use terrain_gen::{create_terrain_generator_function, generate_terrain, GeneratorFunction};
 
-pub use self::terrain_params::TerrainParam;
+#[cfg(feature = "async")]
+use terrain_gen::TerrainGeneratorSettings;
+
+pub use self::{
+    chunk::{
+        ChunkLoader, ChunkTileHealth, TerrainChunk, TerrainChunkCache, TerrainChunkIndex,
+        CHUNK_LOAD_DISTANCE, CHUNK_UNLOAD_DISTANCE,
+    },
+    collider::{TerrainColliderSection, COLLIDER_SECTION_SIZE},
+    terrain_params::{TerrainParam, TerrainTiles},
+};
 pub struct TerrainPlugin;
 
 impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<TileDamageEvent>()
             .add_event::<TileDestroyedEvent>()
+            .add_event::<PlaceTileEvent>()
+            .add_event::<TilePlacedEvent>()
+            .init_resource::<TerrainChunkCache>()
+            .init_resource::<TerrainChunkIndex>()
+            .init_resource::<HeldTilePlacements>()
+            .add_systems(PreUpdate, chunk::index_terrain_chunks)
             .add_systems(
                 Update,
                 (setup_terrain, spawn_tilemap).run_if(resource_exists::<TerrainSettings>()),
@@ -38,6 +62,13 @@ impl Plugin for TerrainPlugin {
                     .run_if(in_state(MainState::Game))
                     .in_set(TerrainSet),
             )
+            .add_systems(
+                Update,
+                chunk::stream_terrain_chunks
+                    .run_if(in_state(MainState::Game))
+                    .in_set(TerrainSet)
+                    .before(TerrainUpdateSet),
+            )
             .add_systems(
                 Update,
                 (update_terrain, apply_deferred)
@@ -48,7 +79,13 @@ impl Plugin for TerrainPlugin {
             )
             .add_systems(
                 Update,
-                (color_damage_tile, remove_destroyed_tiles)
+                (
+                    color_damage_tile,
+                    remove_destroyed_tiles,
+                    place_tiles,
+                    collider::update_terrain_colliders,
+                )
+                    .chain()
                     .run_if(in_state(MainState::Game))
                     .in_set(TerrainSet)
                     .after(TerrainUpdateSet),
@@ -68,15 +105,24 @@ pub struct Terrain;
 #[derive(Bundle, Default)]
 pub struct TerrainBundle {
     pub terrain: Terrain,
+    pub chunk: TerrainChunk,
 }
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TerrainSet;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct TerrainData(Array2<u16>);
 
 impl TerrainData {
+    pub fn new(tiles: Array2<u16>) -> Self {
+        Self(tiles)
+    }
+
+    pub fn tiles(&self) -> &Array2<u16> {
+        &self.0
+    }
+
     pub fn get_tile(&self, tile_pos: UVec2) -> Option<u16> {
         self.0
             .get([tile_pos.x as usize, tile_pos.y as usize])
@@ -87,7 +133,26 @@ impl TerrainData {
         let shape = self.0.shape();
         UVec2::new(shape[0] as u32, shape[1] as u32)
     }
+
+    /// Topmost solid tile of column `x` with an empty tile above it, `None` when the surface
+    /// isn't inside this chunk.
+    pub fn surface_tile(&self, x: u32) -> Option<UVec2> {
+        (0..self.map_size().y.saturating_sub(1))
+            .rev()
+            .map(|y| UVec2::new(x, y))
+            .find(|tile_pos| {
+                self.get_tile(*tile_pos).map_or(false, |tile| tile != 0)
+                    && self.get_tile(*tile_pos + UVec2::Y) == Some(0)
+            })
+    }
 }
+
+/// Marks chunks whose terrain was generated rather than restored from the chunk cache or a save.
+///
+/// Trees and plants are spawned on chunks once they get it, so every chunk is only populated
+/// the first time it is loaded.
+#[derive(Component, Debug, Default)]
+pub struct GeneratedTerrain;
 #[derive(Component)]
 pub struct TerrainGenerator(pub GeneratorFunction);
 
@@ -102,74 +167,54 @@ impl TerrainGenerator {
 #[component(storage = "SparseSet")]
 struct GenerateTerrain(pub Task<TerrainData>);
 
-#[cfg(feature = "async")]
-#[derive(Component)]
-struct SetupGenerator(Task<TerrainGenerator>);
-
 fn setup_terrain(
     mut commands: Commands,
     terrain_settings: Res<TerrainSettings>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    new_terrain_query: Query<Entity, Added<Terrain>>,
+    new_terrain_query: Query<(Entity, &TerrainChunk, Option<&TerrainData>), Added<Terrain>>,
 ) {
-    for terrain_entity in &new_terrain_query {
-        info!("Setting up terrain");
-        let settings = terrain_settings.clone();
-        let generator = TerrainGenerator::new(settings);
-
-        #[cfg(feature = "async")]
-        {
-            info!("Generating terrain asynchronously");
-            let task = generate_terrain_async(generator, terrain_settings.clone().into());
-            commands.spawn(GenerateTerrain(task));
-        }
+    for (terrain_entity, chunk, opt_terrain_data) in &new_terrain_query {
+        info!("Setting up terrain chunk {}", chunk.0);
+        if opt_terrain_data.is_some() {
+            info!("Terrain data already present, skipping generation");
+        } else {
+            let settings = terrain_settings.clone();
+            let generator = TerrainGenerator::new(settings);
+
+            #[cfg(feature = "async")]
+            {
+                info!("Generating terrain asynchronously");
+                let task =
+                    generate_terrain_async(chunk.0, generator, terrain_settings.clone().into());
+                commands
+                    .entity(terrain_entity)
+                    .insert(GenerateTerrain(task));
+            }
 
-        #[cfg(not(feature = "async"))]
-        {
-            info!("Generating terrain synchronously");
-            let generator = Arc::clone(&generator.0);
-            let terrain_data =
-                generate_terrain(IVec2::new(0, 0), generator, terrain_settings.clone().into());
-            commands
-                .entity(terrain_entity)
-                .insert(TerrainData(terrain_data));
+            #[cfg(not(feature = "async"))]
+            {
+                info!("Generating terrain synchronously");
+                let generator = Arc::clone(&generator.0);
+                let terrain_data =
+                    generate_terrain(chunk.0, generator, terrain_settings.clone().into());
+                commands
+                    .entity(terrain_entity)
+                    .insert((TerrainData(terrain_data), GeneratedTerrain));
+            }
         }
-
-        // Spawn a quad behind the terrain to act as a background
-        commands.spawn((
-            Name::new("Background"),
-            MaterialMesh2dBundle {
-                transform: Transform::from_xyz(
-                    -terrain_settings.cell_size / 2. + 0.5 * terrain_settings.cell_size,
-                    -terrain_settings.cell_size / 2. + 0.5 * terrain_settings.cell_size,
-                    TERRAIN_LAYER_Z,
-                ),
-                material: materials.add(Color::TEAL.into()),
-                mesh: meshes
-                    .add(Mesh::from(shape::Quad::new(Vec2::new(
-                        terrain_settings.cell_size * terrain_settings.width as f32,
-                        terrain_settings.cell_size * terrain_settings.height as f32,
-                    ))))
-                    .into(),
-                ..default()
-            },
-        ));
     }
 }
 
 #[cfg(feature = "async")]
 fn generate_terrain_async(
+    region_location: IVec2,
     generator: TerrainGenerator,
-    terrain_settings: TerrainSettings,
+    terrain_settings: TerrainGeneratorSettings,
 ) -> Task<TerrainData> {
     let thread_pool = AsyncComputeTaskPool::get();
-    let terrain_settings_clone = terrain_settings.clone();
-    let task = thread_pool.spawn(async move {
-        let region = generate_terrain(IVec2::new(0, 0), generator, terrain_settings);
+    thread_pool.spawn(async move {
+        let region = generate_terrain(region_location, generator.0, terrain_settings);
         TerrainData(region)
-    });
-    task
+    })
 }
 
 #[cfg(feature = "async")]
@@ -182,7 +227,7 @@ fn spawn_terrain_data(
             commands
                 .entity(terrain_entity)
                 .remove::<GenerateTerrain>()
-                .insert(terrain_data);
+                .insert((terrain_data, GeneratedTerrain));
         }
     }
 }
@@ -192,12 +237,26 @@ pub const TERRAIN_COLLISION_GROUP: Group = Group::GROUP_1;
 
 fn spawn_tilemap(
     mut commands: Commands,
-    mut new_terrain_data_query: Query<(Entity, &TerrainData), Added<TerrainData>>,
+    mut new_terrain_data_query: Query<
+        (
+            Entity,
+            &TerrainChunk,
+            &TerrainData,
+            Option<&ChunkTileHealth>,
+        ),
+        Added<TerrainData>,
+    >,
     terrain_settings: Res<TerrainSettings>,
     asset_server: Res<AssetServer>,
     material_properties: Res<MaterialProperties>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
 ) {
-    for (terrain_entity, terrain_data) in &mut new_terrain_data_query {
+    for (terrain_entity, chunk, terrain_data, opt_tile_health) in &mut new_terrain_data_query {
+        // Damage done to the tiles before the chunk was unloaded
+        let tile_health = opt_tile_health
+            .map(|tile_health| tile_health.0.iter().copied().collect::<HashMap<_, _>>())
+            .unwrap_or_default();
         let texture_handle = asset_server.load("textures/terrain.png");
         let tilemap_size = TilemapSize {
             x: terrain_settings.width,
@@ -205,13 +264,11 @@ fn spawn_tilemap(
         };
         let mut tile_storage = TileStorage::empty(tilemap_size);
 
-        let terrain_transform = Transform::from_translation(Vec3::new(
-            -(terrain_settings.width as f32 * terrain_settings.cell_size) / 2.0
-                + 0.5 * terrain_settings.cell_size,
-            -(terrain_settings.height as f32 * terrain_settings.cell_size) / 2.0
-                + 0.5 * terrain_settings.cell_size,
-            TERRAIN_LAYER_Z,
-        ));
+        let terrain_transform = Transform::from_translation(
+            terrain_settings
+                .chunk_origin(chunk.0)
+                .extend(TERRAIN_LAYER_Z),
+        );
 
         for ((x, y), tile) in terrain_data.0.indexed_iter() {
             if *tile > 0 {
@@ -220,26 +277,17 @@ fn spawn_tilemap(
                     y: y as u32,
                 };
                 commands.entity(terrain_entity).with_children(|parent| {
-                    let tile_entity = parent
-                        .spawn((
-                            Name::new("TerrainTile"),
-                            TileBundle {
-                                position: tile_pos,
-                                tilemap_id: TilemapId(terrain_entity),
-                                texture_index: TileTextureIndex(0),
-                                color: material_properties.0[*tile as usize].color.into(),
-                                ..default()
-                            },
-                            TransformBundle::from_transform(Transform::from_translation(
-                                Vec3::new(
-                                    x as f32 * terrain_settings.cell_size,
-                                    y as f32 * terrain_settings.cell_size,
-                                    0.0,
-                                ),
-                            )),
-                        ))
-                        .id();
-                    tile_storage.set(&tile_pos, tile_entity);
+                    let mut tile_commands = parent.spawn(tile_bundle(
+                        terrain_entity,
+                        tile_pos,
+                        *tile,
+                        &material_properties,
+                        &terrain_settings,
+                    ));
+                    if let Some(&health) = tile_health.get(&tile_pos) {
+                        tile_commands.insert(TileHealth(health));
+                    }
+                    tile_storage.set(&tile_pos, tile_commands.id());
                 });
             }
         }
@@ -249,35 +297,94 @@ fn spawn_tilemap(
             y: terrain_settings.cell_size,
         };
 
-        let tile_colliders = build_terrain_colliders(&terrain_settings, &tile_storage);
-
-        commands.entity(terrain_entity).insert((
-            Name::new("Terrain"),
-            TilemapBundle {
-                grid_size: tile_size.into(),
-                map_type: TilemapType::Square,
-                size: tilemap_size,
-                storage: tile_storage,
-                texture: TilemapTexture::Single(texture_handle),
-                tile_size,
-                transform: terrain_transform,
-                ..default()
-            },
-            RigidBody::Fixed,
-            CollisionGroups::new(TERRAIN_COLLISION_GROUP, Group::ALL),
-            Collider::compound(tile_colliders),
-        ));
+        collider::spawn_terrain_colliders(
+            &mut commands,
+            terrain_entity,
+            terrain_data,
+            &terrain_settings,
+        );
+
+        // Spawn a quad behind the chunk to act as a background
+        let chunk_size = terrain_settings.chunk_size().as_vec2() * terrain_settings.cell_size;
+        commands.entity(terrain_entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("Background"),
+                MaterialMesh2dBundle {
+                    transform: Transform::from_translation(
+                        (chunk_size / 2. - 0.5 * terrain_settings.cell_size).extend(0.),
+                    ),
+                    material: materials.add(Color::TEAL.into()),
+                    mesh: meshes.add(Mesh::from(shape::Quad::new(chunk_size))).into(),
+                    ..default()
+                },
+            ));
+        });
+
+        commands
+            .entity(terrain_entity)
+            .remove::<ChunkTileHealth>()
+            .insert((
+                Name::new(format!("Terrain chunk {}", chunk.0)),
+                TilemapBundle {
+                    grid_size: tile_size.into(),
+                    map_type: TilemapType::Square,
+                    size: tilemap_size,
+                    storage: tile_storage,
+                    texture: TilemapTexture::Single(texture_handle),
+                    tile_size,
+                    transform: terrain_transform,
+                    ..default()
+                },
+                RigidBody::Fixed,
+            ));
     }
 }
 
+fn tile_bundle(
+    terrain_entity: Entity,
+    tile_pos: TilePos,
+    material: u16,
+    material_properties: &MaterialProperties,
+    terrain_settings: &TerrainSettings,
+) -> impl Bundle {
+    (
+        Name::new("TerrainTile"),
+        TileBundle {
+            position: tile_pos,
+            tilemap_id: TilemapId(terrain_entity),
+            texture_index: TileTextureIndex(0),
+            color: material_properties.0[material as usize].color.into(),
+            ..default()
+        },
+        TransformBundle::from_transform(Transform::from_translation(Vec3::new(
+            tile_pos.x as f32 * terrain_settings.cell_size,
+            tile_pos.y as f32 * terrain_settings.cell_size,
+            0.0,
+        ))),
+    )
+}
+
 #[derive(Event)]
 pub struct TileDamageEvent {
     pub tile: Entity,
     pub damage: u32,
 }
 
+/// Health left of a damaged tile. Tiles start with the health of their material.
 #[derive(Component)]
-pub struct TileHealth(u32);
+pub struct TileHealth(pub u32);
+
+/// Material of a tile entity, read from the terrain data of its chunk.
+fn tile_material(
+    tile_pos: &TilePos,
+    tilemap_id: &TilemapId,
+    terrain_data_query: &Query<&TerrainData, With<Terrain>>,
+) -> Option<u16> {
+    terrain_data_query
+        .get(tilemap_id.0)
+        .ok()?
+        .get_tile((*tile_pos).into())
+}
 
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 struct TerrainUpdateSet;
@@ -286,85 +393,212 @@ fn update_terrain(
     mut commands: Commands,
     mut tile_damage_events: EventReader<TileDamageEvent>,
     mut damage_tiles_query: Query<&mut TileHealth>,
+    tile_query: Query<(&TilePos, &TilemapId)>,
+    terrain_data_query: Query<&TerrainData, With<Terrain>>,
+    material_properties: Res<MaterialProperties>,
 ) {
     for damage_event in tile_damage_events.iter() {
+        let Some(material) = tile_query
+            .get(damage_event.tile)
+            .ok()
+            .and_then(|(tile_pos, tilemap_id)| {
+                tile_material(tile_pos, tilemap_id, &terrain_data_query)
+            })
+            .and_then(|material| material_properties.get(material))
+        else {
+            continue;
+        };
+        if !material.diggable {
+            debug!(tile = ?damage_event.tile, "Tile material {} can't be dug", material.name);
+            continue;
+        }
+
         if let Ok(mut tile_health) = damage_tiles_query.get_mut(damage_event.tile) {
             tile_health.0 = tile_health.0.saturating_sub(damage_event.damage);
         } else if let Some(mut tile_entity) = commands.get_entity(damage_event.tile) {
-            tile_entity.insert(TileHealth(100u32.saturating_sub(damage_event.damage)));
+            tile_entity.insert(TileHealth(
+                material.health.saturating_sub(damage_event.damage),
+            ));
         }
     }
 }
 
 fn color_damage_tile(
-    mut damaged_tiles_query: Query<(&TileHealth, &mut TileColor), Changed<TileHealth>>,
+    mut damaged_tiles_query: Query<
+        (&TileHealth, &TilePos, &TilemapId, &mut TileColor),
+        Changed<TileHealth>,
+    >,
+    terrain_data_query: Query<&TerrainData, With<Terrain>>,
+    material_properties: Res<MaterialProperties>,
 ) {
-    for (tile_health, mut tile_color) in &mut damaged_tiles_query {
-        tile_color.0 = Color::rgb(
-            1.0 - (tile_health.0 as f32 / 100.0),
-            tile_health.0 as f32 / 100.0,
-            0.0,
-        );
+    for (tile_health, tile_pos, tilemap_id, mut tile_color) in &mut damaged_tiles_query {
+        let max_health = tile_material(tile_pos, tilemap_id, &terrain_data_query)
+            .and_then(|material| material_properties.get(material))
+            .map_or(100, |material| material.health)
+            .max(1);
+        let health_fraction = (tile_health.0 as f32 / max_health as f32).min(1.0);
+        tile_color.0 = Color::rgb(1.0 - health_fraction, health_fraction, 0.0);
     }
 }
 
 #[derive(Event)]
 pub struct TileDestroyedEvent {
     pub entity: Entity,
+    /// The terrain chunk the tile belonged to.
+    pub chunk: Entity,
+    /// Position of the tile inside its chunk.
     pub tile_pos: TilePos,
+    /// Material the tile was made of.
+    pub material: u16,
 }
 
 fn remove_destroyed_tiles(
     mut commands: Commands,
-    config: Res<TerrainSettings>,
-    tile_query: Query<(Entity, &TileHealth, &TilePos), Changed<TileHealth>>,
-    mut tilemap_query: Query<(Entity, &mut TileStorage, &mut TerrainData), With<Terrain>>,
+    tile_query: Query<(Entity, &TileHealth, &TilePos, &TilemapId), Changed<TileHealth>>,
+    mut tilemap_query: Query<(&mut TileStorage, &mut TerrainData), With<Terrain>>,
     mut destroyed_tiles: EventWriter<TileDestroyedEvent>,
 ) {
-    let (tilemap_entity, mut tile_storage, mut terrain_data) = tilemap_query.single_mut();
-    for (tile_entity, tile_health, tile_pos) in &tile_query {
-        if tile_health.0 == 0 {
-            commands.entity(tile_entity).despawn_recursive();
-            tile_storage.remove(tile_pos);
-            terrain_data.0[[tile_pos.x as usize, tile_pos.y as usize]] = 0;
-            destroyed_tiles.send(TileDestroyedEvent {
-                entity: tile_entity,
-                tile_pos: *tile_pos,
-            });
+    for (tile_entity, tile_health, tile_pos, tilemap_id) in &tile_query {
+        if tile_health.0 > 0 {
+            continue;
         }
+        let Ok((mut tile_storage, mut terrain_data)) = tilemap_query.get_mut(tilemap_id.0) else {
+            continue;
+        };
+        commands.entity(tile_entity).despawn_recursive();
+        tile_storage.remove(tile_pos);
+        let index = [tile_pos.x as usize, tile_pos.y as usize];
+        let material = terrain_data.0[index];
+        terrain_data.0[index] = 0;
+        destroyed_tiles.send(TileDestroyedEvent {
+            entity: tile_entity,
+            chunk: tilemap_id.0,
+            tile_pos: *tile_pos,
+            material,
+        });
     }
+}
 
-    // Rebuild tilemap collider
-    let tile_colliders = build_terrain_colliders(&config, &tile_storage);
+/// Request to fill the empty tile at the world tile position `tile_pos` with `material`.
+///
+/// The tile is only placed once no worker or tree overlaps it, items lying in it are pushed onto
+/// the tile above.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlaceTileEvent {
+    pub tile_pos: IVec2,
+    pub material: u16,
+}
 
-    let mut tilemap_commands = commands.entity(tilemap_entity);
+/// Sent when an empty tile was filled.
+#[derive(Event)]
+pub struct TilePlacedEvent {
+    pub entity: Entity,
+    /// The terrain chunk the tile belongs to.
+    pub chunk: Entity,
+    /// Position of the tile inside its chunk.
+    pub tile_pos: TilePos,
+    /// Material the tile is made of.
+    pub material: u16,
+}
 
-    tilemap_commands.remove::<Collider>();
+/// Tile placements held back while something overlaps their tile or their chunk isn't loaded.
+#[derive(Resource, Default)]
+pub struct HeldTilePlacements(Vec<PlaceTileEvent>);
 
-    if !tile_colliders.is_empty() {
-        tilemap_commands.insert(Collider::compound(tile_colliders));
+impl HeldTilePlacements {
+    pub fn iter(&self) -> impl Iterator<Item = &PlaceTileEvent> {
+        self.0.iter()
     }
 }
 
-fn build_terrain_colliders(
-    config: &TerrainSettings,
-    tile_storage: &TileStorage,
-) -> Vec<(Vec2, f32, Collider)> {
-    let mut tile_colliders = vec![];
-    let half_cell_size = config.cell_size / 2.;
-    let tilemap_size = tile_storage.size;
-
-    for x in 0..tilemap_size.x {
-        for y in 0..tilemap_size.y {
-            if tile_storage.get(&TilePos { x, y }).is_none() {
-                continue;
-            }
-            tile_colliders.push((
-                Vect::new(x as f32 * config.cell_size, y as f32 * config.cell_size),
-                0.,
-                Collider::cuboid(half_cell_size, half_cell_size),
-            ));
+fn place_tiles(
+    mut commands: Commands,
+    mut place_tile_events: EventReader<PlaceTileEvent>,
+    mut held_placements: ResMut<HeldTilePlacements>,
+    chunk_index: Res<TerrainChunkIndex>,
+    mut chunk_query: Query<(&mut TileStorage, &mut TerrainData), With<Terrain>>,
+    mut item_query: Query<&mut Transform, With<Item>>,
+    rapier_context: Res<RapierContext>,
+    terrain_settings: Res<TerrainSettings>,
+    material_properties: Res<MaterialProperties>,
+    mut placed_tiles: EventWriter<TilePlacedEvent>,
+) {
+    let place_tile_events = std::mem::take(&mut held_placements.0)
+        .into_iter()
+        .chain(place_tile_events.iter().copied())
+        .collect::<Vec<_>>();
+    for place_tile_event in place_tile_events {
+        if material_properties.get(place_tile_event.material).is_none() {
+            error!(
+                material = place_tile_event.material,
+                "Unknown material to place"
+            );
+            continue;
         }
+        let (region, tile_pos) = terrain_settings.tile_to_chunk(place_tile_event.tile_pos);
+        let chunk = match chunk_index.get(region) {
+            Some(chunk_entity) => chunk_query
+                .get_mut(chunk_entity)
+                .ok()
+                .map(|chunk| (chunk_entity, chunk)),
+            None => None,
+        };
+        let Some((chunk_entity, (mut tile_storage, mut terrain_data))) = chunk else {
+            debug!(tile_pos = ?place_tile_event.tile_pos, "Holding tile placement, chunk is not loaded");
+            held_placements.0.push(place_tile_event);
+            continue;
+        };
+        let index = [tile_pos.x as usize, tile_pos.y as usize];
+        if terrain_data.0.get(index) != Some(&0) {
+            info!(tile_pos = ?place_tile_event.tile_pos, "Tile is not empty");
+            continue;
+        }
+
+        // Slightly smaller than the tile, so whatever stands next to it doesn't hold it
+        let half_extent = terrain_settings.cell_size / 2. - 1.;
+        let mut blocked = false;
+        rapier_context.intersections_with_shape(
+            terrain_settings.tile_to_world(place_tile_event.tile_pos),
+            0.,
+            &Collider::cuboid(half_extent, half_extent),
+            CollisionGroups::new(
+                TERRAIN_COLLISION_GROUP,
+                DWARF_COLLISION_GROUP | TREE_COLLISION_GROUP | OBJECT_COLLISION_GROUP,
+            )
+            .into(),
+            |hit_entity| {
+                match item_query.get_mut(hit_entity) {
+                    Ok(mut item_transform) => {
+                        item_transform.translation.y += terrain_settings.cell_size;
+                    }
+                    Err(_) => blocked = true,
+                }
+                true
+            },
+        );
+        if blocked {
+            debug!(tile_pos = ?place_tile_event.tile_pos, "Holding tile placement, tile is occupied");
+            held_placements.0.push(place_tile_event);
+            continue;
+        }
+
+        terrain_data.0[index] = place_tile_event.material;
+        let tile_entity = commands
+            .spawn(tile_bundle(
+                chunk_entity,
+                tile_pos,
+                place_tile_event.material,
+                &material_properties,
+                &terrain_settings,
+            ))
+            .id();
+        commands.entity(chunk_entity).add_child(tile_entity);
+        tile_storage.set(&tile_pos, tile_entity);
+        placed_tiles.send(TilePlacedEvent {
+            entity: tile_entity,
+            chunk: chunk_entity,
+            tile_pos,
+            material: place_tile_event.material,
+        });
     }
-    tile_colliders
 }
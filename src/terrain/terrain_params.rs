@@ -1,72 +1,89 @@
 use bevy::{
     ecs::system::SystemParam,
-    math::Vec3Swizzles,
-    prelude::{Entity, GlobalTransform, Query, Vec2, With},
+    prelude::{Entity, IVec2, Query, Res, Vec2, With},
 };
 use bevy_ecs_tilemap::{
-    prelude::{TilemapGridSize, TilemapSize, TilemapType},
+    prelude::TilemapId,
     tiles::{TilePos, TileStorage},
 };
 
-use super::{Terrain, TerrainData};
+use crate::terrain_settings::TerrainSettings;
 
+use super::{Terrain, TerrainChunk, TerrainChunkIndex, TerrainData};
+
+/// Read access to the tiles of the world, in world tile positions.
+pub trait TerrainTiles {
+    /// Material of the tile at `tile_pos`, `None` when its chunk isn't loaded.
+    fn get_tile(&self, tile_pos: IVec2) -> Option<u16>;
+}
+
+/// Access to the loaded terrain chunks.
+///
+/// Tile positions are world tile positions: chunks are laid out side by side, tile (0, 0) is
+/// the bottom left tile of chunk (0, 0).
+///
+/// Systems using it may run before the terrain settings are loaded, lookups return `None`
+/// until then.
 #[derive(SystemParam)]
 pub struct TerrainParam<'w, 's> {
-    pub terrain_query: Query<
+    pub settings: Option<Res<'w, TerrainSettings>>,
+    pub chunk_index: Res<'w, TerrainChunkIndex>,
+    pub chunk_query: Query<
         'w,
         's,
         (
             Entity,
-            &'static GlobalTransform,
-            &'static TilemapGridSize,
-            &'static TilemapSize,
-            &'static TilemapType,
+            &'static TerrainChunk,
+            &'static TerrainData,
+            &'static TileStorage,
         ),
         With<Terrain>,
     >,
-    pub terrain_data_query: Query<'w, 's, &'static TerrainData, With<Terrain>>,
-    pub tile_storage: Query<'w, 's, &'static TileStorage, With<Terrain>>,
-    pub tile_pos_query: Query<'w, 's, &'static TilePos>,
+    pub tile_pos_query: Query<'w, 's, (&'static TilePos, &'static TilemapId)>,
 }
 
 impl TerrainParam<'_, '_> {
-    pub fn global_to_tile_pos(&self, global_pos: Vec2) -> Option<TilePos> {
-        let Ok((_, terrain_transform, tilemap_grid_size, tilemap_size, tilemap_type)) =
-            self.terrain_query.get_single() else {
-                return None;
-            };
-        let global_pos_to_terrain_pos = terrain_transform
-            .compute_matrix()
-            .inverse()
-            .transform_point3(global_pos.extend(0.))
-            .xy();
-        TilePos::from_world_pos(
-            &global_pos_to_terrain_pos,
-            tilemap_size,
-            tilemap_grid_size,
-            tilemap_type,
-        )
+    /// The loaded chunk at `region`.
+    pub fn get_chunk(&self, region: IVec2) -> Option<(Entity, &TerrainData, &TileStorage)> {
+        let chunk_entity = self.chunk_index.get(region)?;
+        self.chunk_query
+            .get(chunk_entity)
+            .ok()
+            .map(|(entity, _, terrain_data, tile_storage)| (entity, terrain_data, tile_storage))
+    }
+
+    /// The tile covering `global_pos`, `None` when its chunk isn't loaded.
+    pub fn global_to_tile_pos(&self, global_pos: Vec2) -> Option<IVec2> {
+        let settings = self.settings.as_deref()?;
+        let tile_pos = settings.world_to_tile(global_pos);
+        let (region, _) = settings.tile_to_chunk(tile_pos);
+        self.get_chunk(region).map(|_| tile_pos)
+    }
+
+    /// World position of the center of the tile at `tile_pos`.
+    pub fn tile_to_global_pos(&self, tile_pos: IVec2) -> Option<Vec2> {
+        let settings = self.settings.as_deref()?;
+        Some(settings.tile_to_world(tile_pos))
     }
 
-    pub fn tile_to_global_pos(&self, tile_pos: TilePos) -> Vec2 {
-        let (_, terrain_transform, tilemap_grid_size, _tilemap_size, tilemap_type) =
-            self.terrain_query.single();
-        terrain_transform
-            .compute_matrix()
-            .transform_point3(
-                tile_pos
-                    .center_in_world(tilemap_grid_size, tilemap_type)
-                    .extend(0.),
-            )
-            .xy()
+    pub fn get_tile_entity(&self, tile_pos: IVec2) -> Option<Entity> {
+        let (region, chunk_tile_pos) = self.settings.as_deref()?.tile_to_chunk(tile_pos);
+        let (_, _, tile_storage) = self.get_chunk(region)?;
+        tile_storage.get(&chunk_tile_pos)
     }
 
-    pub fn get_tile_entity(&self, tile_pos: TilePos) -> Option<Entity> {
-        let tile_storage = self.tile_storage.single();
-        tile_storage.get(&tile_pos)
+    pub fn get_entity_tile_pos(&self, entity: Entity) -> Option<IVec2> {
+        let (tile_pos, tilemap_id) = self.tile_pos_query.get(entity).ok()?;
+        let (_, chunk, _, _) = self.chunk_query.get(tilemap_id.0).ok()?;
+        let settings = self.settings.as_deref()?;
+        Some(settings.chunk_to_tile(chunk.0, *tile_pos))
     }
+}
 
-    pub fn get_entity_tile_pos(&self, entity: Entity) -> Option<TilePos> {
-        self.tile_pos_query.get(entity).ok().copied()
+impl TerrainTiles for TerrainParam<'_, '_> {
+    fn get_tile(&self, tile_pos: IVec2) -> Option<u16> {
+        let (region, chunk_tile_pos) = self.settings.as_deref()?.tile_to_chunk(tile_pos);
+        let (_, terrain_data, _) = self.get_chunk(region)?;
+        terrain_data.get_tile(chunk_tile_pos.into())
     }
 }
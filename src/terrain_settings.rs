@@ -4,6 +4,7 @@ use bevy::{
     utils::HashMap,
 };
 use bevy_common_assets::ron::RonAssetPlugin;
+use bevy_ecs_tilemap::tiles::TilePos;
 use terrain_gen::TerrainGeneratorSettings;
 
 use crate::material::MaterialProperties;
@@ -58,6 +59,52 @@ pub struct TerrainSettings {
     pub seed: u32,
 }
 
+impl TerrainSettings {
+    /// Size of a terrain chunk in tiles.
+    pub fn chunk_size(&self) -> IVec2 {
+        IVec2::new(self.width as i32, self.height as i32)
+    }
+
+    /// World position of the center of tile (0, 0) in the chunk at `region`.
+    ///
+    /// Chunk (0, 0) is centered on the world origin.
+    pub fn chunk_origin(&self, region: IVec2) -> Vec2 {
+        let chunk_size = self.chunk_size().as_vec2() * self.cell_size;
+        -chunk_size / 2. + 0.5 * self.cell_size + region.as_vec2() * chunk_size
+    }
+
+    /// The chunk containing `tile_pos`, along with the tile's position inside that chunk.
+    pub fn tile_to_chunk(&self, tile_pos: IVec2) -> (IVec2, TilePos) {
+        let chunk_size = self.chunk_size();
+        let region = tile_pos.div_euclid(chunk_size);
+        let local = tile_pos.rem_euclid(chunk_size);
+        (region, TilePos::new(local.x as u32, local.y as u32))
+    }
+
+    /// World tile position of `tile_pos` in the chunk at `region`.
+    pub fn chunk_to_tile(&self, region: IVec2, tile_pos: TilePos) -> IVec2 {
+        region * self.chunk_size() + IVec2::new(tile_pos.x as i32, tile_pos.y as i32)
+    }
+
+    /// World tile position of the tile covering `world_pos`.
+    pub fn world_to_tile(&self, world_pos: Vec2) -> IVec2 {
+        ((world_pos - self.chunk_origin(IVec2::ZERO)) / self.cell_size + 0.5)
+            .floor()
+            .as_ivec2()
+    }
+
+    /// World position of the center of the tile at `tile_pos`.
+    pub fn tile_to_world(&self, tile_pos: IVec2) -> Vec2 {
+        self.chunk_origin(IVec2::ZERO) + tile_pos.as_vec2() * self.cell_size
+    }
+
+    /// Seed for the random features of the chunk at `region`, derived from the world seed.
+    pub fn chunk_seed(&self, region: IVec2) -> u64 {
+        let region_bits = ((region.x as u32 as u64) << 32) | region.y as u32 as u64;
+        ((self.seed as u64) << 16) ^ region_bits
+    }
+}
+
 impl From<TerrainSettings> for TerrainGeneratorSettings {
     fn from(settings: TerrainSettings) -> Self {
         Self {
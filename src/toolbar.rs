@@ -1,9 +1,13 @@
 use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_egui::EguiContexts;
 
-use crate::labor::build_structure::BuildToolState;
+use crate::labor::build_structure::{BuildToolState, SelectedStructure};
+use crate::labor::cancel::CancelToolState;
 use crate::labor::chop_tree::FellingToolState;
 use crate::labor::dig_tile::DigToolState;
+use crate::labor::farm::FarmToolState;
+use crate::labor::stockpile::StockpileToolState;
+use crate::structure::StructureDefinitions;
 
 pub struct ToolbarPlugin;
 
@@ -15,8 +19,12 @@ impl Plugin for ToolbarPlugin {
 
 enum Tool {
     Dig,
-    Build,
+    /// Places the structure with the given name.
+    Build(String),
     Chop,
+    Stockpile,
+    Farm,
+    Cancel,
 }
 
 #[derive(SystemParam)]
@@ -24,19 +32,41 @@ struct ToolStates<'w> {
     dig_tool_next_state: ResMut<'w, NextState<DigToolState>>,
     build_tool_next_state: ResMut<'w, NextState<BuildToolState>>,
     chop_tool_next_state: ResMut<'w, NextState<FellingToolState>>,
+    stockpile_tool_next_state: ResMut<'w, NextState<StockpileToolState>>,
+    farm_tool_next_state: ResMut<'w, NextState<FarmToolState>>,
+    cancel_tool_next_state: ResMut<'w, NextState<CancelToolState>>,
+    selected_structure: ResMut<'w, SelectedStructure>,
 }
 
-fn toolbar(mut contexts: EguiContexts, mut tool_states: ToolStates) {
+fn toolbar(
+    mut contexts: EguiContexts,
+    mut tool_states: ToolStates,
+    structure_definitions: Option<Res<StructureDefinitions>>,
+) {
     egui::Window::new("Toolbar").show(contexts.ctx_mut(), |ui| {
         if ui.button("Dig").clicked() {
             switch_to_tool(&mut tool_states, Tool::Dig)
         }
-        if ui.button("Build").clicked() {
-            switch_to_tool(&mut tool_states, Tool::Build)
+        for definition in structure_definitions
+            .iter()
+            .flat_map(|definitions| &definitions.0)
+        {
+            if ui.button(format!("Build {}", definition.name)).clicked() {
+                switch_to_tool(&mut tool_states, Tool::Build(definition.name.clone()))
+            }
         }
         if ui.button("Chop tree").clicked() {
             switch_to_tool(&mut tool_states, Tool::Chop)
         }
+        if ui.button("Stockpile").clicked() {
+            switch_to_tool(&mut tool_states, Tool::Stockpile)
+        }
+        if ui.button("Farm plot").clicked() {
+            switch_to_tool(&mut tool_states, Tool::Farm)
+        }
+        if ui.button("Cancel").clicked() {
+            switch_to_tool(&mut tool_states, Tool::Cancel)
+        }
     });
 }
 
@@ -48,6 +78,15 @@ fn clear_active_tool(tool_states: &mut ToolStates) {
     tool_states
         .chop_tool_next_state
         .set(FellingToolState::Inactive);
+    tool_states
+        .stockpile_tool_next_state
+        .set(StockpileToolState::Inactive);
+    tool_states
+        .farm_tool_next_state
+        .set(FarmToolState::Inactive);
+    tool_states
+        .cancel_tool_next_state
+        .set(CancelToolState::Inactive);
 }
 
 fn switch_to_tool(tool_states: &mut ToolStates, tool: Tool) {
@@ -57,11 +96,23 @@ fn switch_to_tool(tool_states: &mut ToolStates, tool: Tool) {
         Tool::Dig => tool_states
             .dig_tool_next_state
             .set(DigToolState::Designating),
-        Tool::Build => tool_states
-            .build_tool_next_state
-            .set(BuildToolState::Placing),
+        Tool::Build(structure) => {
+            tool_states.selected_structure.0 = Some(structure);
+            tool_states
+                .build_tool_next_state
+                .set(BuildToolState::Placing)
+        }
         Tool::Chop => tool_states
             .chop_tool_next_state
             .set(FellingToolState::Designating),
+        Tool::Stockpile => tool_states
+            .stockpile_tool_next_state
+            .set(StockpileToolState::Designating),
+        Tool::Farm => tool_states
+            .farm_tool_next_state
+            .set(FarmToolState::Designating),
+        Tool::Cancel => tool_states
+            .cancel_tool_next_state
+            .set(CancelToolState::Canceling),
     }
 }
@@ -1,4 +1,5 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_ecs_tilemap::tiles::TilePos;
 use bevy_rapier2d::prelude::{
     Collider, CollisionGroups, Group, QueryFilter, RapierContext, RigidBody,
 };
@@ -6,11 +7,10 @@ use rand::{seq::IteratorRandom, SeedableRng};
 use rand_xoshiro::Xoshiro256StarStar;
 
 use crate::{
-    building_material::BuildingMaterial,
     health::Health,
+    item::{spawn_item, ItemDefinitions},
     labor::chop_tree::PICKER_COLLISION_GROUP,
-    main_state::MainState,
-    terrain::{TerrainSet, TERRAIN_COLLISION_GROUP},
+    terrain::{GeneratedTerrain, TerrainChunk, TerrainData, TERRAIN_COLLISION_GROUP},
     terrain_settings::TerrainSettings,
 };
 
@@ -18,50 +18,56 @@ pub struct TreePlugin;
 
 impl Plugin for TreePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<TreesState>()
-            .add_event::<TreeDestroyedEvent>()
-            .add_systems(OnEnter(MainState::Game), spawn_trees.after(TerrainSet))
-            .add_systems(Update, destroy_trees);
+        app.add_event::<TreeDestroyedEvent>()
+            .add_systems(
+                Update,
+                spawn_trees.run_if(resource_exists::<TerrainSettings>()),
+            )
+            .add_systems(
+                Update,
+                destroy_trees.run_if(resource_exists::<ItemDefinitions>()),
+            );
     }
 }
 
-#[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
-pub enum TreesState {
-    #[default]
-    Spawning,
-    Spawned,
-}
+/// Trees spawned on every newly generated chunk the surface runs through.
+pub const TREES_PER_CHUNK: usize = 25;
 
+/// Spawns trees on the surface of newly generated chunks.
+///
+/// Trees grow on every third column, away from the embark site at the center of the world.
 fn spawn_trees(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     terrain_settings: Res<TerrainSettings>,
-    rapier_context: Res<RapierContext>,
-    mut trees_state: ResMut<NextState<TreesState>>,
+    new_chunk_query: Query<(&TerrainChunk, &TerrainData), Added<GeneratedTerrain>>,
 ) {
-    let mut rng = Xoshiro256StarStar::seed_from_u64(terrain_settings.seed as u64);
-    let terrain_half_width = terrain_settings.width as f32 / 2.0 / 3.;
-    let possible_x_pos: Vec<f32> = (-terrain_half_width as i32..=terrain_half_width as i32)
-        .filter(|x| (*x).abs() > 2)
-        .map(|x| x as f32 * 3. * terrain_settings.cell_size)
-        .choose_multiple(&mut rng, 25);
-    for x in possible_x_pos {
-        // pick location within the 10 center cells of the map
-        // y location is always the top of the map
-        let y = terrain_settings.cell_size * terrain_settings.height as f32 / 2.0;
-
-        let ray_dir = Vec2::new(0.0, -1.0);
-        let max_toi = terrain_settings.cell_size * terrain_settings.height as f32;
-        let filter = QueryFilter::default();
-
-        if let Some((_entity, hit)) =
-            rapier_context.cast_ray(Vec2::new(x, y), ray_dir, max_toi, true, filter)
-        {
-            spawn_tree(&mut commands, x, y - hit, &mut materials, &mut meshes);
+    for (chunk, terrain_data) in &new_chunk_query {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(terrain_settings.chunk_seed(chunk.0));
+        let tree_positions = (0..terrain_settings.width)
+            .filter_map(|x| {
+                let surface_tile = terrain_data.surface_tile(x)?;
+                let tile_pos = terrain_settings
+                    .chunk_to_tile(chunk.0, TilePos::new(surface_tile.x, surface_tile.y));
+                let position = terrain_settings.tile_to_world(tile_pos)
+                    + Vec2::new(0., terrain_settings.cell_size / 2.);
+                (tile_pos.x.rem_euclid(3) == 0
+                    && position.x.abs() > 6. * terrain_settings.cell_size)
+                    .then_some(position)
+            })
+            .choose_multiple(&mut rng, TREES_PER_CHUNK);
+        debug!(chunk = ?chunk.0, trees = tree_positions.len(), "Spawning trees");
+        for position in tree_positions {
+            spawn_tree(
+                &mut commands,
+                position.x,
+                position.y,
+                &mut materials,
+                &mut meshes,
+            );
         }
     }
-    trees_state.set(TreesState::Spawned);
 }
 
 #[derive(Component)]
@@ -69,13 +75,13 @@ pub struct Tree;
 
 pub const TREE_COLLISION_GROUP: Group = Group::GROUP_3;
 
-fn spawn_tree(
+pub fn spawn_tree(
     commands: &mut Commands,
     x: f32,
     y: f32,
     materials: &mut ResMut<Assets<ColorMaterial>>,
     meshes: &mut ResMut<Assets<Mesh>>,
-) {
+) -> Entity {
     let tree_size = Vec2::new(16., 180.);
     commands
         .spawn((
@@ -101,7 +107,8 @@ fn spawn_tree(
                 ),
                 Collider::cuboid(tree_size.x / 2., tree_size.y / 2.),
             ));
-        });
+        })
+        .id()
 }
 
 #[derive(Event)]
@@ -109,19 +116,23 @@ pub struct TreeDestroyedEvent {
     pub tree: Entity,
 }
 
-#[derive(Component)]
-pub struct Log;
-
 pub const OBJECT_COLLISION_GROUP: Group = Group::GROUP_5;
+/// Item dropped by felled trees.
+pub const LOG_ITEM: &str = "Log";
 
 fn destroy_trees(
     mut commands: Commands,
     tree_health_query: Query<(&Health, &GlobalTransform, Entity), With<Tree>>,
     mut tree_destroyed_events: EventWriter<TreeDestroyedEvent>,
     rapier_context: Res<RapierContext>,
+    item_definitions: Res<ItemDefinitions>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
 ) {
+    let Some(log_definition) = item_definitions.get(LOG_ITEM) else {
+        error!("Item {} is not defined", LOG_ITEM);
+        return;
+    };
     for (tree_health, tree_transform, tree_entity) in &tree_health_query {
         if tree_health.0 == 0 {
             info!(tree = ?tree_entity, "Tree destroyed");
@@ -138,27 +149,17 @@ fn destroy_trees(
             if let Some(intersection) =
                 rapier_context.cast_ray_and_get_normal(ray_origin, ray_dir, max_toi, solid, filter)
             {
-                let log_size = Vec2::new(15., 10.);
-                let log_entity = commands
-                    .spawn((
-                        Log,
-                        Name::new("Log"),
-                        BuildingMaterial,
-                        MaterialMesh2dBundle {
-                            transform: Transform::from_xyz(
-                                intersection.1.point.x,
-                                intersection.1.point.y + log_size.y / 2.,
-                                2.,
-                            ),
-                            material: materials.add(Color::rgb(0.29, 0.196, 0.101).into()),
-                            mesh: meshes.add(Mesh::from(shape::Quad::new(log_size))).into(),
-                            ..default()
-                        },
-                        RigidBody::Fixed,
-                        Collider::cuboid(log_size.x / 2., log_size.y / 2.),
-                        CollisionGroups::new(OBJECT_COLLISION_GROUP, TERRAIN_COLLISION_GROUP),
-                    ))
-                    .id();
+                let log_entity = spawn_item(
+                    &mut commands,
+                    log_definition,
+                    1,
+                    Vec2::new(
+                        intersection.1.point.x,
+                        intersection.1.point.y + log_definition.size.y / 2.,
+                    ),
+                    &mut materials,
+                    &mut meshes,
+                );
                 info!(log = ?log_entity, "Log spawned");
             }
         }
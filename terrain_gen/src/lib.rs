@@ -81,18 +81,20 @@ impl NoiseFn<f64, 2> for PlaneNoise {
     }
 }
 
-pub fn generate_terrain(
+type OreDeposit = ([f64; 2], u16, Arc<Box<dyn NoiseFn<f64, 2> + Send + Sync>>);
+
+/// How far outside its region an ore deposit can reach: the deposit radius plus the
+/// displacement of its turbulence.
+const ORE_DEPOSIT_REACH: f64 = 5. + 10.;
+
+/// Ore deposits seeded for a single region, in coordinates local to that region.
+///
+/// Deposits near the region border reach into the neighbouring regions, so
+/// [`generate_terrain`] evaluates the deposits of all adjacent regions as well.
+fn ore_deposits(
     region_location: IVec2,
-    generator: GeneratorFunction,
-    terrain_settings: TerrainGeneratorSettings,
-) -> Array2<u16> {
-    let mut terrain = Array2::from_elem(
-        (
-            terrain_settings.width as usize,
-            terrain_settings.height as usize,
-        ),
-        0u16,
-    );
+    terrain_settings: &TerrainGeneratorSettings,
+) -> Vec<OreDeposit> {
     let useed = terrain_settings.seed as u64;
     let mut hasher: AHasher = RandomState::with_seeds(
         useed,
@@ -119,16 +121,18 @@ pub fn generate_terrain(
         .iter()
         .choose_multiple(&mut rng, 100);
 
-    let ore_types = ore_locations
+    // Sort so the weighted choice below doesn't depend on the hash map's iteration order
+    let mut ore_incidences = terrain_settings
+        .ore_incidences
         .iter()
-        .map(|point| {
-            let ore_types = terrain_settings
-                .ore_incidences
-                .iter()
-                .map(|(ore, inc)| (*ore, *inc))
-                .collect::<Vec<_>>();
+        .map(|(ore, inc)| (*ore, *inc))
+        .collect::<Vec<_>>();
+    ore_incidences.sort_by_key(|(ore, _)| *ore);
 
-            let ore_type = ore_types
+    ore_locations
+        .iter()
+        .map(|point| {
+            let ore_type = ore_incidences
                 .choose_weighted(&mut rng, |item| item.1)
                 .unwrap()
                 .0;
@@ -146,9 +150,56 @@ pub fn generate_terrain(
             let ore_turbulence_function: Arc<Box<dyn NoiseFn<f64, 2> + Send + Sync>> =
                 Arc::new(Box::new(ore_turbulence));
 
-            (ore_type, ore_turbulence_function)
+            (*point, ore_type, ore_turbulence_function)
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+/// Generates the tiles of the region at `region_location`.
+///
+/// Regions are `width` by `height` tiles. Neighbouring regions line up seamlessly, both the
+/// terrain noise and the ore deposits are continuous across region borders.
+pub fn generate_terrain(
+    region_location: IVec2,
+    generator: GeneratorFunction,
+    terrain_settings: TerrainGeneratorSettings,
+) -> Array2<u16> {
+    let mut terrain = Array2::from_elem(
+        (
+            terrain_settings.width as usize,
+            terrain_settings.height as usize,
+        ),
+        0u16,
+    );
+    let region_size = IVec2::new(
+        terrain_settings.width as i32,
+        terrain_settings.height as i32,
+    );
+
+    // Deposits of this region and its neighbours, ordered by region so overlapping deposits
+    // resolve the same way no matter which region is being generated
+    let mut ore_types = Vec::new();
+    for y in -1..=1 {
+        for x in -1..=1 {
+            let offset = IVec2::new(x, y);
+            let deposits = ore_deposits(region_location + offset, &terrain_settings);
+            let deposit_offset = offset * region_size;
+            ore_types.extend(
+                deposits
+                    .into_iter()
+                    .filter(|(location, _, _)| {
+                        // Skip neighbouring deposits that can't reach into this region
+                        let x = location[0] + deposit_offset.x as f64;
+                        let y = location[1] + deposit_offset.y as f64;
+                        x > -ORE_DEPOSIT_REACH
+                            && x < region_size.x as f64 + ORE_DEPOSIT_REACH
+                            && y > -ORE_DEPOSIT_REACH
+                            && y < region_size.y as f64 + ORE_DEPOSIT_REACH
+                    })
+                    .map(|(_, ore_type, noise)| (deposit_offset, ore_type, noise)),
+            );
+        }
+    }
 
     for x in 0..terrain_settings.width as usize {
         for y in 0..terrain_settings.height as usize {
@@ -157,13 +208,17 @@ pub fn generate_terrain(
                 (region_location.y * terrain_settings.height as i32 + y as i32).into(),
             ]);
 
-            let ore_type = ore_types.iter().fold(None, |acc, (ore_type, noise)| {
-                if noise.get([x as f64, y as f64]) > 0. {
-                    Some(ore_type)
-                } else {
-                    acc
-                }
-            });
+            let ore_type = ore_types
+                .iter()
+                .fold(None, |acc, (deposit_offset, ore_type, noise)| {
+                    let local_x = x as i32 - deposit_offset.x;
+                    let local_y = y as i32 - deposit_offset.y;
+                    if noise.get([local_x as f64, local_y as f64]) > 0. {
+                        Some(ore_type)
+                    } else {
+                        acc
+                    }
+                });
             terrain[[x, y]] = if noise > 0. {
                 if let Some(ore) = ore_type {
                     *ore
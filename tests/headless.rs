@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use basebuilder::{
+    dwarf::Dwarf,
+    embark::EmbarkSettings,
+    main_state::MainState,
+    save::{read_save_game, write_save_game, LoadGameRequest, SaveGame, SaveGameEvent, SavedChunk},
+    terrain_settings::TerrainSettings,
+    HeadlessPlugin, SimulationPlugins,
+};
+
+/// Frames the assets get to load and the map to generate in.
+const MAX_LOADING_FRAMES: usize = 2000;
+/// Frames the colony is simulated for once the game started, 10 seconds of game time.
+const SIMULATED_FRAMES: usize = 600;
+
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugin, SimulationPlugins));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn in_game(app: &App) -> bool {
+    *app.world.resource::<State<MainState>>().get() == MainState::Game
+}
+
+/// Runs the app until the game started and the entities spawned when entering it exist.
+fn start_game(app: &mut App) {
+    for _ in 0..MAX_LOADING_FRAMES {
+        if in_game(app) {
+            break;
+        }
+        app.update();
+    }
+    assert!(in_game(app), "Game should start after loading");
+    // Let the spawn commands of entering the game apply
+    app.update();
+}
+
+fn save_and_read(app: &mut App, path: &Path) -> SaveGame {
+    app.world
+        .resource_mut::<Events<SaveGameEvent>>()
+        .send(SaveGameEvent {
+            path: path.to_path_buf(),
+        });
+    app.update();
+    read_save_game(path).expect("Saved game should be readable")
+}
+
+fn temp_save_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("basebuilder-{}-{}.ron", name, std::process::id()))
+}
+
+#[test]
+fn starting_party_survives_on_the_surface() {
+    let mut app = headless_app();
+    start_game(&mut app);
+
+    let dwarves = app.world.resource::<EmbarkSettings>().dwarves;
+    let mut dwarf_query = app.world.query_filtered::<Entity, With<Dwarf>>();
+    assert_eq!(dwarf_query.iter(&app.world).count(), dwarves);
+
+    for _ in 0..SIMULATED_FRAMES {
+        app.update();
+    }
+
+    let terrain_settings = app.world.resource::<TerrainSettings>().clone();
+    let bottom = -terrain_settings.cell_size * terrain_settings.height as f32 / 2.;
+    let mut dwarf_query = app.world.query_filtered::<&GlobalTransform, With<Dwarf>>();
+    let positions: Vec<Vec3> = dwarf_query
+        .iter(&app.world)
+        .map(|transform| transform.translation())
+        .collect();
+    assert_eq!(positions.len(), dwarves, "No dwarf should have died");
+    for position in positions {
+        assert!(
+            position.y > bottom,
+            "Dwarf at {position} fell out of the world"
+        );
+    }
+}
+
+#[test]
+fn saved_game_is_restored() {
+    let mut app = headless_app();
+    start_game(&mut app);
+    let generated_path = temp_save_path("generated");
+    let mut save_game = save_and_read(&mut app, &generated_path);
+
+    // Damage and designate the first solid tile, give the first dwarf something to carry
+    let (width, height) = (save_game.terrain.width, save_game.terrain.height);
+    let solid_tile = save_game
+        .terrain
+        .chunks
+        .iter()
+        .find_map(|chunk| {
+            let index = chunk.tiles.iter().position(|tile| *tile != 0)?;
+            let local = IVec2::new(
+                (index as u32 / height) as i32,
+                (index as u32 % height) as i32,
+            );
+            Some(chunk.region * IVec2::new(width as i32, height as i32) + local)
+        })
+        .expect("Generated terrain should have solid tiles");
+    save_game.terrain.tile_health.push((solid_tile, 1));
+    save_game.dig_designations.push((solid_tile, Some(3)));
+    save_game.dwarves[0].inventory = vec![("Coal".to_string(), 1)];
+    let edited_path = temp_save_path("edited");
+    write_save_game(&edited_path, &save_game).expect("Edited game should be writable");
+
+    let mut app = headless_app();
+    app.insert_resource(LoadGameRequest(edited_path.clone()));
+    start_game(&mut app);
+    let restored_path = temp_save_path("restored");
+    let restored = save_and_read(&mut app, &restored_path);
+
+    for path in [generated_path, edited_path, restored_path] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    let chunk_tiles = |chunks: &[SavedChunk]| {
+        let mut chunk_tiles: Vec<_> = chunks
+            .iter()
+            .map(|chunk| ((chunk.region.x, chunk.region.y), chunk.tiles.clone()))
+            .collect();
+        chunk_tiles.sort();
+        chunk_tiles
+    };
+    let restored_chunks = chunk_tiles(&restored.terrain.chunks);
+    for chunk in chunk_tiles(&save_game.terrain.chunks) {
+        assert!(
+            restored_chunks.contains(&chunk),
+            "Chunk {:?} should be restored",
+            chunk.0
+        );
+    }
+    assert!(restored.terrain.tile_health.contains(&(solid_tile, 1)));
+    assert!(restored.dig_designations.contains(&(solid_tile, Some(3))));
+
+    let dwarves = |save_game: &SaveGame| {
+        let mut dwarves: Vec<_> = save_game
+            .dwarves
+            .iter()
+            .map(|dwarf| (dwarf.name.clone(), dwarf.inventory.clone()))
+            .collect();
+        dwarves.sort();
+        dwarves
+    };
+    assert_eq!(dwarves(&restored), dwarves(&save_game));
+}